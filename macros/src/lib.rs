@@ -1,17 +1,20 @@
 //! Please see the `inline-c` crate to learn more.
 
+mod manifest;
+mod syntax;
+
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use std::env;
 
 /// Execute a C program and return a `Result` of
 /// `wasmer_inline_c::Assert`. See examples inside the `inline-c` crate.
 #[proc_macro]
 pub fn assert_c(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = TokenStream::from(input);
-    let input_as_string = reconstruct(input);
-
-    quote!(
-        wasmer_inline_c::run(wasmer_inline_c::Language::C, #input_as_string).map_err(|e| panic!("{}", e)).unwrap()
+    expand(
+        TokenStream::from(input),
+        "c",
+        quote!(wasmer_inline_c::Language::C),
     )
     .into()
 }
@@ -20,15 +23,525 @@ pub fn assert_c(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 /// `wasmer_inline_c::Assert`. See examples inside the `inline-c` crate.
 #[proc_macro]
 pub fn assert_cxx(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let input = TokenStream::from(input);
-    let input_as_string = reconstruct(input);
+    expand(
+        TokenStream::from(input),
+        "cpp",
+        quote!(wasmer_inline_c::Language::Cxx),
+    )
+    .into()
+}
 
-    quote!(
-        wasmer_inline_c::run(wasmer_inline_c::Language::Cxx, #input_as_string).map_err(|e| panic!("{}", e)).unwrap()
+/// Assemble an assembly program, link it with a C driver calling its
+/// `inline_c_main` function, run it, and return a `Result` of
+/// `wasmer_inline_c::Assert`. The program is given in a string
+/// literal, and the driver can be replaced with the `driver = "…"`
+/// option. See examples inside the `inline-c` crate.
+#[proc_macro]
+pub fn assert_asm(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    expand(
+        TokenStream::from(input),
+        "S",
+        quote!(wasmer_inline_c::Language::Assembler),
     )
     .into()
 }
 
+/// Execute a C program, given in a string literal, e.g. a raw string
+/// literal `r#"…"#`, compiled byte for byte as written, and return a
+/// `Result` of `wasmer_inline_c::Assert`. The options of `assert_c!`
+/// are supported, before the program.
+#[proc_macro]
+pub fn assert_c_str(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    expand_str(
+        TokenStream::from(input),
+        "c",
+        quote!(wasmer_inline_c::Language::C),
+    )
+    .into()
+}
+
+/// Execute a C++ program, given in a string literal, e.g. a raw string
+/// literal `r#"…"#`, compiled byte for byte as written, and return a
+/// `Result` of `wasmer_inline_c::Assert`. The options of `assert_cxx!`
+/// are supported, before the program.
+#[proc_macro]
+pub fn assert_cxx_str(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    expand_str(
+        TokenStream::from(input),
+        "cpp",
+        quote!(wasmer_inline_c::Language::Cxx),
+    )
+    .into()
+}
+
+/// Expand a program which must be given in a string literal.
+fn expand_str(input: TokenStream, extension: &str, language: TokenStream) -> TokenStream {
+    let (_, program) = split_options(input.clone());
+    let tokens = program.into_iter().collect::<Vec<_>>();
+
+    match tokens.as_slice() {
+        [proc_macro2::TokenTree::Literal(literal)]
+            if literal.to_string().starts_with(['"', 'r']) =>
+        {
+            expand(input, extension, language)
+        }
+        tokens => {
+            let span = tokens
+                .first()
+                .map_or_else(proc_macro2::Span::call_site, |token| token.span());
+
+            quote_spanned!(span=> compile_error!(
+                "expected the program in a string literal, e.g. `r#\"…\"#`"
+            ))
+        }
+    }
+}
+
+fn expand(input: TokenStream, extension: &str, language: TokenStream) -> TokenStream {
+    let (options, input) = split_options(input);
+    let tokens = input.clone().into_iter().collect::<Vec<_>>();
+
+    // A program in a string literal is taken as it is, e.g. assembly,
+    // and a program written with tokens is taken from the source file,
+    // exactly as written. The tokens are reconstructed only when the
+    // compiler does not provide their source.
+    let input_as_string = match tokens.as_slice() {
+        [proc_macro2::TokenTree::Literal(literal)] => match string_value(literal) {
+            Ok(value) => value,
+            Err(message) => {
+                return quote_spanned!(literal.span()=> compile_error!(#message));
+            }
+        },
+        _ => source_text(&input).unwrap_or_else(|| reconstruct(input.clone())),
+    };
+
+    // The syntax of assembly depends on the assembler, e.g. `#` starts
+    // the comments of x86 with GAS.
+    if extension != "S" {
+        let error = match tokens.as_slice() {
+            [proc_macro2::TokenTree::Literal(literal)] => {
+                syntax::check(&input_as_string).err().map(|error| {
+                    (
+                        literal.span(),
+                        format!(
+                            "{}, at line {}, column {} of the program",
+                            error.message, error.line, error.column
+                        ),
+                    )
+                })
+            }
+            _ => syntax::check_tokens(input).err(),
+        };
+
+        if let Some((span, message)) = error {
+            return quote_spanned!(span=> compile_error!(#message));
+        }
+    }
+
+    manifest::record(extension, &input_as_string);
+
+    // The name of the test target, e.g. `integration` for
+    // `tests/integration.rs`, scoping the variables.
+    let target = match env::var("CARGO_CRATE_NAME") {
+        Ok(target) => quote!(Some(#target)),
+        Err(_) => quote!(None),
+    };
+    let manifest_dir = match env::var("CARGO_MANIFEST_DIR") {
+        Ok(directory) => quote!(Some(#directory)),
+        Err(_) => quote!(None),
+    };
+    let (names, values): (Vec<_>, Vec<_>) = options.template.into_iter().unzip();
+
+    let name = match &options.name {
+        Some(name) => quote!(Some(#name)),
+        None => quote!(None),
+    };
+    let driver = match &options.driver {
+        Some(driver) => quote!(Some(#driver)),
+        None => quote!(None),
+    };
+    let run_options = quote!(
+        &wasmer_inline_c::run::Options {
+            target: #target,
+            template: &[#((#names, #values)),*],
+            name: #name,
+            driver: #driver,
+            manifest_dir: #manifest_dir,
+        }
+    );
+    let run = quote!(
+        wasmer_inline_c::run::run_with(
+            #language,
+            #input_as_string,
+            #run_options,
+        ).map_err(|e| panic!("{}", e)).unwrap()
+    );
+
+    // The assertions which must be skipped return early from the test.
+    let mut guards = Vec::new();
+
+    // The snippets not selected by `INLINE_C_RS_ONLY`.
+    if let Some(name) = &options.name {
+        guards.push(quote!(
+            if !wasmer_inline_c::run::is_selected(#name) {
+                return wasmer_inline_c::run::Skip::skip();
+            }
+        ));
+    }
+
+    // The snippets with a tag of `INLINE_C_RS_SKIP_TAGS`.
+    if !options.tags.is_empty() {
+        let tags = &options.tags;
+
+        guards.push(quote!(
+            if let Some(tag) = wasmer_inline_c::run::skipped_tag(&[#(#tags),*]) {
+                eprintln!("Skipped: the tag `{}` is skipped by `INLINE_C_RS_SKIP_TAGS`", tag);
+
+                return wasmer_inline_c::run::Skip::skip();
+            }
+        ));
+    }
+
+    // The snippets whose `SKIP_IF_ENV` directive names a variable set
+    // in the environment, or whose `REQUIRES` directive is not
+    // satisfied by the compiler. Without them, the assertion is not
+    // guarded, so that the enclosing function can return anything, e.g.
+    // the `Assert`.
+    if input_as_string.contains("#inline_c_rs SKIP_IF_ENV")
+        || input_as_string.contains("#inline_c_rs REQUIRES")
+    {
+        guards.push(quote!(
+            if let Some(reason) = wasmer_inline_c::run::skip_reason(
+                #language,
+                #input_as_string,
+                #run_options,
+            ) {
+                eprintln!("Skipped: {}", reason);
+
+                return wasmer_inline_c::run::Skip::skip();
+            }
+        ));
+    }
+
+    if guards.is_empty() {
+        run
+    } else {
+        quote!({
+            #(#guards)*
+
+            #run
+        })
+    }
+}
+
+/// The options written before the program.
+#[derive(Default)]
+struct Options {
+    /// `name = "value"`.
+    name: Option<proc_macro2::Literal>,
+
+    /// `template(name = "value", …)`.
+    template: Vec<(String, proc_macro2::Literal)>,
+
+    /// `driver = "value"`.
+    driver: Option<proc_macro2::Literal>,
+
+    /// `tags = ["value", …]`.
+    tags: Vec<proc_macro2::Literal>,
+}
+
+/// Split the options, `name = "value"`, `template(name = "value", …)`,
+/// `driver = "value"` and `tags = ["value", …]`, followed by the
+/// program in braces or in a string literal, from the input. Without
+/// them, the whole input is the program.
+fn split_options(input: TokenStream) -> (Options, TokenStream) {
+    use proc_macro2::{Delimiter, TokenTree::*};
+
+    let tokens = input.clone().into_iter().collect::<Vec<_>>();
+
+    let (options, program) = match tokens.as_slice() {
+        [options @ .., Punct(comma), program]
+            if comma.as_char() == ','
+                && matches!(
+                    options.first(),
+                    Some(Ident(option))
+                        if *option == "name"
+                            || *option == "template"
+                            || *option == "driver"
+                            || *option == "tags"
+                ) =>
+        {
+            match program {
+                Group(program) if program.delimiter() == Delimiter::Brace => {
+                    (options, program.stream())
+                }
+                Literal(_) => (options, TokenStream::from(program.clone())),
+                _ => return (Options::default(), input),
+            }
+        }
+        _ => return (Options::default(), input),
+    };
+
+    let mut parsed = Options::default();
+    let mut options = options.iter().cloned();
+
+    loop {
+        match (options.next(), options.next()) {
+            (Some(Ident(option)), Some(Punct(equal)))
+                if option == "name" && equal.as_char() == '=' =>
+            {
+                match options.next() {
+                    Some(Literal(name)) => parsed.name = Some(name),
+                    token => panic!(
+                        "Invalid `name` option, expected `name = \"value\"`, received `{:?}`.",
+                        token
+                    ),
+                }
+            }
+            (Some(Ident(option)), Some(Punct(equal)))
+                if option == "driver" && equal.as_char() == '=' =>
+            {
+                match options.next() {
+                    Some(Literal(driver)) => parsed.driver = Some(driver),
+                    token => panic!(
+                        "Invalid `driver` option, expected `driver = \"value\"`, received `{:?}`.",
+                        token
+                    ),
+                }
+            }
+            (Some(Ident(option)), Some(Punct(equal)))
+                if option == "tags" && equal.as_char() == '=' =>
+            {
+                match options.next() {
+                    Some(Group(tags)) if tags.delimiter() == Delimiter::Bracket => {
+                        parsed.tags = split_tags(tags.stream())
+                    }
+                    token => panic!(
+                        "Invalid `tags` option, expected `tags = [\"value\", …]`, received `{:?}`.",
+                        token
+                    ),
+                }
+            }
+            (Some(Ident(option)), Some(Group(arguments)))
+                if option == "template" && arguments.delimiter() == Delimiter::Parenthesis =>
+            {
+                parsed.template = split_template(arguments.stream());
+            }
+            (None, _) => break,
+            (token, ..) => panic!(
+                "Invalid option, expected `name = \"value\"`, `template(…)`, `driver = \"value\"` or `tags = [\"value\", …]`, received `{:?}`.",
+                token
+            ),
+        }
+
+        match options.next() {
+            Some(Punct(comma)) if comma.as_char() == ',' => (),
+            None => break,
+            token => panic!("Expected `,` between options, received `{:?}`.", token),
+        }
+    }
+
+    (parsed, program)
+}
+
+/// Parse the `name = "value", …` arguments of the `template` option.
+fn split_template(arguments: TokenStream) -> Vec<(String, proc_macro2::Literal)> {
+    use proc_macro2::TokenTree::*;
+
+    let mut template = Vec::new();
+    let mut arguments = arguments.into_iter();
+
+    loop {
+        match (arguments.next(), arguments.next(), arguments.next()) {
+            (Some(Ident(name)), Some(Punct(equal)), Some(Literal(value)))
+                if equal.as_char() == '=' =>
+            {
+                template.push((name.to_string(), value));
+            }
+            (None, _, _) => break,
+            (token, ..) => panic!(
+                "Invalid `template` argument, expected `name = \"value\"`, received `{:?}`.",
+                token
+            ),
+        }
+
+        match arguments.next() {
+            Some(Punct(comma)) if comma.as_char() == ',' => (),
+            None => break,
+            token => panic!(
+                "Expected `,` between `template` arguments, received `{:?}`.",
+                token
+            ),
+        }
+    }
+
+    template
+}
+
+/// Parse the `"value", …` tags of the `tags` option.
+fn split_tags(tags: TokenStream) -> Vec<proc_macro2::Literal> {
+    use proc_macro2::TokenTree::*;
+
+    let mut parsed = Vec::new();
+    let mut tags = tags.into_iter();
+
+    loop {
+        match tags.next() {
+            Some(Literal(tag)) => parsed.push(tag),
+            None => break,
+            token => panic!(
+                "Invalid tag, expected a string literal, received `{:?}`.",
+                token
+            ),
+        }
+
+        match tags.next() {
+            Some(Punct(comma)) if comma.as_char() == ',' => (),
+            None => break,
+            token => panic!("Expected `,` between tags, received `{:?}`.", token),
+        }
+    }
+
+    parsed
+}
+
+/// The value of the string `literal`, raw or not, or the error of an
+/// invalid escape.
+fn string_value(literal: &proc_macro2::Literal) -> Result<String, String> {
+    let source = literal.to_string();
+
+    if let Some(raw) = source.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+
+        return Ok(raw[hashes + 1..raw.len() - hashes - 1].to_string());
+    }
+
+    let content = match source.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(content) => content,
+        None => return Err(format!("expected a string literal, received `{}`", source)),
+    };
+    let mut value = String::with_capacity(content.len());
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some('0') => value.push('\0'),
+            Some(c @ ('\\' | '"' | '\'')) => value.push(c),
+
+            // An ASCII character, e.g. `\x41`.
+            Some('x') => {
+                let digits = chars.as_str().get(..2).unwrap_or_default();
+
+                match u8::from_str_radix(digits, 16) {
+                    Ok(byte) if byte.is_ascii() && !digits.starts_with('+') => {
+                        value.push(char::from(byte));
+                        chars = chars.as_str()[2..].chars();
+                    }
+                    _ => return Err(format!("invalid escape `\\x{}` in the program", digits)),
+                }
+            }
+
+            // A Unicode character, e.g. `\u{00e9}`.
+            Some('u') => {
+                let rest = chars.as_str();
+                let escape = rest
+                    .strip_prefix('{')
+                    .and_then(|rest| rest.find('}').map(|end| &rest[..end]));
+                let character = escape
+                    .map(|digits| digits.replace('_', ""))
+                    .filter(|digits| (1..=6).contains(&digits.len()))
+                    .and_then(|digits| u32::from_str_radix(&digits, 16).ok())
+                    .and_then(char::from_u32);
+
+                match (escape, character) {
+                    (Some(escape), Some(character)) => {
+                        value.push(character);
+                        chars = rest[escape.len() + 2..].chars();
+                    }
+                    _ => return Err("invalid escape `\\u` in the program".to_string()),
+                }
+            }
+
+            // A line continuation skips the line break, `\n` or
+            // `\r\n`, and the indentation of the next line.
+            Some('\n') => chars = chars.as_str().trim_start().chars(),
+            Some('\r') if chars.as_str().starts_with('\n') => {
+                chars = chars.as_str().trim_start().chars()
+            }
+
+            Some(c) => return Err(format!("unsupported escape `\\{}` in the program", c)),
+            None => unreachable!("a string literal cannot end with `\\`"),
+        }
+    }
+
+    Ok(value)
+}
+
+/// The source of the program written with `input`, as written in the
+/// source file, or `None` if the compiler does not provide it, e.g. for
+/// the tokens generated by another macro.
+fn source_text(input: &TokenStream) -> Option<String> {
+    let tokens = input.clone().into_iter().collect::<Vec<_>>();
+    let (first, last) = (tokens.first()?, tokens.last()?);
+
+    // The whole program at once, comments included, when the spans can
+    // be joined, e.g. on nightly.
+    let mut source = first
+        .span()
+        .join(last.span())
+        .and_then(|span| span.source_text());
+
+    // Otherwise, each token, e.g. a group with all its content, at its
+    // line and column, keeping the layout of the program, but not the
+    // comments between the tokens.
+    if source.is_none() {
+        let mut layout = String::new();
+        let start = first.span().start();
+        let (mut line, mut column) = (start.line, start.column);
+
+        for token in &tokens {
+            let span = token.span();
+            let (start, end) = (span.start(), span.end());
+
+            // The locations are unknown, or not in the order of the
+            // tokens, e.g. when they come from several files.
+            if start.line == 0 || (start.line, start.column) < (line, column) {
+                return None;
+            }
+
+            if start.line > line {
+                layout.push_str(&"\n".repeat(start.line - line));
+                column = 0;
+            }
+
+            layout.push_str(&" ".repeat(start.column - column));
+            layout.push_str(&span.source_text()?);
+            line = end.line;
+            column = end.column;
+        }
+
+        source = Some(layout);
+    }
+
+    // The `#inline_c_rs` directives end with a line feed.
+    source.map(|mut source| {
+        if !source.ends_with('\n') {
+            source.push('\n');
+        }
+
+        source
+    })
+}
+
 fn reconstruct(input: TokenStream) -> String {
     use proc_macro2::{Delimiter, Spacing, TokenTree::*};
 
@@ -105,11 +618,12 @@ fn reconstruct(input: TokenStream) -> String {
                                 }
                             }
 
-                            // #define, only available on nightly.
+                            // #define, only available on nightly, without the
+                            // source of the program.
                             Some(Ident(define)) if *define == "define" => {
                                 #[cfg(not(nightly))]
                                 panic!(
-                                    "`#define` in C is only supported in `inline-c` with Rust nightly"
+                                    "`#define` in C is only supported in `inline-c` when the compiler provides the source of the program, e.g. with Rust nightly, otherwise write the program in a string literal"
                                 );
 
                                 #[cfg(nightly)]
@@ -0,0 +1,52 @@
+//! Record every expanded snippet in a manifest, so that `cargo
+//! inline-c` can list them and rerun them individually.
+//!
+//! The manifest lives in `target/inline-c/manifest/<package>/`, with
+//! one `<name>.<extension>` file per snippet, where `<name>` is derived
+//! from the location of the macro call and from a hash of the snippet,
+//! e.g. `tests_foo_rs_42_1a2b3c4d`, since several snippets can be
+//! expanded at the same location, e.g. by a `macro_rules!`.
+
+use proc_macro2::Span;
+use std::{
+    collections::hash_map::DefaultHasher,
+    env, fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// Write the snippet into the manifest. Failures are ignored: the
+/// manifest is a debugging aid and must never break the build.
+pub(crate) fn record(extension: &str, program: &str) {
+    let directory = match (target_directory(), env::var("CARGO_PKG_NAME")) {
+        (Some(directory), Ok(package)) => directory.join("inline-c").join("manifest").join(package),
+        _ => return,
+    };
+    let span = Span::call_site();
+    let mut hasher = DefaultHasher::new();
+    program.hash(&mut hasher);
+
+    let location = format!("{}_{}", span.file(), span.start().line)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    let name = format!("{}_{:08x}", location, hasher.finish() as u32);
+
+    let _ = fs::create_dir_all(&directory)
+        .and_then(|_| fs::write(directory.join(format!("{}.{}", name, extension)), program));
+}
+
+fn target_directory() -> Option<PathBuf> {
+    if let Some(target_directory) = env::var_os("CARGO_TARGET_DIR") {
+        return Some(PathBuf::from(target_directory));
+    }
+
+    let manifest_directory = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR")?);
+    let workspace_directory = manifest_directory
+        .ancestors()
+        .find(|directory| directory.join("Cargo.lock").exists())
+        .map(Path::to_path_buf)
+        .unwrap_or(manifest_directory);
+
+    Some(workspace_directory.join("target"))
+}
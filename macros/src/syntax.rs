@@ -0,0 +1,328 @@
+//! A cheap sanity check of the programs, when the macros expand, so
+//! that an unbalanced bracket, literal, comment or preprocessor
+//! conditional is reported at the Rust tokens, instead of by the C
+//! compiler, in a temporary file, when the test runs.
+//!
+//! The brackets and the literals of the programs written with tokens
+//! are already balanced, or Rust could not tokenize them, so only their
+//! preprocessor conditionals are checked. The programs written in a
+//! string literal are checked entirely, and the errors point at their
+//! line and column.
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+
+/// A syntax error, at a `line` and a `column` of the program, from 1.
+pub(crate) struct Error {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+/// A preprocessor conditional being checked: where it has been opened,
+/// the brackets open before it, and the ones open after its first
+/// branch, if it has several.
+struct Conditional {
+    line: usize,
+    column: usize,
+    before: Vec<(char, usize, usize)>,
+    after_first_branch: Option<Vec<(char, usize, usize)>>,
+}
+
+/// Check that the brackets, the string and character literals, the
+/// comments, and the preprocessor conditionals of `program` are
+/// balanced. The branches of a conditional are checked from the same
+/// brackets, e.g. when each branch opens a block.
+pub(crate) fn check(program: &str) -> Result<(), Error> {
+    let chars = program.chars().collect::<Vec<_>>();
+    let mut brackets = Vec::<(char, usize, usize)>::new();
+    let mut conditionals = Vec::<Conditional>::new();
+    let (mut index, mut line, mut column) = (0, 1, 1);
+    let mut line_start = true;
+    let mut word = String::new();
+
+    let error = |line, column, message: String| {
+        Err(Error {
+            line,
+            column,
+            message,
+        })
+    };
+
+    // Advance by one character.
+    macro_rules! advance {
+        () => {{
+            if chars[index] == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+
+            index += 1;
+        }};
+    }
+
+    while index < chars.len() {
+        let c = chars[index];
+        let (start_line, start_column) = (line, column);
+        let next = chars.get(index + 1).copied();
+
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            line_start = false;
+            advance!();
+
+            continue;
+        }
+
+        let previous_word = std::mem::take(&mut word);
+
+        match c {
+            '\n' => line_start = true,
+            c if c.is_whitespace() => (),
+
+            '/' if next == Some('/') => {
+                while index < chars.len() && chars[index] != '\n' {
+                    advance!();
+                }
+
+                continue;
+            }
+
+            '/' if next == Some('*') => {
+                advance!();
+                advance!();
+
+                while index < chars.len()
+                    && !(chars[index] == '*' && chars.get(index + 1) == Some(&'/'))
+                {
+                    advance!();
+                }
+
+                if index == chars.len() {
+                    return error(start_line, start_column, "unterminated comment".to_string());
+                }
+
+                advance!();
+                advance!();
+
+                continue;
+            }
+
+            '#' if line_start => {
+                advance!();
+
+                while index < chars.len() && (chars[index] == ' ' || chars[index] == '\t') {
+                    advance!();
+                }
+
+                let mut directive = String::new();
+
+                while index < chars.len() && chars[index].is_alphanumeric() {
+                    directive.push(chars[index]);
+                    advance!();
+                }
+
+                match directive.as_str() {
+                    "if" | "ifdef" | "ifndef" => conditionals.push(Conditional {
+                        line: start_line,
+                        column: start_column,
+                        before: brackets.clone(),
+                        after_first_branch: None,
+                    }),
+                    "elif" | "elifdef" | "elifndef" | "else" => match conditionals.last_mut() {
+                        Some(conditional) => {
+                            if conditional.after_first_branch.is_none() {
+                                conditional.after_first_branch = Some(brackets.clone());
+                            }
+
+                            brackets = conditional.before.clone();
+                        }
+                        None => {
+                            return error(
+                                start_line,
+                                start_column,
+                                format!("`#{}` without `#if`", directive),
+                            )
+                        }
+                    },
+                    "endif" => match conditionals.pop() {
+                        Some(conditional) => {
+                            if let Some(after_first_branch) = conditional.after_first_branch {
+                                brackets = after_first_branch;
+                            }
+                        }
+                        None => {
+                            return error(
+                                start_line,
+                                start_column,
+                                "`#endif` without `#if`".to_string(),
+                            )
+                        }
+                    },
+                    _ => (),
+                }
+
+                // The rest of the directive, e.g. a macro opening a
+                // block, is not checked.
+                while index < chars.len() && !(chars[index] == '\n' && chars[index - 1] != '\\') {
+                    advance!();
+                }
+
+                continue;
+            }
+
+            // A raw string literal of C++, e.g. `R"delimiter(…)delimiter"`.
+            '"' if matches!(previous_word.as_str(), "R" | "LR" | "uR" | "UR" | "u8R") => {
+                let delimiter = chars[index + 1..]
+                    .iter()
+                    .take_while(|c| **c != '(')
+                    .collect::<String>();
+                let end = format!("){}\"", delimiter);
+                let rest = chars[index..].iter().collect::<String>();
+
+                match rest.find(&end) {
+                    Some(length) => {
+                        for _ in 0..rest[..length + end.len()].chars().count() {
+                            advance!();
+                        }
+
+                        line_start = false;
+
+                        continue;
+                    }
+                    None => {
+                        return error(
+                            start_line,
+                            start_column,
+                            "unterminated raw string literal".to_string(),
+                        )
+                    }
+                }
+            }
+
+            // A digit separator, e.g. `1'000'000`.
+            '\'' if previous_word.starts_with(|c: char| c.is_ascii_digit()) => {
+                word = previous_word;
+                word.push(c);
+            }
+
+            '"' | '\'' => {
+                let kind = if c == '"' { "string" } else { "character" };
+                advance!();
+
+                loop {
+                    match chars.get(index) {
+                        Some('\\') => {
+                            advance!();
+
+                            if index < chars.len() {
+                                advance!();
+                            }
+                        }
+                        Some(quote) if *quote == c => break,
+                        None | Some('\n') => {
+                            return error(
+                                start_line,
+                                start_column,
+                                format!("unterminated {} literal", kind),
+                            )
+                        }
+                        Some(_) => advance!(),
+                    }
+                }
+            }
+
+            '(' | '[' | '{' => brackets.push((c, start_line, start_column)),
+
+            ')' | ']' | '}' => {
+                let opening = match c {
+                    ')' => '(',
+                    ']' => '[',
+                    _ => '{',
+                };
+
+                match brackets.pop() {
+                    Some((open, ..)) if open == opening => (),
+                    Some((open, open_line, open_column)) => {
+                        return error(
+                            start_line,
+                            start_column,
+                            format!(
+                                "mismatched `{}`, the `{}` at line {}, column {} is not closed",
+                                c, open, open_line, open_column
+                            ),
+                        )
+                    }
+                    None => return error(start_line, start_column, format!("unexpected `{}`", c)),
+                }
+            }
+
+            _ => (),
+        }
+
+        if c != '\n' && !c.is_whitespace() {
+            line_start = false;
+        }
+
+        advance!();
+    }
+
+    if let Some(conditional) = conditionals.pop() {
+        return error(
+            conditional.line,
+            conditional.column,
+            "`#if` without `#endif`".to_string(),
+        );
+    }
+
+    if let Some((open, line, column)) = brackets.pop() {
+        return error(line, column, format!("unclosed `{}`", open));
+    }
+
+    Ok(())
+}
+
+/// Check that the preprocessor conditionals of the program written
+/// with `tokens` are balanced, and return the span of the directive in
+/// error otherwise.
+pub(crate) fn check_tokens(tokens: TokenStream) -> Result<(), (Span, String)> {
+    let mut conditionals = Vec::new();
+
+    walk(tokens, &mut conditionals)?;
+
+    match conditionals.pop() {
+        Some(span) => Err((span, "`#if` without `#endif`".to_string())),
+        None => Ok(()),
+    }
+}
+
+fn walk(tokens: TokenStream, conditionals: &mut Vec<Span>) -> Result<(), (Span, String)> {
+    let mut tokens = tokens.into_iter().peekable();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Group(group) => walk(group.stream(), conditionals)?,
+            TokenTree::Punct(hash) if hash.as_char() == '#' => {
+                let directive = match tokens.peek() {
+                    Some(TokenTree::Ident(directive)) => directive.to_string(),
+                    _ => continue,
+                };
+
+                match directive.as_str() {
+                    "if" | "ifdef" | "ifndef" => conditionals.push(hash.span()),
+                    "elif" | "elifdef" | "elifndef" | "else" if conditionals.is_empty() => {
+                        return Err((hash.span(), format!("`#{}` without `#if`", directive)))
+                    }
+                    "endif" if conditionals.pop().is_none() => {
+                        return Err((hash.span(), "`#endif` without `#if`".to_string()))
+                    }
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Cross-compilation to Android with the NDK.
+//!
+//! The NDK is found with the `ANDROID_NDK_HOME` variable, and the
+//! API level is given by `ANDROID_API`, 21 by default. The programs
+//! are run on a device or an emulator with [`Runner::Adb`].
+//!
+//! [`Runner::Adb`]: crate::runner::Runner::Adb
+
+use crate::run::Language;
+use std::{collections::HashMap, env, error::Error, path::PathBuf};
+
+const DEFAULT_API: &str = "21";
+
+/// The NDK toolchain building for an Android target.
+pub(crate) struct Ndk {
+    /// The clang wrapper of the target and API level, e.g.
+    /// `aarch64-linux-android21-clang`.
+    pub(crate) compiler: PathBuf,
+
+    /// The sysroot of the toolchain.
+    pub(crate) sysroot: PathBuf,
+}
+
+impl Ndk {
+    pub(crate) fn find(
+        target: &str,
+        language: Language,
+        variables: &HashMap<String, String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let home = variables
+            .get("ANDROID_NDK_HOME")
+            .cloned()
+            .or_else(|| env::var("ANDROID_NDK_HOME").ok())
+            .ok_or("`ANDROID_NDK_HOME` must be set to build for Android")?;
+
+        let host_tag = if cfg!(target_os = "windows") {
+            "windows-x86_64"
+        } else if cfg!(target_os = "macos") {
+            // The NDK ships universal binaries under this name.
+            "darwin-x86_64"
+        } else {
+            "linux-x86_64"
+        };
+
+        let toolchain = PathBuf::from(home)
+            .join("toolchains")
+            .join("llvm")
+            .join("prebuilt")
+            .join(host_tag);
+
+        // Rust and the NDK disagree on the name of the 32-bit ARM
+        // target.
+        let clang_target = match target.split_once('-') {
+            Some(("armv7", rest)) => format!("armv7a-{}", rest),
+            _ => target.to_string(),
+        };
+
+        let api = variables
+            .get("ANDROID_API")
+            .map(|api| api.trim())
+            .unwrap_or(DEFAULT_API);
+
+        let compiler = toolchain.join("bin").join(format!(
+            "{}{}-{}{}",
+            clang_target,
+            api,
+            match language {
+                Language::Cxx | Language::Cuda => "clang++",
+                Language::C | Language::ObjectiveC | Language::Assembler => "clang",
+            },
+            if cfg!(target_os = "windows") {
+                ".cmd"
+            } else {
+                ""
+            }
+        ));
+
+        if !compiler.exists() {
+            return Err(format!(
+                "cannot find the NDK compiler `{}`; check `ANDROID_NDK_HOME` and `ANDROID_API`",
+                compiler.display()
+            )
+            .into());
+        }
+
+        Ok(Self {
+            compiler,
+            sysroot: toolchain.join("sysroot"),
+        })
+    }
+}
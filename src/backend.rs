@@ -0,0 +1,76 @@
+//! Compiler families, and the dialects of their flags.
+//!
+//! `cc` knows GCC, Clang and MSVC. The other compilers are recognized
+//! by their name, and the flags written for the family they derive from
+//! are translated for them: Intel oneAPI (`icx`, `icpx`, and `icx-cl`
+//! which is like MSVC), NVIDIA HPC (`nvc`, `nvc++`), and TinyCC
+//! (`tcc`).
+
+use std::{ffi::OsString, path::Path};
+
+/// The family of a compiler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Family {
+    Gnu,
+    Clang,
+    Msvc,
+    /// Intel oneAPI, based on Clang, or like MSVC on Windows.
+    Intel {
+        msvc: bool,
+    },
+    /// NVIDIA HPC, formerly PGI.
+    Nvidia,
+    /// TinyCC, which ignores the GCC flags it does not support.
+    Tiny,
+}
+
+impl Family {
+    /// The family of the `compiler` found by `cc`.
+    pub(crate) fn detect(compiler: &cc::Tool) -> Self {
+        let name = Path::new(compiler.path())
+            .file_stem()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+
+        if name.starts_with("icx") || name.starts_with("icpx") {
+            Self::Intel {
+                msvc: compiler.is_like_msvc(),
+            }
+        } else if ["nvc", "nvc++", "pgcc", "pgc++"].contains(&name.as_str()) {
+            Self::Nvidia
+        } else if name == "tcc" || name.ends_with("-tcc") {
+            Self::Tiny
+        } else if compiler.is_like_msvc() {
+            Self::Msvc
+        } else if compiler.is_like_clang() {
+            Self::Clang
+        } else {
+            Self::Gnu
+        }
+    }
+
+    /// Whether the flags are spelled like MSVC's.
+    pub(crate) fn is_msvc(self) -> bool {
+        matches!(self, Self::Msvc | Self::Intel { msvc: true })
+    }
+
+    /// Translate `flag`, written for the family this one derives from,
+    /// e.g. `-fopenmp`. Return `None` if it must be dropped.
+    pub(crate) fn translate(self, flag: OsString) -> Option<OsString> {
+        let Some(name) = flag.to_str() else {
+            return Some(flag);
+        };
+
+        let translated = match (self, name) {
+            // The LLVM-based OpenMP runtime.
+            (Self::Intel { msvc: false }, "-fopenmp") => "-fiopenmp",
+            (Self::Intel { msvc: true }, "/openmp") => "/Qiopenmp",
+            (Self::Nvidia, "-fopenmp") => "-mp",
+            // `nvc` has no such warning group.
+            (Self::Nvidia, "-Wextra") => return None,
+            _ => return Some(flag),
+        };
+
+        Some(translated.into())
+    }
+}
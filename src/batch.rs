@@ -0,0 +1,281 @@
+//! Batch compilation of the snippets of a package.
+//!
+//! When the `BATCH` variable is set, e.g. with `INLINE_C_RS_BATCH=1`,
+//! the first assertion to compile its snippet compiles all the snippets
+//! recorded by the macros in the manifest of the package, see `cargo
+//! inline-c`, into objects, with a few invocations of the compiler:
+//! GCC, Clang and `cl.exe` all accept many inputs with `-c`. The other
+//! assertions then only link their object, as long as their source and
+//! their compiler command are the same as the ones of the batch.
+//!
+//! The snippets with directives or placeholders, which may change the
+//! command or the source, are left out of the batch. The snippets that
+//! fail to compile in the batch are compiled on their own, with their
+//! diagnostics.
+
+use crate::{assert::Compiler, build_dir, report};
+use lazy_static::lazy_static;
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    ffi::{OsStr, OsString},
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+/// The maximum number of sources given to one invocation of the
+/// compiler, which keeps the command line under the limit of Windows.
+const CHUNK_SIZE: usize = 64;
+
+lazy_static! {
+    /// The batches compiled by the process, by signature, or `None` if
+    /// the batch has failed.
+    static ref BATCHES: Mutex<HashMap<String, Option<Batch>>> = Mutex::new(HashMap::new());
+}
+
+/// The objects compiled by a batch.
+struct Batch {
+    directory: PathBuf,
+
+    /// The names of the objects compiled by this process. The other
+    /// objects of the directory may be out of date.
+    objects: HashSet<String>,
+}
+
+/// The object of `source`, written at `source_path`, compiled by the
+/// batch of `compiler`, if any. The batch is compiled on the first
+/// call.
+pub(crate) fn object(compiler: &Compiler, source: &str, source_path: &Path) -> Option<PathBuf> {
+    let extension = source_path.extension()?.to_str()?;
+    let args = compile_args(compiler, source_path);
+
+    let signature = report::hash(
+        args.iter()
+            .fold(
+                compiler.command.get_program().to_os_string(),
+                |mut signature, arg| {
+                    signature.push("\0");
+                    signature.push(arg);
+                    signature
+                },
+            )
+            .to_string_lossy()
+            .as_bytes(),
+    );
+
+    let mut batches = BATCHES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let batch = batches
+        .entry(format!("{}-{}", extension, signature))
+        .or_insert_with(|| {
+            compile(compiler, &args, extension, &signature)
+                .map_err(|error| eprintln!("Failed to compile the batch of snippets: {}", error))
+                .ok()
+        })
+        .as_ref()?;
+
+    let object = object_name(&report::hash(source.as_bytes()), compiler.msvc);
+
+    batch
+        .objects
+        .contains(&object)
+        .then(|| batch.directory.join(object))
+}
+
+/// Replace the source given to `compiler` by its `object`, so that the
+/// program is only linked.
+pub(crate) fn link(compiler: &mut Command, source_path: &Path, object: &Path) {
+    let mut linker = Command::new(compiler.get_program());
+
+    for arg in compiler.get_args() {
+        if arg == source_path.as_os_str() {
+            linker.arg(object);
+        } else {
+            linker.arg(arg);
+        }
+    }
+
+    for (key, value) in compiler.get_envs() {
+        match value {
+            Some(value) => linker.env(key, value),
+            None => linker.env_remove(key),
+        };
+    }
+
+    if let Some(directory) = compiler.get_current_dir() {
+        linker.current_dir(directory);
+    }
+
+    *compiler = linker;
+}
+
+/// The arguments of `compiler`, without its input, its outputs, and its
+/// link arguments.
+fn compile_args(compiler: &Compiler, source_path: &Path) -> Vec<OsString> {
+    let mut args = compiler.command.get_args();
+    let mut compile_args = Vec::new();
+
+    while let Some(arg) = args.next() {
+        if compiler.msvc && (arg == "/link" || arg == "-link") {
+            break;
+        }
+
+        let flag = arg.to_string_lossy();
+
+        if arg == source_path.as_os_str()
+            || compiler.link_args.iter().any(|link_arg| link_arg == arg)
+            || (compiler.msvc && (flag.starts_with("-Fo") || flag.starts_with("-Fe")))
+        {
+            continue;
+        }
+
+        // `clang-cl` is given `-o`, like Clang.
+        if arg == "-o" {
+            args.next();
+            continue;
+        }
+
+        compile_args.push(arg.to_os_string());
+    }
+
+    compile_args
+}
+
+/// Compile the snippets of the manifest written in `extension` with
+/// `args`.
+fn compile(
+    compiler: &Compiler,
+    args: &[OsString],
+    extension: &str,
+    signature: &str,
+) -> io::Result<Batch> {
+    let (target_directory, package) =
+        match (build_dir::target_directory(), env::var("CARGO_PKG_NAME")) {
+            (Some(target_directory), Ok(package)) => (target_directory, package),
+            _ => {
+                return Err(io::Error::other(
+                    "the manifest of the snippets cannot be found outside of `cargo test`",
+                ))
+            }
+        };
+    let manifest = target_directory
+        .join("inline-c")
+        .join("manifest")
+        .join(package);
+    let directory = target_directory
+        .join("inline-c")
+        .join("batch")
+        .join(format!("{}-{}", extension, signature));
+    fs::create_dir_all(&directory)?;
+
+    // The objects are written aside, then moved into the batch, so that
+    // concurrent test binaries never link an object half-written.
+    let work = tempfile::tempdir_in(&directory)?;
+    let mut sources = Vec::new();
+
+    for entry in fs::read_dir(manifest)? {
+        let path = entry?.path();
+
+        if path.extension() != Some(OsStr::new(extension)) {
+            continue;
+        }
+
+        let source = fs::read_to_string(&path)?;
+
+        if source.contains("#inline_c_rs") || source.contains("{{") {
+            continue;
+        }
+
+        let name = report::hash(source.as_bytes());
+        let source_path = work.path().join(format!("{}.{}", name, extension));
+
+        if !source_path.exists() {
+            fs::write(&source_path, source)?;
+            sources.push((name, source_path));
+        }
+    }
+
+    let current_directory = match compiler.command.get_current_dir() {
+        Some(directory) => directory.to_path_buf(),
+        None => env::current_dir()?,
+    };
+    let mut children = Vec::new();
+
+    for chunk in sources.chunks(CHUNK_SIZE) {
+        let mut command = Command::new(compiler.command.get_program());
+
+        for (key, value) in compiler.command.get_envs() {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
+        }
+
+        // GCC and Clang write the objects in the current directory,
+        // where the relative include directories must be found too.
+        if compiler.msvc {
+            let mut output = OsString::from("-Fo");
+            output.push(work.path());
+            output.push("\\");
+            command.args(args).arg("-c").arg(output);
+        } else {
+            for arg in args {
+                match arg.to_str().and_then(|arg| arg.strip_prefix("-I")) {
+                    Some(include) if Path::new(include).is_relative() && !include.is_empty() => {
+                        let mut absolute = OsString::from("-I");
+                        absolute.push(current_directory.join(include));
+                        command.arg(absolute);
+                    }
+                    _ => {
+                        command.arg(arg);
+                    }
+                }
+            }
+
+            command.arg("-c").current_dir(work.path());
+        }
+
+        command
+            .args(chunk.iter().map(|(_, source_path)| source_path))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        children.push(command.spawn()?);
+    }
+
+    // The snippets failing to compile fail their chunk, but the objects
+    // of the other ones are still written.
+    for mut child in children {
+        child.wait()?;
+    }
+
+    let mut objects = HashSet::new();
+
+    for (name, _) in &sources {
+        let object = object_name(name, compiler.msvc);
+        let compiled = work.path().join(&object);
+
+        if !compiled.is_file() {
+            continue;
+        }
+
+        // The object may be used by another test binary on Windows,
+        // where it has just been compiled the same way, and cannot be
+        // replaced then. The other failures leave no object behind, and
+        // the snippet is compiled alone.
+        let destination = directory.join(&object);
+
+        if fs::rename(compiled, &destination).is_ok() || destination.is_file() {
+            objects.insert(object);
+        }
+    }
+
+    Ok(Batch { directory, objects })
+}
+
+fn object_name(name: &str, msvc: bool) -> String {
+    format!("{}.{}", name, if msvc { "obj" } else { "o" })
+}
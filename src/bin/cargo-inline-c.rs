@@ -0,0 +1,288 @@
+//! A cargo subcommand to list the inline-c snippets of a crate, and to
+//! rerun one of them individually.
+//!
+//! ```text
+//! cargo inline-c list
+//! cargo inline-c show NAME
+//! cargo inline-c run NAME [--keep-artifacts] [--verbose]
+//! cargo inline-c watch NAME
+//! ```
+//!
+//! Snippets are recorded by `assert_c!` and `assert_cxx!` when the
+//! tests are compiled, in `target/inline-c/manifest/<package>/`.
+//!
+//! `watch` reruns a snippet each time a Rust source of the package or
+//! a header included by the snippet changes, after recompiling the
+//! tests to extract it again, and prints the diff of its standard
+//! output with the previous run.
+
+use regex::Regex;
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, prelude::*},
+    path::{Path, PathBuf},
+    process::{self, Command},
+    thread,
+    time::{Duration, SystemTime},
+};
+use wasmer_inline_c::{diff, Language};
+
+const USAGE: &str = "Usage:
+    cargo inline-c list
+    cargo inline-c show NAME
+    cargo inline-c run NAME [--keep-artifacts] [--verbose]
+    cargo inline-c watch NAME";
+
+/// The delay between two checks of the watched files.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+struct Snippet {
+    package: String,
+    name: String,
+    path: PathBuf,
+}
+
+fn main() {
+    match execute() {
+        Ok(code) => process::exit(code),
+        Err(error) => {
+            eprintln!("cargo-inline-c: {}", error);
+            process::exit(2);
+        }
+    }
+}
+
+fn execute() -> Result<i32, Box<dyn Error>> {
+    // Cargo passes the subcommand name as the first argument.
+    let arguments = env::args()
+        .skip(1)
+        .skip_while(|argument| argument == "inline-c")
+        .collect::<Vec<_>>();
+
+    match arguments.first().map(String::as_str) {
+        Some("list") => {
+            let status = Command::new(cargo())
+                .args(["test", "--no-run", "--quiet"])
+                .status()?;
+
+            if !status.success() {
+                return Err("failed to compile the tests".into());
+            }
+
+            for snippet in snippets()? {
+                println!("{}/{}", snippet.package, snippet.name);
+            }
+
+            Ok(0)
+        }
+
+        Some("show") => {
+            let snippet = find(arguments.get(1))?;
+            print!("{}", fs::read_to_string(snippet.path)?);
+
+            Ok(0)
+        }
+
+        Some("run") => {
+            let snippet = find(arguments.get(1))?;
+            let keep_artifacts = arguments[2..].iter().any(|a| a == "--keep-artifacts");
+            let verbose = arguments[2..].iter().any(|a| a == "--verbose");
+
+            let program = fs::read_to_string(&snippet.path)?;
+            let language = language(&snippet);
+
+            if verbose {
+                eprintln!("=== {}/{} ({})", snippet.package, snippet.name, language);
+                eprintln!("{}", program);
+            }
+
+            let mut assert = wasmer_inline_c::run(language, &program)?;
+
+            if keep_artifacts {
+                assert.keep_artifacts();
+            }
+
+            if verbose || keep_artifacts {
+                for artifact in assert.artifacts() {
+                    eprintln!("=== artifact: {}", artifact.display());
+                }
+            }
+
+            let assert = assert.assert();
+            let output = assert.get_output();
+
+            io::stdout().write_all(&output.stdout)?;
+            io::stderr().write_all(&output.stderr)?;
+
+            if verbose {
+                eprintln!("=== {}", output.status);
+            }
+
+            Ok(output.status.code().unwrap_or(1))
+        }
+
+        Some("watch") => {
+            let name = arguments.get(1);
+            let mut previous_stdout: Option<String> = None;
+
+            loop {
+                let status = Command::new(cargo())
+                    .args(["test", "--no-run", "--quiet"])
+                    .status()?;
+
+                let mut watched = rust_sources(Path::new("."));
+
+                if status.success() {
+                    let snippet = find(name)?;
+                    let mut assert = wasmer_inline_c::run(
+                        language(&snippet),
+                        &fs::read_to_string(&snippet.path)?,
+                    )?;
+                    let output = assert.output()?;
+                    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+
+                    eprintln!(
+                        "=== {}/{}: exit code {:?}",
+                        snippet.package, snippet.name, output.exit_code
+                    );
+                    io::stderr().write_all(&output.stderr)?;
+
+                    match &previous_stdout {
+                        Some(previous) if *previous == stdout => eprintln!("=== stdout unchanged"),
+                        Some(previous) => print!("{}", diff::lines(previous, &stdout)),
+                        None => print!("{}", stdout),
+                    }
+
+                    previous_stdout = Some(stdout);
+                    watched.extend(assert.dependencies().iter().cloned());
+                } else {
+                    eprintln!("=== failed to compile the tests");
+                }
+
+                let stamps = modified(&watched);
+
+                eprintln!("=== watching {} files", watched.len());
+
+                while modified(&watched) == stamps {
+                    thread::sleep(WATCH_INTERVAL);
+                }
+            }
+        }
+
+        _ => {
+            eprintln!("{}", USAGE);
+
+            Ok(2)
+        }
+    }
+}
+
+fn language(snippet: &Snippet) -> Language {
+    snippet
+        .path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(Language::from_extension)
+        .unwrap_or(Language::C)
+}
+
+/// The Rust sources of the package in `directory`, outside of its
+/// target directory.
+fn rust_sources(directory: &Path) -> Vec<PathBuf> {
+    let mut sources = Vec::new();
+
+    for entry in fs::read_dir(directory).into_iter().flatten().flatten() {
+        let path = entry.path();
+
+        if path.is_dir() {
+            if !matches!(
+                path.file_name().and_then(|name| name.to_str()),
+                Some("target") | Some(".git")
+            ) {
+                sources.extend(rust_sources(&path));
+            }
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            sources.push(path);
+        }
+    }
+
+    sources
+}
+
+fn modified(paths: &[PathBuf]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+        })
+        .collect()
+}
+
+fn cargo() -> String {
+    env::var("CARGO").unwrap_or_else(|_| "cargo".to_string())
+}
+
+fn manifest_directory() -> Result<PathBuf, Box<dyn Error>> {
+    let output = Command::new(cargo())
+        .args(["metadata", "--format-version", "1", "--no-deps"])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned().into());
+    }
+
+    let metadata = String::from_utf8(output.stdout)?;
+    let regex = Regex::new(r#""target_directory":"((?:[^"\\]|\\.)*)""#).unwrap();
+    let target_directory = regex
+        .captures(&metadata)
+        .ok_or("cannot find the target directory")?[1]
+        .replace("\\\\", "\\");
+
+    Ok(PathBuf::from(target_directory)
+        .join("inline-c")
+        .join("manifest"))
+}
+
+fn snippets() -> Result<Vec<Snippet>, Box<dyn Error>> {
+    let mut snippets = Vec::new();
+    let directory = manifest_directory()?;
+
+    if !directory.exists() {
+        return Ok(snippets);
+    }
+
+    for package in fs::read_dir(directory)? {
+        let package = package?;
+
+        for entry in fs::read_dir(package.path())? {
+            let path = entry?.path();
+
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                snippets.push(Snippet {
+                    package: package.file_name().to_string_lossy().into_owned(),
+                    name: name.to_string(),
+                    path: path.clone(),
+                });
+            }
+        }
+    }
+
+    snippets.sort_by(|a, b| (&a.package, &a.name).cmp(&(&b.package, &b.name)));
+
+    Ok(snippets)
+}
+
+fn find(name: Option<&String>) -> Result<Snippet, Box<dyn Error>> {
+    let name = name.ok_or(USAGE)?;
+
+    snippets()?
+        .into_iter()
+        .find(|snippet| {
+            snippet.name == *name || format!("{}/{}", snippet.package, snippet.name) == *name
+        })
+        .ok_or_else(|| format!("no snippet named `{}`, see `cargo inline-c list`", name).into())
+}
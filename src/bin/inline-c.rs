@@ -0,0 +1,81 @@
+//! Compile and run a C or C++ snippet outside of `cargo test`.
+//!
+//! The snippet goes through the same processing as `assert_c!` and
+//! `assert_cxx!`: `#inline_c_rs` directives and `INLINE_C_RS_*`
+//! environment variables are honored.
+//!
+//! ```text
+//! inline-c [--c | --cxx] [FILE]
+//! ```
+//!
+//! The snippet is read from `FILE`, or from the standard input if
+//! `FILE` is absent or `-`. The language is guessed from the file
+//! extension, and defaults to C.
+
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, prelude::*},
+    path::Path,
+    process,
+};
+use wasmer_inline_c::Language;
+
+const USAGE: &str = "Usage: inline-c [--c | --cxx] [FILE]";
+
+fn main() {
+    match execute() {
+        Ok(code) => process::exit(code),
+        Err(error) => {
+            eprintln!("inline-c: {}", error);
+            process::exit(2);
+        }
+    }
+}
+
+fn execute() -> Result<i32, Box<dyn Error>> {
+    let mut language = None;
+    let mut file = None;
+
+    for argument in env::args().skip(1) {
+        match argument.as_str() {
+            "--c" => language = Some(Language::C),
+            "--cxx" => language = Some(Language::Cxx),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+
+                return Ok(0);
+            }
+            _ if file.is_none() => file = Some(argument),
+            _ => return Err(format!("unexpected argument `{}`\n{}", argument, USAGE).into()),
+        }
+    }
+
+    let program = match file.as_deref() {
+        None | Some("-") => {
+            let mut program = String::new();
+            io::stdin().read_to_string(&mut program)?;
+
+            program
+        }
+
+        Some(file) => fs::read_to_string(file)?,
+    };
+
+    let language = language.unwrap_or_else(|| {
+        file.as_deref()
+            .and_then(|file| Path::new(file).extension())
+            .and_then(|extension| extension.to_str())
+            .and_then(Language::from_extension)
+            .unwrap_or(Language::C)
+    });
+
+    let assert = wasmer_inline_c::run(language, &program)?.assert();
+    let output = assert.get_output();
+
+    io::stdout().write_all(&output.stdout)?;
+    io::stderr().write_all(&output.stderr)?;
+
+    Ok(output.status.code().unwrap_or(1))
+}
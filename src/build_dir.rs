@@ -0,0 +1,153 @@
+//! The build directory of the snippets, under the target directory.
+//!
+//! The sources, objects and executables of the snippets are written in
+//! `target/inline-c/<crate>/`, where `<crate>` is the test binary, e.g.
+//! `integration` for `tests/integration.rs`, instead of the temporary
+//! directory of the system. They are removed by `cargo clean`, are easy
+//! to find to debug a snippet, and are left out of the backups and the
+//! scans of the directories tagged with `CACHEDIR.TAG` like the target
+//! directory. The temporary directory of the system is used when the
+//! target directory cannot be found.
+//!
+//! Each run of a test binary writes in its own directory, `run-<pid>`,
+//! so that the files of a run killed before it could remove them are
+//! not leaked forever: the directories of the runs whose process is
+//! not alive anymore are removed by the next run. The artifacts kept
+//! with `Assert::keep_artifacts` are removed by the next run too.
+//!
+//! Another directory can be given with the `ARTIFACT_DIR` variable, or
+//! with `Assert::artifact_dir`.
+
+use lazy_static::lazy_static;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{self, Command, Stdio},
+};
+
+/// The prefix of the directory of each run.
+const RUN_PREFIX: &str = "run-";
+
+lazy_static! {
+    static ref DIRECTORY: PathBuf = {
+        let parent = target_directory()
+            .map(|target_directory| {
+                target_directory
+                    .join("inline-c")
+                    .join(crate_name().unwrap_or_else(|| "inline-c".to_string()))
+            })
+            .filter(|directory| fs::create_dir_all(directory).is_ok())
+            .unwrap_or_else(|| env::temp_dir().join("inline-c-rs"));
+
+        sweep(&parent);
+
+        let directory = parent.join(format!("{}{}", RUN_PREFIX, process::id()));
+
+        if fs::create_dir_all(&directory).is_ok() {
+            directory
+        } else {
+            env::temp_dir()
+        }
+    };
+}
+
+/// The build directory of the running test binary, created once.
+pub(crate) fn directory() -> PathBuf {
+    DIRECTORY.clone()
+}
+
+/// Remove the directories of the dead runs in `parent`.
+fn sweep(parent: &Path) {
+    let Ok(entries) = fs::read_dir(parent) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let pid = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix(RUN_PREFIX))
+            .and_then(|pid| pid.parse::<u32>().ok());
+
+        match pid {
+            Some(pid) if pid != process::id() && !is_alive(pid) => {
+                // Another run may be sweeping it at the same time.
+                let _ = fs::remove_dir_all(entry.path());
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Whether the process `pid` is running. A reused PID only keeps the
+/// directory of the dead run until a later sweep, and so does a
+/// failure of the check.
+fn is_alive(pid: u32) -> bool {
+    if cfg!(target_os = "linux") {
+        return Path::new("/proc").join(pid.to_string()).exists();
+    }
+
+    let output = if cfg!(windows) {
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+            .stderr(Stdio::null())
+            .output()
+    } else {
+        Command::new("kill")
+            .args(["-0", &pid.to_string()])
+            .stderr(Stdio::null())
+            .output()
+    };
+
+    match output {
+        // `tasklist` succeeds with an informational message when no
+        // process matches.
+        Ok(output) if cfg!(windows) => {
+            String::from_utf8_lossy(&output.stdout).contains(&format!("\"{}\"", pid))
+        }
+        Ok(output) => output.status.success(),
+        Err(_) => true,
+    }
+}
+
+/// The target directory: the `CARGO_TARGET_DIR` variable, the directory
+/// tagged by Cargo with `CACHEDIR.TAG` containing the running binary,
+/// or the `target` directory of the workspace under test.
+pub(crate) fn target_directory() -> Option<PathBuf> {
+    if let Some(target_directory) = env::var_os("CARGO_TARGET_DIR") {
+        return Some(PathBuf::from(target_directory));
+    }
+
+    if let Some(target_directory) = env::current_exe().ok().and_then(|executable| {
+        executable
+            .ancestors()
+            .find(|directory| directory.join("CACHEDIR.TAG").is_file())
+            .map(Path::to_path_buf)
+    }) {
+        return Some(target_directory);
+    }
+
+    let manifest_directory = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR")?);
+    let workspace_directory = manifest_directory
+        .ancestors()
+        .find(|directory| directory.join("Cargo.lock").exists())
+        .map(Path::to_path_buf)
+        .unwrap_or(manifest_directory);
+
+    Some(workspace_directory.join("target"))
+}
+
+/// The name of the running test binary, without the hash appended by
+/// Cargo.
+pub(crate) fn crate_name() -> Option<String> {
+    let name = env::current_exe()
+        .ok()?
+        .file_stem()?
+        .to_string_lossy()
+        .into_owned();
+
+    Some(match name.rsplit_once('-') {
+        Some((name, hash)) if hash.chars().all(|c| c.is_ascii_hexdigit()) => name.to_string(),
+        _ => name,
+    })
+}
@@ -0,0 +1,132 @@
+//! A cache of the compiled executables, shared by the runs of the
+//! test suites.
+//!
+//! When the `CACHE` variable is set, e.g. with
+//! `INLINE_C_RS_CACHE=target/inline-c/cache`, an executable is reused
+//! when the source, the compiler command, and the headers the source
+//! includes are the same. The headers are found with
+//! [`includes::resolve`], and their content is part of the key, so
+//! editing a header invalidates the executables depending on it. The
+//! headers of each entry are listed next to it, in a Makefile-like
+//! `.d` file.
+
+use crate::includes;
+use sha2::{Digest, Sha256};
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The entry of an executable in the cache.
+pub(crate) struct Key {
+    path: PathBuf,
+}
+
+/// The headers included by `source`, found in the include directories
+/// given to `compiler`.
+pub(crate) fn dependencies(
+    compiler: &Command,
+    source: &str,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let include_directories = compiler
+        .get_args()
+        .filter_map(|argument| argument.to_str())
+        .filter_map(|argument| {
+            argument
+                .strip_prefix("-I")
+                .or_else(|| argument.strip_prefix("/I"))
+        })
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
+    includes::resolve(source, &include_directories)
+}
+
+impl Key {
+    /// The entry of the executable built by `compiler` from `source`,
+    /// written at `source_path`, into `executable`, in the cache
+    /// `directory`. `source` includes the `dependencies` headers.
+    pub(crate) fn new(
+        directory: &Path,
+        compiler: &Command,
+        source: &str,
+        source_path: &Path,
+        executable: &Path,
+        dependencies: &[PathBuf],
+    ) -> io::Result<Self> {
+        // The generated paths differ from one run to the other.
+        let source_path = source_path.to_string_lossy();
+        let executable_stem = executable.with_extension("");
+        let executable_stem = executable_stem.to_string_lossy();
+
+        let mut hasher = Sha256::new();
+        hasher.update(compiler.get_program().to_string_lossy().as_bytes());
+
+        for argument in compiler.get_args() {
+            let argument = argument
+                .to_string_lossy()
+                .replace(&*source_path, "<source>")
+                .replace(&*executable_stem, "<executable>");
+            hasher.update([0]);
+            hasher.update(argument.as_bytes());
+        }
+
+        hasher.update([0]);
+        hasher.update(source.as_bytes());
+
+        for dependency in dependencies {
+            hasher.update([0]);
+            hasher.update(dependency.to_string_lossy().as_bytes());
+            hasher.update(Sha256::digest(fs::read(dependency)?));
+        }
+
+        let key = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect::<String>();
+        let mut path = directory.join(key);
+
+        if let Some(extension) = executable.extension() {
+            path.set_extension(extension);
+        }
+
+        Ok(Self { path })
+    }
+
+    /// Copy the cached executable to `executable`. Return whether it
+    /// was cached.
+    pub(crate) fn restore(&self, executable: &Path) -> io::Result<bool> {
+        if !self.path.is_file() {
+            return Ok(false);
+        }
+
+        fs::copy(&self.path, executable)?;
+
+        Ok(true)
+    }
+
+    /// Add `executable` to the cache, along the list of its
+    /// `dependencies`.
+    pub(crate) fn store(&self, executable: &Path, dependencies: &[PathBuf]) -> io::Result<()> {
+        let directory = self.path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(directory)?;
+
+        // Concurrent tests may store the same entry: it is written
+        // aside, then renamed, so that it is never read half-written.
+        let mut partial = tempfile::NamedTempFile::new_in(directory)?;
+        io::copy(&mut fs::File::open(executable)?, partial.as_file_mut())?;
+        fs::set_permissions(partial.path(), fs::metadata(executable)?.permissions())?;
+        partial.persist(&self.path).map_err(|error| error.error)?;
+
+        let rule = dependencies
+            .iter()
+            .fold(format!("{}:", self.path.display()), |rule, dependency| {
+                format!("{} \\\n  {}", rule, dependency.display())
+            });
+
+        fs::write(self.path.with_extension("d"), rule + "\n")
+    }
+}
@@ -0,0 +1,112 @@
+//! The diagnostics of the compiler, parsed from its output.
+//!
+//! Both the GCC and Clang format, `file:line:column: warning: message
+//! [-Wflag]`, and the MSVC format, `file(line,column): warning C4996:
+//! message`, are understood. The other lines, like the excerpts of the
+//! source and the notes without a location, are ignored.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+
+/// The severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Note,
+    Warning,
+    Error,
+}
+
+/// A diagnostic of the compiler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file the diagnostic points at, if any, as written by the
+    /// compiler.
+    pub file: Option<String>,
+
+    /// The line the diagnostic points at, if any.
+    pub line: Option<u32>,
+
+    /// The column the diagnostic points at, if any.
+    pub column: Option<u32>,
+
+    pub severity: Severity,
+
+    /// The code of the diagnostic, e.g. `C4996` with MSVC, or the flag
+    /// enabling it, e.g. `-Wunused-variable` with GCC and Clang.
+    pub code: Option<String>,
+
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+
+            if let Some(line) = self.line {
+                write!(f, "{}:", line)?;
+            }
+
+            if let Some(column) = self.column {
+                write!(f, "{}:", column)?;
+            }
+
+            write!(f, " ")?;
+        }
+
+        write!(
+            f,
+            "{}",
+            match self.severity {
+                Severity::Note => "note",
+                Severity::Warning => "warning",
+                Severity::Error => "error",
+            }
+        )?;
+
+        if let Some(code) = &self.code {
+            write!(f, " {}", code)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+lazy_static! {
+    static ref GNU: Regex = Regex::new(
+        r"^(?P<file>.+?):(?:(?P<line>\d+):(?:(?P<column>\d+):)?)? (?:fatal )?(?P<severity>error|warning|note): (?P<message>.*?)(?: \[(?P<code>-W[^\]]+)\])?$"
+    )
+    .unwrap();
+    static ref MSVC: Regex = Regex::new(
+        r"^(?:cl|(?P<file>.+?)(?:\((?P<line>\d+)(?:,(?P<column>\d+))?\))?) ?: (?:Command line |fatal )?(?P<severity>error|warning|note)(?: (?P<code>[A-Z]+\d+))?\s*: (?P<message>.*)$"
+    )
+    .unwrap();
+}
+
+/// Parse the diagnostics written by the compiler in `output`.
+pub fn parse(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .map(str::trim_end)
+        // The GNU format is tried first, since the MSVC one would take
+        // its line and column for a part of the file.
+        .filter_map(|line| GNU.captures(line).or_else(|| MSVC.captures(line)))
+        .map(|captures| Diagnostic {
+            file: captures.name("file").map(|file| file.as_str().to_string()),
+            line: captures
+                .name("line")
+                .and_then(|line| line.as_str().parse().ok()),
+            column: captures
+                .name("column")
+                .and_then(|column| column.as_str().parse().ok()),
+            severity: match &captures["severity"] {
+                "error" => Severity::Error,
+                "warning" => Severity::Warning,
+                _ => Severity::Note,
+            },
+            code: captures.name("code").map(|code| code.as_str().to_string()),
+            message: captures["message"].to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+//! Line diffs of the outputs, shown when they differ from the expected
+//! ones.
+//!
+//! The lines are colored with ANSI escapes, the removed ones in red and
+//! the added ones in green, when the standard error is a terminal and
+//! the `NO_COLOR` environment variable is not set, or when the
+//! `CLICOLOR_FORCE` environment variable is set, e.g. in a CI job whose
+//! log renders the colors.
+
+use std::{
+    env,
+    io::{self, IsTerminal},
+};
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const RESET: &str = "\x1b[0m";
+
+/// A line diff of `old` and `new`, from their longest common
+/// subsequence of lines: the lines of `old` only are prefixed by `- `,
+/// the lines of `new` only by `+ `, and the common lines by two spaces.
+pub fn lines(old: &str, new: &str) -> String {
+    render(old, new, false)
+}
+
+/// Same as [`lines`], colored if the standard error supports it.
+pub fn colored_lines(old: &str, new: &str) -> String {
+    render(old, new, use_color())
+}
+
+/// Whether the diffs written to the standard error are colored.
+pub fn use_color() -> bool {
+    if env::var_os("CLICOLOR_FORCE").is_some_and(|force| force != "0") {
+        return true;
+    }
+
+    env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal()
+}
+
+fn render(old: &str, new: &str, color: bool) -> String {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut output = String::new();
+    let mut push = |sign: char, line: &str, escape: &str| {
+        if color && !escape.is_empty() {
+            output.push_str(&format!("{}{} {}{}\n", escape, sign, line, RESET));
+        } else {
+            output.push_str(&format!("{} {}\n", sign, line));
+        }
+    };
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            push(' ', old[i], "");
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || common[i][j + 1] >= common[i + 1][j]) {
+            push('+', new[j], GREEN);
+            j += 1;
+        } else {
+            push('-', old[i], RED);
+            i += 1;
+        }
+    }
+
+    output
+}
@@ -0,0 +1,177 @@
+//! Extract C and C++ code blocks from Rust doc comments or Markdown
+//! files, and run each of them as an inline-c assertion, so that the
+//! examples in the documentation cannot rot.
+
+use crate::run::{run, Language};
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A fenced code block written in C or C++.
+#[derive(Debug, Clone)]
+pub struct CodeBlock {
+    /// The file containing the code block.
+    pub path: PathBuf,
+
+    /// The line of the opening fence, starting at 1.
+    pub line: usize,
+
+    /// The language declared by the fence, e.g. ```` ```c ```` or
+    /// ```` ```cpp ````.
+    pub language: Language,
+
+    /// Whether the fence carries the `ignore` attribute, e.g.
+    /// ```` ```c,ignore ````.
+    pub ignore: bool,
+
+    /// The content of the code block.
+    pub source: String,
+}
+
+/// Extract all the C and C++ code blocks from a file.
+///
+/// Markdown files (`.md` or `.markdown`) are scanned as is. Any other
+/// file is considered to be a Rust source file, and only its doc
+/// comments (`///` and `//!`) are scanned.
+pub fn extract<P: AsRef<Path>>(path: P) -> Result<Vec<CodeBlock>, Box<dyn Error>> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)?;
+    let markdown = matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("md") | Some("markdown")
+    );
+
+    Ok(extract_from_str(path, &content, markdown))
+}
+
+fn extract_from_str(path: &Path, content: &str, markdown: bool) -> Vec<CodeBlock> {
+    struct Opened {
+        fence: String,
+        line: usize,
+        kind: Option<(Language, bool)>,
+        source: String,
+    }
+
+    let mut blocks = Vec::new();
+    let mut opened: Option<Opened> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let line = if markdown {
+            line
+        } else {
+            match doc_comment(line) {
+                Some(line) => line,
+                None => {
+                    // A doc comment has ended, so any unterminated
+                    // block is dropped.
+                    opened = None;
+
+                    continue;
+                }
+            }
+        };
+
+        let trimmed = line.trim_start();
+
+        match opened.take() {
+            None => {
+                if let Some(fence) = fence(trimmed) {
+                    opened = Some(Opened {
+                        fence: fence.to_string(),
+                        line: index + 1,
+                        kind: parse_info_string(&trimmed[fence.len()..]),
+                        source: String::new(),
+                    });
+                }
+            }
+
+            Some(mut block) => {
+                if trimmed.trim_end() == block.fence {
+                    if let Some((language, ignore)) = block.kind {
+                        blocks.push(CodeBlock {
+                            path: path.to_path_buf(),
+                            line: block.line,
+                            language,
+                            ignore,
+                            source: block.source,
+                        });
+                    }
+                } else {
+                    block.source.push_str(line);
+                    block.source.push('\n');
+                    opened = Some(block);
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+/// Return the content of a doc comment line, without its marker and
+/// the following space, if any.
+fn doc_comment(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let content = line
+        .strip_prefix("///")
+        .filter(|rest| !rest.starts_with('/'))
+        .or_else(|| line.strip_prefix("//!"))?;
+
+    Some(content.strip_prefix(' ').unwrap_or(content))
+}
+
+fn fence(line: &str) -> Option<&str> {
+    ["```", "~~~"].iter().find_map(|marker| {
+        if line.starts_with(marker) {
+            let length = line.chars().take_while(|c| marker.starts_with(*c)).count();
+
+            Some(&line[..length])
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_info_string(info: &str) -> Option<(Language, bool)> {
+    let mut tokens = info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty());
+
+    let language = match tokens.next()? {
+        "c" | "C" => Language::C,
+        "cpp" | "c++" | "cxx" => Language::Cxx,
+        _ => return None,
+    };
+    let ignore = tokens.any(|token| token == "ignore");
+
+    Some((language, ignore))
+}
+
+/// Extract all the C and C++ code blocks from the given files, and
+/// assert that each of them compiles and runs successfully.
+///
+/// Blocks with the `ignore` attribute are skipped. It panics on the
+/// first failing block, with its location.
+pub fn assert_code_blocks<P: AsRef<Path>>(paths: &[P]) {
+    for path in paths {
+        let path = path.as_ref();
+        let blocks = extract(path)
+            .unwrap_or_else(|error| panic!("Failed to read `{}`: {}", path.display(), error));
+
+        for block in blocks.iter().filter(|block| !block.ignore) {
+            let location = format!("{}:{}", block.path.display(), block.line);
+
+            run(block.language, &block.source)
+                .unwrap_or_else(|error| {
+                    panic!("Failed to run the code block at `{}`: {}", location, error)
+                })
+                .assert()
+                .try_success()
+                .unwrap_or_else(|error| {
+                    panic!("The code block at `{}` has failed: {}", location, error)
+                });
+        }
+    }
+}
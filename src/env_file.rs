@@ -0,0 +1,80 @@
+//! Variables loaded from a `.env` file.
+//!
+//! The file `tests/inline-c.env`, or the one given by the
+//! `INLINE_C_RS_ENV_FILE` environment variable, holds `KEY=VALUE`
+//! lines, e.g. `CFLAGS=-I/opt/wasmer/include`, so that the paths
+//! specific to a machine stay out of the sources and of the CI scripts.
+//! The keys can also be prefixed with `INLINE_C_RS_`. Empty lines and
+//! lines starting with `#` are ignored, and the values can be quoted.
+//!
+//! The variables of the file are overridden by the `INLINE_C_RS_*`
+//! environment variables, which are overridden by the `#inline_c_rs`
+//! directives of the snippet.
+
+use std::{
+    env,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The file loaded when `INLINE_C_RS_ENV_FILE` is not set.
+const DEFAULT_PATH: &str = "tests/inline-c.env";
+
+/// The variables of the `.env` file, if there is one. The default file
+/// may be missing, but not the one given explicitly.
+pub(crate) fn load(prefix: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let (path, explicit) = match env::var_os(format!("{}ENV_FILE", prefix)) {
+        Some(path) => (PathBuf::from(path), true),
+        None => (PathBuf::from(DEFAULT_PATH), false),
+    };
+
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) if !explicit && error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(format!("failed to read `{}`: {}", path.display(), error).into()),
+    };
+
+    parse(&path, &content, prefix)
+}
+
+fn parse(
+    path: &Path,
+    content: &str,
+    prefix: &str,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut variables = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            format!(
+                "{}:{}: expected `KEY=VALUE`, found `{}`",
+                path.display(),
+                index + 1,
+                line
+            )
+        })?;
+        let key = key.trim();
+        let key = key.strip_prefix(prefix).unwrap_or(key);
+        let value = value.trim();
+        let value = ['"', '\'']
+            .iter()
+            .find_map(|quote| {
+                value
+                    .strip_prefix(*quote)
+                    .and_then(|value| value.strip_suffix(*quote))
+            })
+            .unwrap_or(value);
+
+        variables.push((key.to_string(), value.to_string()));
+    }
+
+    Ok(variables)
+}
@@ -0,0 +1,227 @@
+//! Validation of the flags given to the compiler, before it runs.
+//!
+//! The flags come from `cc`, from the `CPPFLAGS`, `CFLAGS`, `CXXFLAGS`
+//! and `LDFLAGS` variables, and from the options of `Assert`. Harmless
+//! mixes are fixed up: the duplicated include directories are dropped,
+//! and only the last C runtime of MSVC is kept, as `cl.exe` would do
+//! with a warning. Flags written for the other kind of compiler fail
+//! with a message naming them, instead of failing deep inside the
+//! compiler.
+//!
+//! These flags are usually written for GCC, so the common ones are
+//! translated to their MSVC spelling first, see [`to_msvc`].
+
+use crate::backend::Family;
+use std::{collections::HashSet, ffi::OsString, io, process::Command};
+
+/// Flags of GCC and Clang that MSVC does not understand. The ones
+/// ending with `=`, `:` or `,` are prefixes.
+const GNU_ONLY: &[&str] = &[
+    "-std=",
+    "-Wl,",
+    "-march=",
+    "-pthread",
+    "-m32",
+    "-m64",
+    "-O3",
+    "-Ofast",
+    "-shared",
+    "-fPIC",
+    "-pedantic",
+];
+
+/// Flags of MSVC that GCC and Clang do not understand. The ones ending
+/// with `=`, `:` or `,` are prefixes.
+const MSVC_ONLY: &[&str] = &[
+    "/MD",
+    "/MDd",
+    "/MT",
+    "/MTd",
+    "/W0",
+    "/W1",
+    "/W2",
+    "/W3",
+    "/W4",
+    "/WX",
+    "/O1",
+    "/O2",
+    "/Od",
+    "/Ox",
+    "/std:",
+    "/EHsc",
+    "/EHs",
+    "/EHa",
+    "/Zi",
+    "/Z7",
+    "/nologo",
+    "/link",
+    "/utf-8",
+    "/permissive-",
+];
+
+/// Translate the GNU-style `flags` to their MSVC spelling, e.g. `-O2`
+/// to `/O2` or `-std=c11` to `/std:c11`. The other flags are kept as
+/// they are.
+pub(crate) fn to_msvc(flags: Vec<String>) -> Vec<String> {
+    flags
+        .into_iter()
+        .map(|flag| match flag.as_str() {
+            "-O0" => "/Od".to_string(),
+            "-O1" | "-Os" => "/O1".to_string(),
+            "-O2" | "-O3" | "-Ofast" => "/O2".to_string(),
+            "-g" => "/Zi".to_string(),
+            "-w" => "/w".to_string(),
+            "-Wall" | "-Wextra" => "/W4".to_string(),
+            "-Werror" => "/WX".to_string(),
+            _ => prefixed_to_msvc(flag),
+        })
+        .collect()
+}
+
+/// Whether `flag` sets the optimization level, like `-O2`, `-Os` or
+/// `-O`, or `/O2` and `-Od` with MSVC.
+pub(crate) fn is_optimization(flag: &str) -> bool {
+    match flag.strip_prefix("-O") {
+        Some(level) => matches!(
+            level,
+            "" | "0" | "1" | "2" | "3" | "s" | "z" | "g" | "d" | "x" | "fast"
+        ),
+        None => matches!(flag, "/O1" | "/O2" | "/Od" | "/Ox"),
+    }
+}
+
+/// Translate a GNU-style flag with a value, e.g. `-Iinclude`.
+fn prefixed_to_msvc(flag: String) -> String {
+    if let Some(standard) = flag.strip_prefix("-std=") {
+        // MSVC has no GNU dialects.
+        return match standard.strip_prefix("gnu") {
+            Some(version) => format!("/std:c{}", version),
+            None => format!("/std:{}", standard),
+        };
+    }
+
+    for prefix in ["-I", "-D", "-U"] {
+        if let Some(value) = flag.strip_prefix(prefix) {
+            return format!("/{}{}", &prefix[1..], value);
+        }
+    }
+
+    flag
+}
+
+/// Replace the arguments of `command` by their normalized version for
+/// the compiler `family`, see [`normalize`].
+pub(crate) fn normalize_command(command: &mut Command, family: Family) -> io::Result<()> {
+    let args = command
+        .get_args()
+        .map(OsString::from)
+        .filter_map(|arg| family.translate(arg))
+        .collect::<Vec<_>>();
+    let args = normalize(&args, family.is_msvc())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+    let mut normalized = Command::new(command.get_program());
+    normalized.args(args);
+
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => normalized.env(key, value),
+            None => normalized.env_remove(key),
+        };
+    }
+
+    if let Some(directory) = command.get_current_dir() {
+        normalized.current_dir(directory);
+    }
+
+    *command = normalized;
+
+    Ok(())
+}
+
+/// Drop the duplicated include directories and the overridden C
+/// runtimes from `args`, or fail if some of them are meant for the
+/// other kind of compiler. The arguments of the MSVC linker, after
+/// `/link`, are kept as they are.
+pub(crate) fn normalize(args: &[OsString], msvc: bool) -> Result<Vec<OsString>, String> {
+    let compiler_args = if msvc {
+        args.iter()
+            .position(|arg| arg == "/link" || arg == "-link")
+            .unwrap_or(args.len())
+    } else {
+        args.len()
+    };
+    let runtime = if msvc {
+        args[..compiler_args]
+            .iter()
+            .rposition(|arg| arg.to_str().is_some_and(is_msvc_runtime))
+    } else {
+        None
+    };
+
+    let mut normalized = Vec::with_capacity(args.len());
+    let mut include_directories = HashSet::new();
+    let mut invalid = Vec::new();
+
+    for (index, arg) in args[..compiler_args].iter().enumerate() {
+        let Some(flag) = arg.to_str() else {
+            normalized.push(arg.clone());
+            continue;
+        };
+
+        if let Some(directory) = include_directory(flag, msvc) {
+            if !include_directories.insert(directory) {
+                continue;
+            }
+        }
+
+        if msvc && is_msvc_runtime(flag) && Some(index) != runtime {
+            continue;
+        }
+
+        let foreign = if msvc { GNU_ONLY } else { MSVC_ONLY };
+
+        if foreign.iter().any(|foreign| matches_flag(flag, foreign)) {
+            invalid.push(flag);
+        }
+
+        normalized.push(arg.clone());
+    }
+
+    if !invalid.is_empty() {
+        return Err(format!(
+            "the flags {:?} are not understood by {}; check the `CPPFLAGS`, `CFLAGS`, `CXXFLAGS` and `LDFLAGS` variables",
+            invalid,
+            if msvc { "MSVC" } else { "GCC or Clang" }
+        ));
+    }
+
+    normalized.extend(args[compiler_args..].iter().cloned());
+
+    Ok(normalized)
+}
+
+/// The directory of an include flag written in one argument, e.g.
+/// `-Iinclude`.
+fn include_directory(flag: &str, msvc: bool) -> Option<&str> {
+    flag.strip_prefix("-I")
+        .or_else(|| flag.strip_prefix("/I").filter(|_| msvc))
+        .filter(|directory| !directory.is_empty())
+}
+
+/// Whether `flag` is `pattern`, or starts with it if it is a prefix.
+fn matches_flag(flag: &str, pattern: &str) -> bool {
+    if pattern.ends_with(['=', ':', ',']) {
+        flag.starts_with(pattern)
+    } else {
+        flag == pattern
+    }
+}
+
+/// Whether `flag` selects the C runtime of MSVC, e.g. `/MD`.
+fn is_msvc_runtime(flag: &str) -> bool {
+    matches!(
+        flag.get(1..),
+        Some("MD" | "MDd" | "MT" | "MTd") if flag.starts_with(['-', '/'])
+    )
+}
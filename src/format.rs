@@ -0,0 +1,63 @@
+//! The formatting of the sources with clang-format, see
+//! `Assert::format_source`, so that the sources kept for debugging,
+//! e.g. with the `ARTIFACT_DIR` variable, are readable.
+//!
+//! The source is formatted before the shims are prepended, so that the
+//! lines of the diagnostics are the lines of the formatted source,
+//! written next to the executable.
+
+use std::{
+    io::{self, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// The `source` of `source_path` formatted by clang-format, with the
+/// `.clang-format` file `style`, or else the one found from the
+/// directory of `source_path`, or the LLVM style.
+pub(crate) fn clang_format(
+    source: &str,
+    source_path: &Path,
+    style: Option<&Path>,
+) -> io::Result<String> {
+    let mut command = Command::new("clang-format");
+
+    match style {
+        Some(style) => command.arg(format!("--style=file:{}", style.display())),
+        None => command.args(["--style=file", "--fallback-style=LLVM"]),
+    };
+
+    // The language is guessed from the extension.
+    let mut child = command
+        .arg(format!("--assume-filename={}", source_path.display()))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!("failed to run clang-format: {}", error),
+            )
+        })?;
+
+    // clang-format reads the whole source before writing anything.
+    child
+        .stdin
+        .take()
+        .expect("the standard input is piped")
+        .write_all(source.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "clang-format has failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        )));
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
@@ -0,0 +1,251 @@
+//! C and C++ test frameworks, whose output is parsed into per-case
+//! results.
+//!
+//! With [`Framework::Unity`], the snippet is compiled along the
+//! `unity.c` of the given Unity source directory. With
+//! [`Framework::GoogleTest`], it is linked against `gtest` and
+//! `gtest_main` from the given installation prefix. Both can also be
+//! configured with the `UNITY` and `GTEST` variables.
+//!
+//! Programs emitting [TAP](https://testanything.org/) are parsed with
+//! [`parse_tap`], see `Assert::tap`.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{ffi::OsString, fmt, path::PathBuf};
+
+/// A test framework the snippet is built with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Framework {
+    /// Unity, from the directory containing `unity.c` and `unity.h`.
+    Unity(PathBuf),
+
+    /// GoogleTest, from the installation prefix containing the
+    /// `include` and `lib` directories.
+    GoogleTest(PathBuf),
+}
+
+/// The outcome of a test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Ignored,
+}
+
+/// The result of a test case, as reported by the test framework.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    /// The name of the test case, e.g. `test_parse` with Unity, or
+    /// `Parser.Empty` with GoogleTest.
+    pub name: String,
+
+    /// The outcome of the test case.
+    pub outcome: Outcome,
+
+    /// The failure or ignore message, if any.
+    pub message: Option<String>,
+}
+
+impl fmt::Display for TestCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:?}", self.name, self.outcome)?;
+
+        if let Some(message) = &self.message {
+            write!(f, " ({})", message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Framework {
+    /// The flags building the snippet with the framework.
+    pub(crate) fn flags(&self, msvc: bool) -> Vec<OsString> {
+        match self {
+            Self::Unity(directory) => {
+                let mut include = OsString::from("-I");
+                include.push(directory);
+
+                vec![include, directory.join("unity.c").into()]
+            }
+
+            Self::GoogleTest(prefix) => {
+                let mut include = OsString::from("-I");
+                include.push(prefix.join("include"));
+
+                let lib = prefix.join("lib");
+
+                if msvc {
+                    vec![
+                        include,
+                        lib.join("gtest.lib").into(),
+                        lib.join("gtest_main.lib").into(),
+                    ]
+                } else {
+                    let mut library_path = OsString::from("-L");
+                    library_path.push(lib);
+
+                    vec![
+                        include,
+                        library_path,
+                        "-lgtest".into(),
+                        "-lgtest_main".into(),
+                        "-pthread".into(),
+                    ]
+                }
+            }
+        }
+    }
+
+    /// Parse the test cases from the standard output of the program.
+    pub fn parse(&self, stdout: &str) -> Vec<TestCase> {
+        match self {
+            Self::Unity(_) => parse_unity(stdout),
+            Self::GoogleTest(_) => parse_gtest(stdout),
+        }
+    }
+}
+
+/// Parse the output of Unity, e.g. `test.c:12:test_parse:FAIL:
+/// Expected 1 Was 2`.
+pub fn parse_unity(stdout: &str) -> Vec<TestCase> {
+    lazy_static! {
+        static ref LINE: Regex = Regex::new(
+            r"^.*?:\d+:(?P<name>[^:]+):(?P<outcome>PASS|FAIL|IGNORE)(?::\s*(?P<message>.*))?$"
+        )
+        .unwrap();
+    }
+
+    stdout
+        .lines()
+        .filter_map(|line| LINE.captures(line.trim_end()))
+        .map(|captures| TestCase {
+            name: captures["name"].to_string(),
+            outcome: match &captures["outcome"] {
+                "PASS" => Outcome::Passed,
+                "FAIL" => Outcome::Failed,
+                _ => Outcome::Ignored,
+            },
+            message: captures
+                .name("message")
+                .map(|message| message.as_str().to_string())
+                .filter(|message| !message.is_empty()),
+        })
+        .collect()
+}
+
+/// Parse the output of GoogleTest, e.g. `[  FAILED  ] Parser.Empty (0
+/// ms)`. The lines printed between `[ RUN      ]` and the result of a
+/// failed test become its message.
+pub fn parse_gtest(stdout: &str) -> Vec<TestCase> {
+    lazy_static! {
+        static ref RESULT: Regex =
+            Regex::new(r"^\[\s*(?P<outcome>OK|FAILED|SKIPPED)\s*\] (?P<name>\S+) \(\d+ ms\)$")
+                .unwrap();
+    }
+
+    let mut test_cases = Vec::new();
+    let mut message = Vec::new();
+
+    for line in stdout.lines().map(str::trim_end) {
+        if line.starts_with("[ RUN") {
+            message.clear();
+        } else if let Some(captures) = RESULT.captures(line) {
+            let outcome = match &captures["outcome"] {
+                "OK" => Outcome::Passed,
+                "FAILED" => Outcome::Failed,
+                _ => Outcome::Ignored,
+            };
+
+            test_cases.push(TestCase {
+                name: captures["name"].to_string(),
+                outcome,
+                message: if outcome == Outcome::Passed || message.is_empty() {
+                    None
+                } else {
+                    Some(message.join("\n"))
+                },
+            });
+
+            message.clear();
+        } else {
+            message.push(line);
+        }
+    }
+
+    test_cases
+}
+
+/// Parse the output of a program emitting TAP, e.g. `not ok 2 - empty
+/// input # TODO`. Skipped tests and failed `TODO` tests are ignored.
+/// The tests announced by the plan but absent from the output, and a
+/// `Bail out!`, are reported as failures.
+pub fn parse_tap(stdout: &str) -> Vec<TestCase> {
+    lazy_static! {
+        static ref PLAN: Regex = Regex::new(r"^1\.\.(?P<count>\d+)").unwrap();
+        static ref TEST: Regex = Regex::new(
+            r"^(?P<not>not )?ok\b\s*(?P<number>\d+)?\s*(?:-\s*)?(?P<description>[^#]*?)\s*(?:#\s*(?P<directive>\S+)\s*(?P<reason>.*))?$"
+        )
+        .unwrap();
+    }
+
+    let mut test_cases = Vec::new();
+    let mut plan = None;
+
+    for line in stdout.lines().map(str::trim_end) {
+        if let Some(captures) = PLAN.captures(line) {
+            plan = captures["count"].parse::<usize>().ok();
+        } else if let Some(reason) = line.strip_prefix("Bail out!") {
+            test_cases.push(TestCase {
+                name: "Bail out!".to_string(),
+                outcome: Outcome::Failed,
+                message: Some(reason.trim().to_string()).filter(|reason| !reason.is_empty()),
+            });
+
+            return test_cases;
+        } else if let Some(captures) = TEST.captures(line) {
+            let number = captures.name("number").map_or_else(
+                || (test_cases.len() + 1).to_string(),
+                |n| n.as_str().to_string(),
+            );
+            let description = &captures["description"];
+            let directive = captures
+                .name("directive")
+                .map(|directive| directive.as_str().to_ascii_uppercase());
+            let reason = captures
+                .name("reason")
+                .map(|reason| reason.as_str().to_string())
+                .filter(|reason| !reason.is_empty());
+
+            let outcome = match (captures.name("not").is_some(), directive.as_deref()) {
+                (_, Some(directive)) if directive.starts_with("SKIP") => Outcome::Ignored,
+                (true, Some("TODO")) => Outcome::Ignored,
+                (true, _) => Outcome::Failed,
+                (false, _) => Outcome::Passed,
+            };
+
+            test_cases.push(TestCase {
+                name: if description.is_empty() {
+                    number
+                } else {
+                    format!("{} - {}", number, description)
+                },
+                outcome,
+                message: reason,
+            });
+        }
+    }
+
+    if let Some(plan) = plan {
+        for number in test_cases.len() + 1..=plan {
+            test_cases.push(TestCase {
+                name: number.to_string(),
+                outcome: Outcome::Failed,
+                message: Some("missing from the output".to_string()),
+            });
+        }
+    }
+
+    test_cases
+}
@@ -0,0 +1,97 @@
+//! libFuzzer harnesses.
+//!
+//! In fuzzing mode, the snippet defines `LLVMFuzzerTestOneInput`
+//! instead of `main`, and is compiled with `-fsanitize=fuzzer`. Each
+//! run is bounded, by a number of iterations or by a duration. A crash
+//! fails the assertion, and its reproducer is kept next to the
+//! executable.
+
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The number of iterations when the run is not bounded otherwise.
+const DEFAULT_RUNS: u64 = 10_000;
+
+/// The bounds and the inputs of a fuzzing run.
+#[derive(Debug, Clone, Default)]
+pub struct Fuzz {
+    /// The maximum number of iterations, `-runs`.
+    pub runs: Option<u64>,
+
+    /// The maximum duration of the run, `-max_total_time`, rounded up
+    /// to the second.
+    pub max_total_time: Option<Duration>,
+
+    /// The directory of the seed corpus. libFuzzer adds the
+    /// interesting inputs it finds to it.
+    pub corpus: Option<PathBuf>,
+}
+
+impl Fuzz {
+    /// The arguments of the harness, writing the reproducers of the
+    /// crashes next to `executable`.
+    pub(crate) fn args(&self, executable: &Path) -> Vec<OsString> {
+        let mut args = Vec::new();
+
+        if let Some(max_total_time) = self.max_total_time {
+            let seconds = max_total_time.as_secs() + u64::from(max_total_time.subsec_nanos() > 0);
+            args.push(format!("-max_total_time={}", seconds).into());
+        }
+
+        match (self.runs, self.max_total_time) {
+            (Some(runs), _) => args.push(format!("-runs={}", runs).into()),
+            (None, None) => args.push(format!("-runs={}", DEFAULT_RUNS).into()),
+            (None, Some(_)) => {}
+        }
+
+        let mut artifact_prefix = OsString::from("-artifact_prefix=");
+        artifact_prefix.push(reproducer_prefix(executable));
+        args.push(artifact_prefix);
+
+        if let Some(corpus) = &self.corpus {
+            args.push(corpus.into());
+        }
+
+        args
+    }
+}
+
+/// The reproducers written by a harness, e.g. `crash-<sha1>`.
+pub(crate) fn reproducers(executable: &Path) -> Vec<PathBuf> {
+    let prefix = reproducer_prefix(executable);
+    let (Some(directory), Some(prefix)) = (prefix.parent(), prefix.file_name()) else {
+        return Vec::new();
+    };
+    let prefix = prefix.to_string_lossy();
+
+    let mut reproducers = fs::read_dir(directory)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy())
+                .and_then(|name| name.strip_prefix(&*prefix).map(ToString::to_string))
+                .is_some_and(|kind| {
+                    ["crash-", "leak-", "timeout-", "oom-"]
+                        .iter()
+                        .any(|kind_prefix| kind.starts_with(kind_prefix))
+                })
+        })
+        .collect::<Vec<_>>();
+    reproducers.sort();
+
+    reproducers
+}
+
+fn reproducer_prefix(executable: &Path) -> PathBuf {
+    let mut prefix = executable.as_os_str().to_os_string();
+    prefix.push("-");
+
+    PathBuf::from(prefix)
+}
@@ -0,0 +1,136 @@
+//! Resolution of the headers included by a snippet.
+//!
+//! Only the quoted includes, `#include "header.h"`, are followed: they
+//! are looked up next to the including header, then in the include
+//! directories. The headers that cannot be found are left to the
+//! compiler. Each header is visited once, so mutually-including headers
+//! are walked once, and the chains deeper than [`MAX_DEPTH`] fail with
+//! the chain in the error.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The maximum length of an include chain.
+pub const MAX_DEPTH: usize = 64;
+
+/// The headers included by `source`, directly or not, found in the
+/// include `directories`. They are listed once each, in the order they
+/// are first included, and canonicalized when possible.
+pub fn resolve(source: &str, directories: &[PathBuf]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut walker = Walker::new(directories, |_| Ok(()));
+    walker.source(source)?;
+
+    Ok(walker.visited)
+}
+
+/// A traversal of the include graph, calling `visit` on each header
+/// before it is read.
+pub(crate) struct Walker<'d, F> {
+    directories: &'d [PathBuf],
+    visit: F,
+    visited: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+}
+
+impl<'d, F> Walker<'d, F>
+where
+    F: FnMut(&Path) -> io::Result<()>,
+{
+    pub(crate) fn new(directories: &'d [PathBuf], visit: F) -> Self {
+        Self {
+            directories,
+            visit,
+            visited: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Walk the headers included by `source`.
+    pub(crate) fn source(&mut self, source: &str) -> Result<(), Box<dyn Error>> {
+        for include in includes(source) {
+            if let Some(header) = self.find(None, include) {
+                self.walk(header, &mut Vec::new())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Walk `header` and the headers it includes.
+    pub(crate) fn header(&mut self, header: &Path) -> Result<(), Box<dyn Error>> {
+        self.walk(canonicalize(header), &mut Vec::new())
+    }
+
+    fn walk(&mut self, header: PathBuf, chain: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+        if self.seen.contains(&header) {
+            return Ok(());
+        }
+
+        if chain.len() >= MAX_DEPTH {
+            chain.push(header);
+
+            return Err(format!(
+                "the include chain is deeper than {} headers:\n  {}",
+                MAX_DEPTH,
+                chain
+                    .iter()
+                    .map(|header| header.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n  includes ")
+            )
+            .into());
+        }
+
+        self.seen.insert(header.clone());
+        (self.visit)(&header)?;
+
+        let content = fs::read_to_string(&header)
+            .map_err(|error| format!("failed to read `{}`: {}", header.display(), error))?;
+        let parent = header.parent().map(Path::to_path_buf);
+        self.visited.push(header.clone());
+        chain.push(header);
+
+        for include in includes(&content) {
+            if let Some(included) = self.find(parent.as_deref(), include) {
+                self.walk(included, chain)?;
+            }
+        }
+
+        chain.pop();
+
+        Ok(())
+    }
+
+    /// Look `include` up next to the including header, if any, then in
+    /// the include directories.
+    fn find(&self, parent: Option<&Path>, include: &str) -> Option<PathBuf> {
+        parent
+            .into_iter()
+            .chain(self.directories.iter().map(PathBuf::as_path))
+            .map(|directory| directory.join(include))
+            .find(|path| path.is_file())
+            .map(|path| canonicalize(&path))
+    }
+}
+
+/// The quoted includes of `source`.
+fn includes(source: &str) -> impl Iterator<Item = &str> {
+    lazy_static! {
+        static ref INCLUDE: Regex = Regex::new(r#"(?m)^\s*#\s*include\s*"([^"]+)""#).unwrap();
+    }
+
+    INCLUDE
+        .captures_iter(source)
+        .filter_map(|captures| captures.get(1))
+        .map(|include| include.as_str())
+}
+
+fn canonicalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
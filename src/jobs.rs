@@ -0,0 +1,34 @@
+//! The limit of the compilations running at once in the test binary,
+//! see `Assert::compile_jobs`, independent of the number of test
+//! threads, so that many concurrent compilers, e.g. `cl.exe`, do not
+//! thrash the machine.
+
+use std::sync::{Condvar, Mutex, PoisonError};
+
+/// The number of compilations running.
+static RUNNING: Mutex<usize> = Mutex::new(0);
+
+/// Notified when a compilation finishes.
+static FINISHED: Condvar = Condvar::new();
+
+/// The permission to compile, released when dropped.
+pub(crate) struct Permit(());
+
+/// Wait until less than `limit` compilations are running, and count one
+/// more until the returned permit is dropped.
+pub(crate) fn acquire(limit: usize) -> Permit {
+    let running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut running = FINISHED
+        .wait_while(running, |running| *running >= limit.max(1))
+        .unwrap_or_else(PoisonError::into_inner);
+    *running += 1;
+
+    Permit(())
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        *RUNNING.lock().unwrap_or_else(PoisonError::into_inner) -= 1;
+        FINISHED.notify_all();
+    }
+}
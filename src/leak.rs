@@ -0,0 +1,81 @@
+//! Memory leak detection, with the CRT debug heap or LeakSanitizer.
+//!
+//! On MSVC, where no sanitizer is needed, the program is linked with
+//! the debug C runtime, and a shim prepended to its source enables the
+//! leak check of the CRT debug heap before `main`. The CRT dumps the
+//! leaked blocks to the standard error when the program exits, and the
+//! dump fails the assertion.
+//!
+//! With GCC and Clang, the program is built with LeakSanitizer, with
+//! `-fsanitize=leak`, optionally given a suppressions file. Its report
+//! fails the assertion the same way.
+
+use std::path::Path;
+
+/// The source enabling the leak check of the CRT debug heap, run as a
+/// C initializer. The `#line` directive keeps the diagnostics pointing
+/// at the lines of the snippet.
+pub(crate) const CRT_SHIM: &str = r#"#include <crtdbg.h>
+
+static void __cdecl inline_c_rs_crt_leak_check(void) {
+    _CrtSetDbgFlag(_CrtSetDbgFlag(_CRTDBG_REPORT_FLAG) | _CRTDBG_ALLOC_MEM_DF | _CRTDBG_LEAK_CHECK_DF);
+    _CrtSetReportMode(_CRT_WARN, _CRTDBG_MODE_FILE);
+    _CrtSetReportFile(_CRT_WARN, _CRTDBG_FILE_STDERR);
+}
+
+#pragma section(".CRT$XCU", read)
+__declspec(allocate(".CRT$XCU")) void (__cdecl *inline_c_rs_crt_leak_check_init)(void) = inline_c_rs_crt_leak_check;
+
+#line 1
+"#;
+
+/// The first line of the dump of the CRT debug heap.
+const CRT_HEADER: &str = "Detected memory leaks!";
+
+/// The last line of the dump of the CRT debug heap.
+const CRT_FOOTER: &str = "Object dump complete.";
+
+/// The leaks dumped by the CRT debug heap to `stderr`, if any.
+pub(crate) fn crt_report(stderr: &str) -> Option<&str> {
+    let start = stderr.find(CRT_HEADER)?;
+    let report = &stderr[start..];
+    let end = report
+        .find(CRT_FOOTER)
+        .map_or(report.len(), |end| end + CRT_FOOTER.len());
+
+    Some(&report[..end])
+}
+
+/// The first line of the report of LeakSanitizer.
+const LSAN_HEADER: &str = "ERROR: LeakSanitizer: detected memory leaks";
+
+/// The prefix of the last line of the report of LeakSanitizer.
+const LSAN_FOOTER: &str = "SUMMARY: LeakSanitizer:";
+
+/// The `LSAN_OPTIONS` of the program: the `options` already set, if
+/// any, followed by the `suppressions` file.
+pub(crate) fn lsan_options(options: Option<&str>, suppressions: Option<&Path>) -> String {
+    let mut options = options
+        .map(str::trim)
+        .filter(|options| !options.is_empty())
+        .map(ToString::to_string)
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    if let Some(suppressions) = suppressions {
+        options.push(format!("suppressions={}", suppressions.display()));
+    }
+
+    options.join(":")
+}
+
+/// The leaks reported by LeakSanitizer to `stderr`, if any.
+pub(crate) fn lsan_report(stderr: &str) -> Option<&str> {
+    let start = stderr.find(LSAN_HEADER)?;
+    let report = &stderr[start..];
+    let end = report.find(LSAN_FOOTER).map_or(report.len(), |end| {
+        end + report[end..].find('\n').unwrap_or(report.len() - end)
+    });
+
+    Some(&report[..end])
+}
@@ -1,102 +1,4717 @@
 pub mod assert {
-    use std::{fs, path::PathBuf, process::Command};
+    #[cfg(feature = "proptest")]
+    use crate::property;
+    #[cfg(feature = "wasm")]
+    use crate::wasm;
+    use crate::{
+        backend::Family,
+        batch, build_dir, cache,
+        diagnostic::{self, Diagnostic, Severity},
+        flags, format,
+        framework::{self, Framework, Outcome, TestCase},
+        fuzz::{self, Fuzz},
+        jobs, leak,
+        network::{self, Network},
+        output::{self, Chunk, Encoding, Stream},
+        pe, predicates, race, replay, report, resource, retry,
+        run::{self, Language},
+        runner::Runner,
+        sandbox, scheduling,
+        stamp::Stamp,
+        template, workspace,
+    };
+    use std::{
+        collections::HashMap,
+        ffi::{OsStr, OsString},
+        fs, io, panic,
+        path::{Path, PathBuf},
+        process::{Command, ExitStatus, Output},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex, PoisonError,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// The raw result of running the program, for tests that need to
+    /// parse its output themselves rather than using predicates.
+    #[derive(Debug, Clone)]
+    pub struct RunResult {
+        /// The exit code, or `None` if the program has been terminated
+        /// by a signal.
+        pub exit_code: Option<i32>,
+
+        /// The standard output.
+        pub stdout: Vec<u8>,
+
+        /// The standard error.
+        pub stderr: Vec<u8>,
+
+        /// The time the program took to run.
+        pub duration: Duration,
+
+        /// The lines of the standard output and of the standard error,
+        /// in the order they have been received, if the output has been
+        /// captured with [`Assert::interleaved`].
+        pub chunks: Vec<Chunk>,
+    }
+
+    impl RunResult {
+        /// Whether the program has exited with a zero exit code.
+        pub fn success(&self) -> bool {
+            self.exit_code == Some(0)
+        }
+
+        /// The index in [`RunResult::chunks`] of the first line of
+        /// `stream` containing `needle`.
+        pub fn position(&self, stream: Stream, needle: &str) -> Option<usize> {
+            self.chunks.iter().position(|chunk| {
+                chunk.stream == stream && String::from_utf8_lossy(&chunk.bytes).contains(needle)
+            })
+        }
+    }
+
+    /// How a library of [`Assert::link_library`] is linked.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum LinkKind {
+        /// The way the linker prefers, usually the shared library.
+        Default,
+        Static,
+        Dynamic,
+    }
+
+    /// A compiler invocation, building the executable or one slice of
+    /// a universal binary.
+    pub(crate) struct Compiler {
+        pub(crate) command: Command,
+
+        /// The arguments given after the input file to link it, e.g.
+        /// `LDFLAGS`. They are dropped when the program is only
+        /// compiled.
+        pub(crate) link_args: Vec<OsString>,
+
+        /// Whether the compiler is like MSVC, e.g. `cl.exe` or
+        /// `clang-cl`, which spells its flags differently.
+        pub(crate) msvc: bool,
+
+        /// The family of the compiler.
+        pub(crate) family: Family,
+
+        /// The target triple.
+        pub(crate) target: String,
+    }
+
+    /// The largest number of defines of [`Assert::defines_matrix`], whose
+    /// configurations are built one after the other.
+    pub const MAX_MATRIX_DEFINES: usize = 8;
+
+    /// The environment of a deterministic run: the C locale, UTC, and
+    /// fixed timestamp and seed.
+    const DETERMINISTIC_ENVS: &[(&str, &str)] = &[
+        ("LC_ALL", "C"),
+        ("TZ", "UTC"),
+        ("SOURCE_DATE_EPOCH", "0"),
+        ("SEED", "0"),
+    ];
+
+    type SourceHook = Box<dyn FnMut(&mut String) + Send>;
+    type CompiledHook = Box<dyn FnMut(&Path) + Send>;
+    type FinishedHook = Box<dyn FnMut(&RunResult) + Send>;
+    type BuildHook = Box<dyn FnMut(&mut cc::Build) + Send>;
+
+    /// `Assert` is a wrapper around the [`assert_cmd::assert::Assert`]
+    /// struct.
+    ///
+    /// The program is compiled lazily, the first time it is run.
+    pub struct Assert {
+        source_path: PathBuf,
+        source: String,
+        template: HashMap<String, String>,
+        language: Option<Language>,
+        executable: PathBuf,
+        compiler: Compiler,
+        slices: Vec<Compiler>,
+        pre_build_steps: Vec<Command>,
+        build_steps: Vec<Command>,
+        post_link_steps: Vec<(Command, Vec<String>)>,
+        compilation: Option<Output>,
+        runner: Runner,
+        args: Vec<OsString>,
+        envs: HashMap<String, String>,
+        current_dir: Option<PathBuf>,
+        stdin: Option<Vec<u8>>,
+        files_to_remove: Option<Vec<PathBuf>>,
+        keep_artifacts: bool,
+        freestanding: bool,
+        openmp: bool,
+        threads: bool,
+        math: bool,
+        resources: Vec<PathBuf>,
+        manifest: Option<PathBuf>,
+        linker_map: bool,
+        cache: Option<PathBuf>,
+        batch: bool,
+        dependencies: Vec<PathBuf>,
+        denied_warnings: Vec<String>,
+        system_includes: Vec<PathBuf>,
+        raw_compiler_args: Vec<OsString>,
+        raw_linker_args: Vec<OsString>,
+        libraries: Vec<(String, LinkKind)>,
+        library_directories: Vec<PathBuf>,
+        link_group: bool,
+        rpath: bool,
+        stage_dlls: bool,
+        dll_directories: Vec<PathBuf>,
+        workspace_crates: Vec<workspace::Library>,
+        compile_time: Option<Duration>,
+        cache_hit: bool,
+        crt_leak_check: bool,
+        leak_sanitizer: bool,
+        lsan_suppressions: Option<PathBuf>,
+        thread_sanitizer: bool,
+        tsan_suppressions: Option<PathBuf>,
+        record: Option<PathBuf>,
+        replay: Option<PathBuf>,
+        replayed_run: Option<Output>,
+        framework: Option<Framework>,
+        fuzz: Option<Fuzz>,
+        strip_ansi: bool,
+        output_encoding: Option<Encoding>,
+        network: Network,
+        sandbox: bool,
+        priority: Option<i32>,
+        cpu_affinity: Vec<usize>,
+        tee: bool,
+        interleaved: bool,
+        deterministic: bool,
+        update_golden: bool,
+        retries: usize,
+        compile_jobs: Option<usize>,
+        finishes_within: Option<Duration>,
+        compare_optimizations: bool,
+        format_source: bool,
+        clang_format_file: Option<PathBuf>,
+        stamp: bool,
+        source_hooks: Vec<SourceHook>,
+        compiled_hooks: Vec<CompiledHook>,
+        finished_hooks: Vec<FinishedHook>,
+        build_hooks: Vec<BuildHook>,
+        report: Option<report::Pending>,
+    }
+
+    impl Assert {
+        pub(crate) fn new(
+            source_path: PathBuf,
+            source: String,
+            executable: PathBuf,
+            compiler: Compiler,
+            runner: Runner,
+            envs: HashMap<String, String>,
+            files_to_remove: Option<Vec<PathBuf>>,
+        ) -> Self {
+            Self {
+                source_path,
+                source,
+                template: HashMap::new(),
+                language: None,
+                executable,
+                compiler,
+                slices: Vec::new(),
+                pre_build_steps: Vec::new(),
+                build_steps: Vec::new(),
+                post_link_steps: Vec::new(),
+                compilation: None,
+                runner,
+                args: Vec::new(),
+                envs,
+                current_dir: None,
+                stdin: None,
+                files_to_remove,
+                keep_artifacts: false,
+                freestanding: false,
+                openmp: false,
+                threads: false,
+                math: false,
+                resources: Vec::new(),
+                manifest: None,
+                linker_map: false,
+                cache: None,
+                batch: false,
+                dependencies: Vec::new(),
+                denied_warnings: Vec::new(),
+                system_includes: Vec::new(),
+                raw_compiler_args: Vec::new(),
+                raw_linker_args: Vec::new(),
+                libraries: Vec::new(),
+                library_directories: Vec::new(),
+                link_group: false,
+                rpath: false,
+                stage_dlls: false,
+                dll_directories: Vec::new(),
+                workspace_crates: Vec::new(),
+                compile_time: None,
+                cache_hit: false,
+                crt_leak_check: false,
+                leak_sanitizer: false,
+                lsan_suppressions: None,
+                thread_sanitizer: false,
+                tsan_suppressions: None,
+                record: None,
+                replay: None,
+                replayed_run: None,
+                framework: None,
+                fuzz: None,
+                strip_ansi: false,
+                output_encoding: None,
+                network: Network::Inherit,
+                sandbox: false,
+                priority: None,
+                cpu_affinity: Vec::new(),
+                tee: false,
+                interleaved: false,
+                deterministic: false,
+                update_golden: false,
+                retries: 0,
+                compile_jobs: None,
+                finishes_within: None,
+                compare_optimizations: false,
+                format_source: false,
+                clang_format_file: None,
+                stamp: false,
+                source_hooks: Vec::new(),
+                compiled_hooks: Vec::new(),
+                finished_hooks: Vec::new(),
+                build_hooks: Vec::new(),
+                report: None,
+            }
+        }
+
+        pub(crate) fn language(&mut self, language: Language) {
+            self.language = Some(language);
+        }
+
+        pub(crate) fn report(&mut self, report: report::Pending) {
+            self.report = Some(report);
+        }
+
+        /// Add a compiler building another slice of a universal binary.
+        pub(crate) fn slice(&mut self, compiler: Compiler) {
+            self.slices.push(compiler);
+        }
+
+        /// Add a command to run before the compilers, e.g. to assemble
+        /// the snippet with MASM. Its failure is reported as a
+        /// compilation failure.
+        pub(crate) fn pre_build_step(&mut self, command: Command) {
+            self.pre_build_steps.push(command);
+        }
+
+        /// Add a command to run after the compilers, e.g. to merge the
+        /// slices of a universal binary. Its failure is reported as a
+        /// compilation failure.
+        pub(crate) fn build_step(&mut self, command: Command) {
+            self.build_steps.push(command);
+        }
+
+        pub(crate) fn update_golden(&mut self, update_golden: bool) {
+            self.update_golden = update_golden;
+        }
+
+        /// The files generated to run the program: the source file,
+        /// the intermediate object file, and the executable.
+        pub fn artifacts(&self) -> &[PathBuf] {
+            self.files_to_remove.as_deref().unwrap_or_default()
+        }
+
+        /// Do not remove the generated files when `Assert` is dropped,
+        /// so that they can be inspected.
+        pub fn keep_artifacts(&mut self) -> &mut Self {
+            self.keep_artifacts = true;
+
+            self
+        }
+
+        /// Write the generated files in `directory`, instead of
+        /// `target/inline-c/<crate>/`, e.g. when the temporary
+        /// directory is locked down. It must be called before the
+        /// program is compiled. It can also be set with the
+        /// `ARTIFACT_DIR` variable.
+        pub fn artifact_dir<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            assert!(
+                self.compilation.is_none(),
+                "The artifact directory must be set before the program is compiled"
+            );
+
+            let directory = directory.as_ref();
+            fs::create_dir_all(directory).unwrap_or_else(|error| {
+                panic!("Failed to create `{}`: {}", directory.display(), error)
+            });
+
+            let Some(artifacts) = self.files_to_remove.clone() else {
+                return self;
+            };
+            let mut moved = Vec::with_capacity(artifacts.len());
+
+            for artifact in artifacts {
+                let Some(name) = artifact.file_name() else {
+                    continue;
+                };
+                let relocated = directory.join(name);
+
+                if artifact.exists() {
+                    fs::rename(&artifact, &relocated)
+                        .or_else(|_| {
+                            fs::copy(&artifact, &relocated).and_then(|_| fs::remove_file(&artifact))
+                        })
+                        .unwrap_or_else(|error| {
+                            panic!("Failed to move `{}`: {}", artifact.display(), error)
+                        });
+                }
+
+                moved.push((artifact, relocated));
+            }
+
+            let relocate = |path: &mut PathBuf| {
+                if let Some((_, relocated)) = moved.iter().find(|(artifact, _)| *artifact == *path)
+                {
+                    *path = relocated.clone();
+                }
+            };
+
+            relocate(&mut self.source_path);
+            relocate(&mut self.executable);
+
+            let commands = std::iter::once(&mut self.compiler.command)
+                .chain(self.slices.iter_mut().map(|compiler| &mut compiler.command))
+                .chain(self.pre_build_steps.iter_mut())
+                .chain(self.build_steps.iter_mut());
+
+            for command in commands {
+                relocate_args(command, &moved);
+            }
+
+            self.files_to_remove =
+                Some(moved.into_iter().map(|(_, relocated)| relocated).collect());
+
+            self
+        }
+
+        /// Compile the program without the hosted C library, with
+        /// `-ffreestanding -nostdlib` or `/kernel` on MSVC, to check
+        /// that it does not depend on it. The program is compiled but
+        /// not linked nor run: the assertions apply to the output of
+        /// the compiler. It can also be enabled with the
+        /// `FREESTANDING` variable.
+        pub fn freestanding(&mut self) -> &mut Self {
+            self.freestanding = true;
+
+            self
+        }
+
+        /// Compile the program with OpenMP, with `-fopenmp` or
+        /// `/openmp` on MSVC, and link it with the OpenMP runtime. It
+        /// can also be enabled with the `OPENMP` variable.
+        pub fn openmp(&mut self, openmp: bool) -> &mut Self {
+            self.openmp = openmp;
+
+            self
+        }
+
+        /// Compile and link the program with POSIX threads, with
+        /// `-pthread`. It does nothing on MSVC. It can also be enabled
+        /// with the `THREADS` variable.
+        pub fn threads(&mut self, threads: bool) -> &mut Self {
+            self.threads = threads;
+
+            self
+        }
+
+        /// Link the program with the math library, with `-lm`. It does
+        /// nothing on MSVC, where the math functions are part of the C
+        /// runtime. It can also be enabled with the `MATH` variable.
+        pub fn math(&mut self, math: bool) -> &mut Self {
+            self.math = math;
+
+            self
+        }
+
+        /// Compile the Windows resource `script`, e.g. a version
+        /// resource, and link it into the program. It can also be set
+        /// with the `RESOURCE` variable.
+        pub fn resource<P>(&mut self, script: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.resources.push(script.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Embed the Windows application `manifest` into the program,
+        /// e.g. to declare it DPI-aware. It can also be set with the
+        /// `MANIFEST` variable.
+        pub fn manifest<P>(&mut self, manifest: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.manifest = Some(manifest.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Make the linker write a map of the program, with `/MAP` or
+        /// `-Wl,-Map`, next to the executable. See
+        /// [`Assert::map_file`]. It can also be enabled with the
+        /// `LINKER_MAP` variable.
+        pub fn linker_map(&mut self, linker_map: bool) -> &mut Self {
+            self.linker_map = linker_map;
+
+            self
+        }
+
+        /// Cache the executable in `directory`, and reuse it as long as
+        /// the source, the compiler command, and the included headers
+        /// are the same. See the [`cache`](crate::cache) module. It can
+        /// also be set with the `CACHE` variable.
+        pub fn cache<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.cache = Some(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Link the object compiled for the program by the batch of all
+        /// the snippets of the package, when there is one, instead of
+        /// compiling it on its own. See the [`batch`](crate::batch)
+        /// module. It can also be enabled with the `BATCH` variable.
+        pub fn batch(&mut self, batch: bool) -> &mut Self {
+            self.batch = batch;
+
+            self
+        }
+
+        /// The headers the program includes, directly or not, found in
+        /// its include directories once it has been compiled. See
+        /// [`includes::resolve`](crate::includes::resolve).
+        pub fn dependencies(&self) -> &[PathBuf] {
+            &self.dependencies
+        }
+
+        /// The duration of the compilation, including the build steps,
+        /// once the program has been compiled. It is the time taken to
+        /// restore the executable when it was cached.
+        pub fn compile_time(&self) -> Option<Duration> {
+            self.compile_time
+        }
+
+        /// Whether the executable has been restored from the cache, see
+        /// [`Assert::cache`].
+        pub fn cache_hit(&self) -> bool {
+            self.cache_hit
+        }
+
+        /// Check the program for memory leaks with the CRT debug heap,
+        /// on MSVC: the program is linked with the debug C runtime,
+        /// with `/MDd`, and the assertion fails if the CRT reports
+        /// leaks when the program exits. It does nothing with the
+        /// other compilers. It can also be enabled with the
+        /// `CRT_LEAK_CHECK` variable.
+        pub fn crt_leak_check(&mut self, crt_leak_check: bool) -> &mut Self {
+            self.crt_leak_check = crt_leak_check;
+
+            self
+        }
+
+        /// Build the program with LeakSanitizer, with
+        /// `-fsanitize=leak`, and fail the assertion with its report if
+        /// the program leaks memory. It does nothing on MSVC, see
+        /// [`Assert::crt_leak_check`]. It can also be enabled with the
+        /// `LEAK_SANITIZER` variable.
+        pub fn leak_sanitizer(&mut self, leak_sanitizer: bool) -> &mut Self {
+            self.leak_sanitizer = leak_sanitizer;
+
+            self
+        }
+
+        /// Give a suppressions file to LeakSanitizer, e.g. with
+        /// `leak:third_party_init` lines, for the leaks that are known
+        /// and accepted. It can also be set with the
+        /// `LSAN_SUPPRESSIONS` variable.
+        pub fn lsan_suppressions<P>(&mut self, suppressions: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.lsan_suppressions = Some(suppressions.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Build the program with ThreadSanitizer, with
+        /// `-fsanitize=thread`, and fail the assertion with its reports
+        /// if the program has data races, e.g. to check the concurrent
+        /// parts of a library. On Linux, the program is run with
+        /// `setarch -R`, since the runtime of ThreadSanitizer does not
+        /// support the address space randomization of the recent
+        /// kernels. It does nothing on MSVC. It can also be enabled
+        /// with the `THREAD_SANITIZER` variable.
+        pub fn thread_sanitizer(&mut self, thread_sanitizer: bool) -> &mut Self {
+            self.thread_sanitizer = thread_sanitizer;
+
+            self
+        }
+
+        /// Give a suppressions file to ThreadSanitizer, e.g. with
+        /// `race:legacy_cache` lines, for the races that are known and
+        /// accepted. It can also be set with the `TSAN_SUPPRESSIONS`
+        /// variable.
+        pub fn tsan_suppressions<P>(&mut self, suppressions: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.tsan_suppressions = Some(suppressions.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Record the command and the output of the compiler, and the
+        /// command and the output of the program, in the replay bundle
+        /// `directory`, see the [`replay`](crate::replay) module, e.g.
+        /// to triage locally a failure only occurring in CI with
+        /// [`Assert::replay`]. It can also be set with the `RECORD`
+        /// variable.
+        pub fn record<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.record = Some(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Take the outputs of the compiler and of the program from the
+        /// replay bundle `directory`, recorded with [`Assert::record`],
+        /// instead of compiling and running the program, so that the
+        /// assertions are checked again without the toolchain. It fails
+        /// if the bundle has been recorded from another source. It can
+        /// also be set with the `REPLAY` variable.
+        pub fn replay<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.replay = Some(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Build the program with a test framework, whose results are
+        /// returned by [`Assert::test_cases`]. It can also be set with
+        /// the `UNITY` or `GTEST` variables.
+        pub fn framework(&mut self, framework: Framework) -> &mut Self {
+            self.framework = Some(framework);
+
+            self
+        }
+
+        /// Compile the program as a libFuzzer harness, with
+        /// `-fsanitize=fuzzer`, and run it within the bounds of `fuzz`.
+        /// The program defines `LLVMFuzzerTestOneInput` instead of
+        /// `main`. When it crashes, the assertion fails and the path of
+        /// the reproducer, which is kept, is part of the failure
+        /// message. It can also be enabled with the `FUZZ` variable,
+        /// and bounded with the `FUZZ_RUNS`, `FUZZ_TIME` (in seconds)
+        /// and `FUZZ_CORPUS` variables.
+        pub fn fuzz(&mut self, fuzz: Fuzz) -> &mut Self {
+            self.fuzz = Some(fuzz);
+
+            self
+        }
+
+        /// Strip the ANSI escape sequences, e.g. colors, from the
+        /// standard output and the standard error before they are
+        /// asserted. It can also be enabled with the `STRIP_ANSI`
+        /// variable.
+        pub fn strip_ansi(&mut self) -> &mut Self {
+            self.strip_ansi = true;
+
+            self
+        }
+
+        /// Decode the standard output and the standard error of the
+        /// program from `encoding` to UTF-8 before they are asserted,
+        /// e.g. for the programs built with MSVC writing in the OEM code
+        /// page of the console, or in UTF-16. The lines recorded by
+        /// [`Assert::interleaved`] are left as they are received. It
+        /// can also be set with the `OUTPUT_ENCODING` variable, e.g.
+        /// `auto`, `utf-16le` or `cp437`.
+        pub fn output_encoding(&mut self, encoding: Encoding) -> &mut Self {
+            self.output_encoding = Some(encoding);
+
+            self
+        }
+
+        /// Deny the program the access to the network, e.g. to check
+        /// that a code path of a library never touches it, or require
+        /// it, so that the assertion fails early when the host is
+        /// offline, see the [`network`](crate::network) module. The
+        /// assertion fails when the network cannot be denied with the
+        /// host or the runner. It can also be set with the `NETWORK`
+        /// variable, `inherit`, `deny` or `require`.
+        pub fn network(&mut self, network: Network) -> &mut Self {
+            self.network = network;
+
+            self
+        }
+
+        /// Run the program in a sandbox where it can only write in its
+        /// working directory, given by [`Assert::current_dir`] or else
+        /// the one of the tests, so that a bug in experimental code
+        /// cannot damage the machine, see the
+        /// [`sandbox`](crate::sandbox) module. The assertion fails when
+        /// the program cannot be sandboxed with the host or the runner.
+        /// It can also be enabled with the `SANDBOX` variable.
+        pub fn sandbox(&mut self, sandbox: bool) -> &mut Self {
+            self.sandbox = sandbox;
+
+            self
+        }
+
+        /// Run the program with the niceness `priority`, from -20, the
+        /// highest priority, which usually requires privileges, to 19,
+        /// the lowest, or with the closest priority class on Windows,
+        /// e.g. to reduce the noise of a benchmark, see the
+        /// [`scheduling`](crate::scheduling) module. It can also be set
+        /// with the `PRIORITY` variable.
+        pub fn priority(&mut self, priority: i32) -> &mut Self {
+            self.priority = Some(priority.clamp(-20, 19));
+
+            self
+        }
+
+        /// Run the program on the CPUs `cpus` only, numbered from 0,
+        /// e.g. on a single one to reproduce a race condition that only
+        /// occurs there. The assertion fails when the program cannot be
+        /// pinned with the host or the runner, see the
+        /// [`scheduling`](crate::scheduling) module. It can also be set
+        /// with the `CPU_AFFINITY` variable, a comma-separated list.
+        pub fn cpu_affinity(&mut self, cpus: &[usize]) -> &mut Self {
+            self.cpu_affinity = cpus.to_vec();
+
+            self
+        }
+
+        /// Wait, before compiling the program, until less than `jobs`
+        /// compilations are running in the test binary, whatever the
+        /// number of test threads, so that many concurrent compilers do
+        /// not thrash the machine. It can also be set with the
+        /// `COMPILE_JOBS` variable.
+        pub fn compile_jobs(&mut self, jobs: usize) -> &mut Self {
+            self.compile_jobs = Some(jobs);
+
+            self
+        }
+
+        /// Fail the assertion if the program has run for longer than
+        /// `budget`, e.g. to check the latency of a C API. Unlike a
+        /// timeout, the program is not killed: it runs to its end, and
+        /// the assertion fails with its output. It can also be set with
+        /// the `FINISHES_WITHIN` variable, in seconds, e.g. `0.25`.
+        pub fn finishes_within(&mut self, budget: Duration) -> &mut Self {
+            self.finishes_within = Some(budget);
+
+            self
+        }
+
+        /// Format the source with clang-format before compiling it, e.g.
+        /// so that the sources kept with the `ARTIFACT_DIR` variable are
+        /// readable. The diagnostics point at the lines of the formatted
+        /// source. The `.clang-format` file is found from the directory
+        /// of the source, unless [`Assert::clang_format_file`] gives
+        /// one, and the LLVM style is used otherwise. It does nothing
+        /// on the assembly programs. It can also be enabled with the
+        /// `FORMAT_SOURCE` variable.
+        pub fn format_source(&mut self, format_source: bool) -> &mut Self {
+            self.format_source = format_source;
+
+            self
+        }
+
+        /// Format the source with the style of the `.clang-format`
+        /// `file`, which enables [`Assert::format_source`]. It can also
+        /// be set with the `CLANG_FORMAT_FILE` variable.
+        pub fn clang_format_file<P>(&mut self, file: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.clang_format_file = Some(file.as_ref().to_path_buf());
+            self.format_source = true;
+
+            self
+        }
+
+        /// Embed the metadata of the snippet into its executable: the
+        /// hash of its source, its name, its test, and its compiler, with
+        /// its version and its flags, so that a stray executable can be
+        /// traced back to its test, see the [`stamp`](crate::stamp)
+        /// module. It does nothing on the assembly programs. It can also
+        /// be enabled with the `STAMP` variable.
+        pub fn stamp(&mut self, stamp: bool) -> &mut Self {
+            self.stamp = stamp;
+
+            self
+        }
+
+        /// Compile and run the program again, without and with
+        /// optimizations, with `-O0` and `-O2`, or `/Od` and `/O2` with
+        /// MSVC, and fail the assertion if the two runs differ, by their
+        /// exit code or their output, e.g. because of an undefined
+        /// behavior only showing with the optimizations. These builds
+        /// skip the build steps of the program. It can also be enabled
+        /// with the `COMPARE_OPTIMIZATIONS` variable.
+        pub fn compare_optimizations(&mut self, compare: bool) -> &mut Self {
+            self.compare_optimizations = compare;
+
+            self
+        }
+
+        /// Run the program built without and with optimizations, and
+        /// panic if the runs differ, see
+        /// [`Assert::compare_optimizations`].
+        fn check_optimizations(&mut self) {
+            let (unoptimized, optimized) = if self.compiler.msvc {
+                ("/Od", "/O2")
+            } else {
+                ("-O0", "-O2")
+            };
+            let mut run = |flag: &str| {
+                self.run_optimized(flag).unwrap_or_else(|error| {
+                    panic!("Failed to run the program built with `{}`: {}", flag, error)
+                })
+            };
+            let first = run(unoptimized);
+            let second = run(optimized);
+
+            if first.status != second.status
+                || first.stdout != second.stdout
+                || first.stderr != second.stderr
+            {
+                panic!(
+                    "The program behaves differently with `{}` and `{}`:\n\
+                     status: {} and {}\n\
+                     stdout:\n{}\n\
+                     stderr:\n{}",
+                    unoptimized,
+                    optimized,
+                    first.status,
+                    second.status,
+                    crate::diff::colored_lines(
+                        &String::from_utf8_lossy(&first.stdout),
+                        &String::from_utf8_lossy(&second.stdout)
+                    ),
+                    crate::diff::colored_lines(
+                        &String::from_utf8_lossy(&first.stderr),
+                        &String::from_utf8_lossy(&second.stderr)
+                    ),
+                );
+            }
+        }
+
+        /// Compile the program again with the optimization `flag`, next
+        /// to its executable, and run it.
+        fn run_optimized(&mut self, flag: &str) -> io::Result<Output> {
+            // The outputs of the compiler, e.g. the executable and its
+            // object with MSVC, share the path of the executable without
+            // its extension.
+            let stem = self
+                .executable
+                .with_extension("")
+                .to_string_lossy()
+                .into_owned();
+            let variant = format!("{}-{}", stem, &flag[1..]);
+            let with_variant = |arg: &OsStr| match arg.to_str() {
+                Some(arg) => OsString::from(arg.replace(&stem, &variant)),
+                None => arg.to_os_string(),
+            };
+
+            let mut compiler = Command::new(self.compiler.command.get_program());
+            compiler.arg(flag).args(
+                self.compiler
+                    .command
+                    .get_args()
+                    .filter(|arg| !arg.to_str().is_some_and(flags::is_optimization))
+                    .map(with_variant),
+            );
+
+            for (key, value) in self.compiler.command.get_envs() {
+                match value {
+                    Some(value) => compiler.env(key, value),
+                    None => compiler.env_remove(key),
+                };
+            }
+
+            if let Some(directory) = self.compiler.command.get_current_dir() {
+                compiler.current_dir(directory);
+            }
+
+            let executable = PathBuf::from(with_variant(self.executable.as_os_str()));
+            self.remove_later(&executable);
+            self.remove_later(&executable.with_extension("obj"));
+
+            let output = {
+                let _permit = self.compile_jobs.map(jobs::acquire);
+
+                compiler.output()?
+            };
+
+            if !output.status.success() {
+                return Err(io::Error::other(format!(
+                    "the compilation has failed:\n{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                )));
+            }
+
+            let executable = std::mem::replace(&mut self.executable, executable);
+            let run = self
+                .runner
+                .prepare(&self.executable)
+                .and_then(|()| self.capture(Self::command, self.stdin.as_deref()));
+            self.runner.cleanup(&self.executable);
+            self.executable = executable;
+
+            let (mut output, _, mut chunks) = run?;
+            self.decode(&mut output, &mut chunks);
+
+            Ok(output)
+        }
+
+        /// Panic if a run of `duration` has exceeded the budget of
+        /// [`Assert::finishes_within`].
+        fn check_finishes_within(&self, duration: Duration, assert: &assert_cmd::assert::Assert) {
+            if let Some(budget) = self.finishes_within {
+                if duration > budget {
+                    panic!(
+                        "The program has run for {:?}, more than the {:?} it must finish within:\n{}",
+                        duration, budget, assert
+                    );
+                }
+            }
+        }
+
+        /// Retry the compilation or the execution up to `retries`
+        /// times when it fails because of a known transient error, like
+        /// a file locked by an antivirus, or the linker failing to open
+        /// a busy file. Genuine failures are never retried. It can also
+        /// be set with the `RETRIES` variable.
+        pub fn retries(&mut self, retries: usize) -> &mut Self {
+            self.retries = retries;
+
+            self
+        }
+
+        /// Stream the standard output and the standard error of the
+        /// program to the standard error of the test, line by line and
+        /// prefixed, while they are still captured for the assertions.
+        /// It is useful for long-running programs. It can also be
+        /// enabled with the `TEE` variable.
+        pub fn tee(&mut self) -> &mut Self {
+            self.tee = true;
+
+            self
+        }
+
+        /// Record the lines of the standard output and of the standard
+        /// error in the order they are received, with their time, in
+        /// [`RunResult::chunks`], so that the order of the two streams
+        /// can be asserted, e.g. with [`Assert::printed_before`]. The
+        /// standard output of a C program is fully buffered when it is
+        /// not a terminal, so the program should flush it, or disable
+        /// its buffer with `setvbuf(stdout, NULL, _IONBF, 0)`. It can
+        /// also be enabled with the `INTERLEAVED` variable.
+        pub fn interleaved(&mut self) -> &mut Self {
+            self.interleaved = true;
+
+            self
+        }
+
+        /// Run the program in a deterministic environment, with
+        /// `LC_ALL=C`, `TZ=UTC`, `SOURCE_DATE_EPOCH=0` and `SEED=0`, so
+        /// that formatted numbers, dates, and locale-sensitive strings
+        /// do not depend on the machine. The variables given to the
+        /// program explicitly are kept. It can also be enabled with the
+        /// `DETERMINISTIC` variable.
+        pub fn deterministic(&mut self) -> &mut Self {
+            self.deterministic = true;
+
+            self
+        }
+
+        /// Run the program in the `directory` working directory,
+        /// instead of the one of the test. It can also be set with the
+        /// `CWD` variable.
+        pub fn current_dir<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.current_dir = Some(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Add an argument to pass to the program.
+        pub fn arg<S>(&mut self, argument: S) -> &mut Self
+        where
+            S: AsRef<OsStr>,
+        {
+            self.args.push(argument.as_ref().to_os_string());
+
+            self
+        }
+
+        /// Add arguments to pass to the program.
+        pub fn args<I, S>(&mut self, arguments: I) -> &mut Self
+        where
+            I: IntoIterator<Item = S>,
+            S: AsRef<OsStr>,
+        {
+            for argument in arguments {
+                self.arg(argument);
+            }
+
+            self
+        }
+
+        /// Write `stdin` to the standard input of the program.
+        pub fn stdin<B>(&mut self, stdin: B) -> &mut Self
+        where
+            B: Into<Vec<u8>>,
+        {
+            self.stdin = Some(stdin.into());
+
+            self
+        }
+
+        /// Select how the program is run, e.g. on a remote machine. It
+        /// can also be set with the `RUNNER` variable, see
+        /// [`Runner::parse`].
+        pub fn runner(&mut self, runner: Runner) -> &mut Self {
+            self.runner = runner;
+
+            self
+        }
+
+        /// Map the `host` directory to the `guest` directory of a
+        /// WebAssembly program run under WASI. The program has no
+        /// access to the host file system otherwise.
+        pub fn wasi_dir<P, S>(&mut self, host: P, guest: S) -> &mut Self
+        where
+            P: AsRef<Path>,
+            S: Into<String>,
+        {
+            self.wasi(|wasi| wasi.dirs.push((host.as_ref().to_path_buf(), guest.into())))
+        }
+
+        /// Define an environment variable for a WebAssembly program run
+        /// under WASI. The program has no access to the environment
+        /// variables of the host otherwise.
+        pub fn wasi_env<K, V>(&mut self, key: K, value: V) -> &mut Self
+        where
+            K: Into<String>,
+            V: Into<String>,
+        {
+            self.wasi(|wasi| wasi.envs.push((key.into(), value.into())))
+        }
+
+        fn wasi<F>(&mut self, configure: F) -> &mut Self
+        where
+            F: FnOnce(&mut crate::runner::Wasi),
+        {
+            match &mut self.runner {
+                Runner::Wasmer { wasi, .. } => configure(wasi),
+                _ => panic!("WASI can only be configured for WebAssembly targets"),
+            }
+
+            self
+        }
+
+        /// The command running the program.
+        fn command(&self) -> Command {
+            self.command_with(&[], &HashMap::new())
+        }
+
+        /// The command running the program, with `extra_args` after
+        /// its arguments, and `extra_envs` over its environment.
+        fn command_with(
+            &self,
+            extra_args: &[OsString],
+            extra_envs: &HashMap<String, String>,
+        ) -> Command {
+            let mut args = self.args.clone();
+            args.extend(extra_args.iter().cloned());
+
+            if let Some(fuzz) = &self.fuzz {
+                args.extend(fuzz.args(&self.executable));
+            }
+
+            let mut envs = self.envs.clone();
+            envs.extend(extra_envs.clone());
+
+            if self.deterministic {
+                for (key, value) in DETERMINISTIC_ENVS {
+                    envs.entry(key.to_string())
+                        .or_insert_with(|| value.to_string());
+                }
+            }
+
+            if self.leak_sanitizer && !self.compiler.msvc {
+                let options = envs
+                    .get("LSAN_OPTIONS")
+                    .cloned()
+                    .or_else(|| std::env::var("LSAN_OPTIONS").ok());
+
+                envs.insert(
+                    "LSAN_OPTIONS".to_string(),
+                    leak::lsan_options(options.as_deref(), self.lsan_suppressions.as_deref()),
+                );
+            }
+
+            if self.thread_sanitizer && !self.compiler.msvc {
+                let options = envs
+                    .get("TSAN_OPTIONS")
+                    .cloned()
+                    .or_else(|| std::env::var("TSAN_OPTIONS").ok());
+
+                envs.insert(
+                    "TSAN_OPTIONS".to_string(),
+                    race::tsan_options(options.as_deref(), self.tsan_suppressions.as_deref()),
+                );
+            }
+
+            // The shared libraries are found by the rpaths of the
+            // executable, or else by the variable of the target.
+            let runtime_directories = if self.embeds_rpath() {
+                Vec::new()
+            } else {
+                self.runtime_directories()
+            };
+
+            if !runtime_directories.is_empty() {
+                let variable = workspace::library_path_variable(&self.compiler.target);
+                let inherited = envs
+                    .get(variable)
+                    .map(OsString::from)
+                    .or_else(|| std::env::var_os(variable))
+                    .unwrap_or_default();
+                let library_path = std::env::join_paths(
+                    runtime_directories
+                        .into_iter()
+                        .chain(std::env::split_paths(&inherited)),
+                )
+                .unwrap_or(inherited);
+
+                envs.insert(
+                    variable.to_string(),
+                    library_path.to_string_lossy().into_owned(),
+                );
+            }
+
+            let mut command = self.runner.command(&self.executable, &args, &envs);
+            command.envs(&envs);
+
+            if let Some(directory) = &self.current_dir {
+                command.current_dir(directory);
+            }
+
+            if self.thread_sanitizer
+                && !self.compiler.msvc
+                && matches!(self.runner, Runner::Native)
+                && cfg!(target_os = "linux")
+            {
+                network::wrap(&mut command, "setarch", [std::env::consts::ARCH, "-R"]);
+            }
+
+            let deny_network = self.network == Network::Denied;
+
+            if self.sandbox {
+                let directory = self
+                    .current_dir
+                    .clone()
+                    .or_else(|| std::env::current_dir().ok())
+                    .unwrap_or_default();
+                let readable = self
+                    .executable
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .into_iter()
+                    .chain(self.runtime_directories())
+                    .collect::<Vec<_>>();
+
+                sandbox::confine(
+                    &mut command,
+                    &self.runner,
+                    &directory,
+                    &readable,
+                    deny_network,
+                );
+            } else if deny_network {
+                network::deny(&mut command, &self.runner);
+            }
+
+            scheduling::apply(&mut command, self.priority, &self.cpu_affinity);
+
+            command
+        }
+
+        /// Replace the `{{name}}` placeholders of the program by `value`
+        /// before it is compiled, e.g. to instantiate the same snippet
+        /// with different type names. The placeholders can also be
+        /// given to the macros, with
+        /// `assert_c!(template(name = "value"), { … })`.
+        pub fn template<K, V>(&mut self, name: K, value: V) -> &mut Self
+        where
+            K: Into<String>,
+            V: Into<String>,
+        {
+            self.template.insert(name.into(), value.into());
+
+            self
+        }
+
+        /// Register a hook called with the source of the program before
+        /// it is compiled, which it can modify.
+        pub fn on_source<F>(&mut self, hook: F) -> &mut Self
+        where
+            F: FnMut(&mut String) + Send + 'static,
+        {
+            self.source_hooks.push(Box::new(hook));
+
+            self
+        }
+
+        /// Register a hook configuring the `cc::Build` finding the
+        /// compiler, to set the options of `cc` which have no dedicated
+        /// method, e.g. `flag_if_supported`, `force_frame_pointer` or
+        /// `file` to compile and link an assembly file with the program.
+        ///
+        /// The flags, the environment variables and the files of the
+        /// configured build are added to the command. The compiler it
+        /// sets, e.g. with `compiler`, replaces the one of the command,
+        /// but the flags of the crate are still spelled for the family
+        /// of the compiler found without the hooks.
+        pub fn configure_build<F>(&mut self, hook: F) -> &mut Self
+        where
+            F: FnMut(&mut cc::Build) + Send + 'static,
+        {
+            self.build_hooks.push(Box::new(hook));
+
+            self
+        }
+
+        /// Register a hook called with the path of the executable once
+        /// it has been successfully compiled, e.g. to sign it or to
+        /// archive it.
+        pub fn on_compiled<F>(&mut self, hook: F) -> &mut Self
+        where
+            F: FnMut(&Path) + Send + 'static,
+        {
+            self.compiled_hooks.push(Box::new(hook));
+
+            self
+        }
+
+        /// Run `command` once the program has been linked, before it
+        /// runs, e.g. to strip it, to extract a section with `objcopy`,
+        /// or to sign it with `signtool`. `{executable}` is replaced by
+        /// the path of the executable in its arguments and in its
+        /// `outputs`, which are removed with the other generated files.
+        /// Its failure is reported as a compilation failure.
+        pub fn post_link<I, S>(&mut self, command: Command, outputs: I) -> &mut Self
+        where
+            I: IntoIterator<Item = S>,
+            S: Into<String>,
+        {
+            self.post_link_steps
+                .push((command, outputs.into_iter().map(Into::into).collect()));
+
+            self
+        }
+
+        /// Register a hook called with the result of each run of the
+        /// program.
+        pub fn on_finished<F>(&mut self, hook: F) -> &mut Self
+        where
+            F: FnMut(&RunResult) + Send + 'static,
+        {
+            self.finished_hooks.push(Box::new(hook));
+
+            self
+        }
+
+        /// Compile the program if needed, and return the path of the
+        /// executable. It panics with the compiler output if the
+        /// compilation has failed.
+        pub fn executable(&mut self) -> &Path {
+            self.ensure_compiled().unwrap_or_else(|error| {
+                panic!("Failed to run `{:?}`: {}", self.compiler.command, error)
+            });
+
+            if !self.compiled() {
+                let output = self.compilation.as_ref().unwrap();
+
+                panic!(
+                    "The compilation has failed:\n{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+
+            &self.executable
+        }
+
+        /// Compile the program, and return a handle to run it many
+        /// times, with different arguments, standard inputs or
+        /// environments, without compiling it again, e.g.
+        /// `compiled.run().arg("--verbose").success()`. The runs have
+        /// the arguments, the environment and the options of the
+        /// assertion. It panics if the program could not be compiled.
+        pub fn compile(&mut self) -> Compiled<'_> {
+            self.executable();
+
+            Compiled { assert: self }
+        }
+
+        /// Compile the program if needed, and return the path of the
+        /// linker map, e.g. to check which objects and sections have
+        /// been linked. The map must have been enabled with
+        /// [`Assert::linker_map`].
+        pub fn map_file(&mut self) -> PathBuf {
+            assert!(
+                self.linker_map,
+                "The linker map has not been enabled, see `Assert::linker_map`"
+            );
+
+            self.executable().with_extension("map")
+        }
+
+        /// Compile the program if needed, and return the diagnostics of
+        /// the compiler, even if the compilation has failed. There are
+        /// none when the executable comes from the cache or from a
+        /// batch.
+        pub fn compile_diagnostics(&mut self) -> Vec<Diagnostic> {
+            self.ensure_compiled().unwrap_or_else(|error| {
+                panic!("Failed to run `{:?}`: {}", self.compiler.command, error)
+            });
+
+            compiler_diagnostics(self.compilation.as_ref().unwrap())
+        }
+
+        /// Give `arg` to the compiler as it is, after the other compile
+        /// flags and before the input file, or before `/link` on MSVC.
+        /// Unlike the flags, it is neither translated for MSVC nor
+        /// deduplicated. It is an escape hatch for the flags that must
+        /// be at an exact position.
+        pub fn raw_compiler_arg<S>(&mut self, arg: S) -> &mut Self
+        where
+            S: AsRef<OsStr>,
+        {
+            self.raw_compiler_args.push(arg.as_ref().to_os_string());
+
+            self
+        }
+
+        /// Give `arg` to the linker as it is, at the end of the command
+        /// line, after `/link` on MSVC. It is dropped when the program
+        /// is only compiled, see [`Assert::freestanding`].
+        pub fn raw_linker_arg<S>(&mut self, arg: S) -> &mut Self
+        where
+            S: AsRef<OsStr>,
+        {
+            self.raw_linker_args.push(arg.as_ref().to_os_string());
+
+            self
+        }
+
+        /// Include the headers of `directory` as system headers, with
+        /// `-isystem`, or `/external:I` on MSVC, so that their warnings
+        /// do not fail the compilation, e.g. for third-party or SDK
+        /// headers. The `-I` flags already given for `directory` are
+        /// replaced. It can also be set with the `SYSTEM_INCLUDES`
+        /// variable, a list of directories separated like in `PATH`.
+        pub fn system_include<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.system_includes.push(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Link against the `staticlib`, or else the `cdylib`, of the
+        /// crate `name` of the workspace, built by `cargo build` with
+        /// the profile of the tests, and include its headers from its
+        /// `include` directory, or from its root. It panics if the
+        /// crate cannot be built. It can also be set with the
+        /// `WORKSPACE_CRATES` variable, a comma-separated list.
+        pub fn link_workspace_crate(&mut self, name: &str) -> &mut Self {
+            let library = workspace::Library::resolve(name).unwrap_or_else(|error| {
+                panic!("Failed to link the workspace crate `{}`: {}", name, error)
+            });
+            self.workspace_crates.push(library);
+
+            self
+        }
+
+        /// Link against the library `name`, with `-lname`, or
+        /// `name.lib` on MSVC. The libraries are linked after the
+        /// program, in the order of the calls, which matters to the GNU
+        /// linker: a library must come before the libraries it depends
+        /// on, see [`Assert::link_group`]. It can also be set with the
+        /// `LINK_LIBRARIES` variable, a comma-separated list, where
+        /// `static=name` and `dylib=name` are like
+        /// [`Assert::link_static`] and [`Assert::link_dylib`].
+        pub fn link_library<S>(&mut self, name: S) -> &mut Self
+        where
+            S: Into<String>,
+        {
+            self.libraries.push((name.into(), LinkKind::Default));
+
+            self
+        }
+
+        /// Link against the static archive of the library `name`, even
+        /// if its shared library is next to it: with `-Wl,-Bstatic`
+        /// with the GNU linker, the path of `libname.a` found in the
+        /// search paths with the Apple linker, and `name.lib` on MSVC.
+        pub fn link_static<S>(&mut self, name: S) -> &mut Self
+        where
+            S: Into<String>,
+        {
+            self.libraries.push((name.into(), LinkKind::Static));
+
+            self
+        }
+
+        /// Link against the shared library `name`: with `-lname`, or
+        /// its import library on MSVC, `name.dll.lib` if it is found in
+        /// the search paths, or else `name.lib`.
+        pub fn link_dylib<S>(&mut self, name: S) -> &mut Self
+        where
+            S: Into<String>,
+        {
+            self.libraries.push((name.into(), LinkKind::Dynamic));
+
+            self
+        }
+
+        /// Search the libraries in `directory`, with `-L`, or
+        /// `/LIBPATH:` on MSVC. It can also be set with the
+        /// `LINK_SEARCH_PATHS` variable, a list of directories
+        /// separated like in `PATH`.
+        pub fn link_search_path<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.library_directories
+                .push(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Embed the directories of the shared libraries, given by
+        /// [`Assert::link_search_path`] or built from the workspace, in
+        /// the executable as rpaths relative to it, `$ORIGIN/…` on Linux
+        /// and `@loader_path/…` on macOS, e.g. to test the layout of an
+        /// installation. Otherwise, and always on Windows, they are
+        /// added to `LD_LIBRARY_PATH`, `DYLD_LIBRARY_PATH`, or `PATH`
+        /// when the program runs. It can also be enabled with the
+        /// `RPATH` variable.
+        pub fn rpath(&mut self, rpath: bool) -> &mut Self {
+            self.rpath = rpath;
+
+            self
+        }
+
+        /// Copy next to the executable, on Windows, the DLLs it imports,
+        /// directly or through other DLLs, that are found in the
+        /// directories of [`Assert::dll_search_path`], of
+        /// [`Assert::link_search_path`], or of the workspace, so that
+        /// the program runs like once installed, without changing
+        /// `PATH`. The DLLs of the system are found in none of them,
+        /// and are left out. The copies are removed with the other
+        /// artifacts. It can also be enabled with the `STAGE_DLLS`
+        /// variable.
+        pub fn stage_dlls(&mut self, stage_dlls: bool) -> &mut Self {
+            self.stage_dlls = stage_dlls;
+
+            self
+        }
+
+        /// Search the DLLs staged by [`Assert::stage_dlls`] in
+        /// `directory` too, e.g. the `bin` directory of a library whose
+        /// import library is in `lib`. It can also be set with the
+        /// `DLL_SEARCH_PATHS` variable, a list of directories separated
+        /// like in `PATH`.
+        pub fn dll_search_path<P>(&mut self, directory: P) -> &mut Self
+        where
+            P: AsRef<Path>,
+        {
+            self.dll_directories.push(directory.as_ref().to_path_buf());
+
+            self
+        }
+
+        /// Whether the directories of the shared libraries are embedded
+        /// in the executable, see [`Assert::rpath`].
+        fn embeds_rpath(&self) -> bool {
+            let target = &self.compiler.target;
+
+            self.rpath && !target.contains("windows") && !target.starts_with("wasm")
+        }
+
+        /// The directories where the program finds its shared
+        /// libraries when it runs.
+        fn runtime_directories(&self) -> Vec<PathBuf> {
+            self.library_directories
+                .iter()
+                .cloned()
+                .chain(
+                    self.workspace_crates
+                        .iter()
+                        .filter_map(workspace::Library::runtime_directory)
+                        .map(Path::to_path_buf),
+                )
+                .collect()
+        }
+
+        /// Wrap the libraries of [`Assert::link_library`] in
+        /// `--start-group` and `--end-group`, so that the GNU linker
+        /// resolves their circular dependencies, whatever their order.
+        /// The Apple linker and MSVC always do. It can also be enabled
+        /// with the `LINK_GROUP` variable.
+        pub fn link_group(&mut self, link_group: bool) -> &mut Self {
+            self.link_group = link_group;
+
+            self
+        }
+
+        /// Fail the assertion when the compiler reports one of the
+        /// `warnings`, given by their code, e.g. `C4013` with MSVC, or
+        /// by their name, e.g. `implicit-function-declaration` with GCC
+        /// and Clang, without turning the other warnings into errors.
+        /// It can also be set with the `DENY_WARNINGS` variable, a
+        /// comma-separated list.
+        pub fn deny_warnings<S>(&mut self, warnings: &[S]) -> &mut Self
+        where
+            S: AsRef<str>,
+        {
+            self.denied_warnings.extend(
+                warnings
+                    .iter()
+                    .map(|warning| warning.as_ref().trim().to_string())
+                    .filter(|warning| !warning.is_empty()),
+            );
+
+            self
+        }
+
+        /// Assert that the diagnostics of the compiler satisfy
+        /// `predicate`, e.g. [`predicates::no_warnings`] or
+        /// [`predicates::diagnostic_code`].
+        pub fn diagnostics<P>(&mut self, predicate: P) -> &mut Self
+        where
+            P: predicates::Predicate<[Diagnostic]>,
+        {
+            let diagnostics = self.compile_diagnostics();
+
+            if !predicate.eval(&diagnostics) {
+                panic!(
+                    "The compiler diagnostics do not satisfy `{}`:\n{}",
+                    predicate,
+                    diagnostics
+                        .iter()
+                        .map(|diagnostic| format!("  {}", diagnostic))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                );
+            }
+
+            self
+        }
+
+        /// Assert that the executable is not larger than `bytes`, e.g.
+        /// to catch the code that a minimal usage of a library drags
+        /// in.
+        pub fn max_binary_size(&mut self, bytes: u64) -> &mut Self {
+            let path = self.executable().to_path_buf();
+            let size = fs::metadata(&path)
+                .unwrap_or_else(|error| panic!("Failed to read `{}`: {}", path.display(), error))
+                .len();
+
+            assert!(
+                size <= bytes,
+                "The executable is {} bytes large, more than the {} bytes allowed",
+                size,
+                bytes
+            );
+
+            self
+        }
+
+        /// Assert that the executable is not larger than `bytes` once
+        /// its symbols have been stripped, with `strip`, or the program
+        /// given by the `STRIP` variable. MSVC executables are measured
+        /// as they are, since their symbols live in a separate PDB.
+        pub fn max_stripped_binary_size(&mut self, bytes: u64) -> &mut Self {
+            if self.compiler.msvc {
+                return self.max_binary_size(bytes);
+            }
+
+            let path = self.executable().to_path_buf();
+            let mut stripped = path.clone().into_os_string();
+            stripped.push(".stripped");
+            let stripped = PathBuf::from(stripped);
+            self.remove_later(&stripped);
+
+            let output = Command::new(self.envs.get("STRIP").map_or("strip", String::as_str))
+                .arg("-o")
+                .arg(&stripped)
+                .arg(&path)
+                .output()
+                .unwrap_or_else(|error| panic!("Failed to strip `{}`: {}", path.display(), error));
+
+            assert!(
+                output.status.success(),
+                "Failed to strip `{}`: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            let size = fs::metadata(&stripped)
+                .unwrap_or_else(|error| {
+                    panic!("Failed to read `{}`: {}", stripped.display(), error)
+                })
+                .len();
+
+            assert!(
+                size <= bytes,
+                "The stripped executable is {} bytes large, more than the {} bytes allowed",
+                size,
+                bytes
+            );
+
+            self
+        }
+
+        /// Compile the program to WebAssembly, e.g. with the
+        /// `wasm32-wasi` `TARGET` variable, and inspect the produced
+        /// module without running it.
+        #[cfg(feature = "wasm")]
+        pub fn wasm_module(&mut self) -> wasm::WasmModule {
+            let path = self.executable().to_path_buf();
+
+            fs::read(&path)
+                .map_err(Into::into)
+                .and_then(|bytes| wasm::WasmModule::parse(&bytes))
+                .unwrap_or_else(|error| panic!("Failed to inspect `{}`: {}", path.display(), error))
+        }
+
+        /// Assert that the WebAssembly module exports all the
+        /// `expected` names.
+        #[cfg(feature = "wasm")]
+        pub fn wasm_exports(&mut self, expected: &[&str]) -> &mut Self {
+            let module = self.wasm_module();
+            let missing = expected
+                .iter()
+                .filter(|name| !module.exports.iter().any(|export| export == *name))
+                .collect::<Vec<_>>();
+
+            assert!(
+                missing.is_empty(),
+                "The WebAssembly module does not export {:?}; its exports are {:?}",
+                missing,
+                module.exports
+            );
+
+            self
+        }
+
+        /// Assert that the WebAssembly module imports exactly the
+        /// `expected` `(module, name)` pairs, in any order.
+        #[cfg(feature = "wasm")]
+        pub fn wasm_imports(&mut self, expected: &[(&str, &str)]) -> &mut Self {
+            let module = self.wasm_module();
+            let mut imports = module
+                .imports
+                .iter()
+                .map(|(module, name)| (module.as_str(), name.as_str()))
+                .collect::<Vec<_>>();
+            let mut expected = expected.to_vec();
+            imports.sort_unstable();
+            expected.sort_unstable();
+
+            assert_eq!(
+                imports, expected,
+                "The WebAssembly module imports are not the expected ones"
+            );
+
+            self
+        }
+
+        /// Assert that the WebAssembly module does not require any
+        /// feature beyond `allowed`, e.g. `&["bulk-memory", "simd"]`.
+        #[cfg(feature = "wasm")]
+        pub fn wasm_features(&mut self, allowed: &[&str]) -> &mut Self {
+            let module = self.wasm_module();
+            let unexpected = module
+                .required_features
+                .iter()
+                .filter(|feature| !allowed.contains(feature))
+                .collect::<Vec<_>>();
+
+            assert!(
+                unexpected.is_empty(),
+                "The WebAssembly module requires the unexpected features {:?}",
+                unexpected
+            );
+
+            self
+        }
+
+        /// Skip the assertion when one of its `tags`, e.g. `&["slow"]`,
+        /// is in the `INLINE_C_RS_SKIP_TAGS` variable, a comma-separated
+        /// list of tags, like [`Assert::skip_if`]. The macros skip the
+        /// assertion of a snippet with the `tags = ["slow", …]` option
+        /// in the same way.
+        pub fn tags(&mut self, tags: &[&str]) -> Option<&mut Self> {
+            if let Some(tag) = run::skipped_tag(tags) {
+                eprintln!(
+                    "Skipped: the tag `{}` is skipped by `INLINE_C_RS_SKIP_TAGS`",
+                    tag
+                );
+
+                return None;
+            }
+
+            Some(self)
+        }
+
+        /// Skip the assertion when `condition` holds, e.g. when the
+        /// machine has no GPU: the program is neither compiled nor run,
+        /// and `None` is returned, so that the assertions are written
+        /// `assert.skip_if(|| …).map(|assert| assert.success())`. The
+        /// macros skip the assertion of a snippet with the `SKIP_IF_ENV`
+        /// variable in the same way.
+        pub fn skip_if<F>(&mut self, condition: F) -> Option<&mut Self>
+        where
+            F: FnOnce() -> bool,
+        {
+            if condition() {
+                eprintln!("Skipped: the condition of `skip_if` holds");
+
+                return None;
+            }
+
+            Some(self)
+        }
+
+        pub fn assert(&mut self) -> assert_cmd::assert::Assert {
+            let (output, duration, _) = self
+                .execute()
+                .unwrap_or_else(|error| panic!("Failed to run `{:?}`: {}", self.command(), error));
+
+            let command = if self.compiled() && !self.freestanding {
+                format!("{:?}", self.command())
+            } else {
+                format!("{:?}", self.compiler.command)
+            };
+
+            let mut assert =
+                assert_cmd::assert::Assert::new(output).append_context("command", command);
+
+            if let Some(name) = self.envs.get("NAME") {
+                assert = assert.append_context("snippet", name.clone());
+            }
+
+            if self.crt_leak_check && self.compiler.msvc && self.compiled() && !self.freestanding {
+                let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+                if let Some(report) = leak::crt_report(&stderr) {
+                    panic!(
+                        "The CRT debug heap has detected memory leaks:\n{}\n{}",
+                        report, assert
+                    );
+                }
+            }
+
+            if self.leak_sanitizer && !self.compiler.msvc && self.compiled() && !self.freestanding {
+                let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+                if let Some(report) = leak::lsan_report(&stderr) {
+                    panic!(
+                        "LeakSanitizer has detected memory leaks:\n{}\n{}",
+                        report, assert
+                    );
+                }
+            }
+
+            if self.thread_sanitizer && !self.compiler.msvc && self.compiled() && !self.freestanding
+            {
+                let stderr = String::from_utf8_lossy(&assert.get_output().stderr);
+
+                if let Some(report) = race::tsan_report(&stderr) {
+                    panic!(
+                        "ThreadSanitizer has detected data races:\n{}\n{}",
+                        report, assert
+                    );
+                }
+            }
+
+            if self.compiled() && !self.freestanding {
+                self.check_finishes_within(duration, &assert);
+
+                if self.compare_optimizations {
+                    self.check_optimizations();
+                }
+            }
+
+            if self.fuzz.is_some() {
+                for reproducer in fuzz::reproducers(&self.executable) {
+                    assert = assert.append_context("reproducer", reproducer.display().to_string());
+                }
+            }
+
+            assert
+        }
+
+        /// Run the program, and parse the results of its test cases
+        /// from its standard output, according to its
+        /// [`Assert::framework`]. It panics if the program could not
+        /// be compiled.
+        pub fn test_cases(&mut self) -> Vec<TestCase> {
+            let framework = self
+                .framework
+                .clone()
+                .expect("No test framework has been set, see `Assert::framework`");
+
+            self.executable();
+
+            let result = self
+                .output()
+                .unwrap_or_else(|error| panic!("Failed to run `{:?}`: {}", self.command(), error));
+
+            framework.parse(&String::from_utf8_lossy(&result.stdout))
+        }
+
+        /// Run the program, and parse its standard output as TAP. It
+        /// panics with a summary if a test has failed, and returns the
+        /// test cases otherwise.
+        pub fn tap(&mut self) -> Vec<TestCase> {
+            self.executable();
+
+            let result = self
+                .output()
+                .unwrap_or_else(|error| panic!("Failed to run `{:?}`: {}", self.command(), error));
+            let test_cases = framework::parse_tap(&String::from_utf8_lossy(&result.stdout));
+            let failed = test_cases
+                .iter()
+                .filter(|test_case| test_case.outcome == Outcome::Failed)
+                .collect::<Vec<_>>();
+
+            assert!(
+                failed.is_empty(),
+                "{} of {} TAP tests have failed:\n{}",
+                failed.len(),
+                test_cases.len(),
+                failed
+                    .iter()
+                    .map(|test_case| format!("  {}", test_case))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            );
+
+            test_cases
+        }
+
+        /// Run the program once per case generated by `strategy`, and
+        /// check each result with `property`. A failing case is shrunk
+        /// to a minimal one, which is reported in the panic message.
+        /// The number of cases is configured like any proptest test,
+        /// e.g. with the `PROPTEST_CASES` environment variable.
+        #[cfg(feature = "proptest")]
+        pub fn property<S, F>(&mut self, strategy: S, property: F) -> &mut Self
+        where
+            S: proptest::strategy::Strategy<Value = property::Case>,
+            F: Fn(&property::Case, &RunResult) -> Result<(), String>,
+        {
+            use proptest::test_runner::{TestCaseError, TestRunner};
+
+            self.executable();
+
+            let result = TestRunner::default().run(&strategy, |case| {
+                let mut command = self.command_with(&case.args, &HashMap::new());
+                let start = Instant::now();
+                let (output, chunks) = output::capture(
+                    &mut command,
+                    case.stdin.as_deref().or(self.stdin.as_deref()),
+                    false,
+                    self.interleaved,
+                )
+                .map_err(|error| TestCaseError::fail(error.to_string()))?;
+
+                let result = RunResult {
+                    exit_code: output.status.code(),
+                    stdout: output.stdout,
+                    stderr: output.stderr,
+                    duration: start.elapsed(),
+                    chunks,
+                };
+
+                property(&case, &result).map_err(TestCaseError::fail)
+            });
+
+            if let Err(error) = result {
+                panic!("The property does not hold: {}", error);
+            }
+
+            self
+        }
+
+        /// Compile and run the program once per combination of the
+        /// `defines`, from none to all of them, e.g. `&["NDEBUG",
+        /// "WASM_API_EXTERN="]`, and check each configuration with
+        /// `check`. It panics once all the configurations have been
+        /// checked, labeling each failure with its defines. Each
+        /// configuration keeps the options of the assertion. It panics
+        /// right away with more than [`MAX_MATRIX_DEFINES`] defines,
+        /// whose configurations would take too long to build.
+        pub fn defines_matrix<F>(&mut self, defines: &[&str], mut check: F) -> &mut Self
+        where
+            F: FnMut(&mut Assert),
+        {
+            assert!(
+                defines.len() <= MAX_MATRIX_DEFINES,
+                "The matrix of {} defines has too many configurations, at most {} defines are supported",
+                defines.len(),
+                MAX_MATRIX_DEFINES
+            );
+
+            let configurations = 1usize << defines.len();
+            let mut failures = Vec::new();
+
+            // The configurations are compiled by this assertion, with all
+            // its options, from copies of its commands, which compiling
+            // changes. Each one has its own executable.
+            let executable = self.executable.clone();
+            let compiler = copy_command(&self.compiler.command);
+            let slices = self
+                .slices
+                .iter()
+                .map(|slice| copy_command(&slice.command))
+                .collect::<Vec<_>>();
+            let pre_build_steps = self
+                .pre_build_steps
+                .iter()
+                .map(copy_command)
+                .collect::<Vec<_>>();
+            let build_steps = self
+                .build_steps
+                .iter()
+                .map(copy_command)
+                .collect::<Vec<_>>();
+            let post_link_steps = self
+                .post_link_steps
+                .iter()
+                .map(|(step, _)| copy_command(step))
+                .collect::<Vec<_>>();
+            let compilation = self.compilation.take();
+            let (compile_time, cache_hit) = (self.compile_time, self.cache_hit);
+            let stem = executable.with_extension("").to_string_lossy().into_owned();
+
+            for combination in 0..configurations {
+                let enabled = defines
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| combination & (1 << index) != 0)
+                    .map(|(_, define)| *define)
+                    .collect::<Vec<_>>();
+
+                // The defines are given first, before the arguments of
+                // the linker of MSVC.
+                let variant = format!("{}-defines{}", stem, combination);
+                let configure = |command: &Command, flags: &[OsString]| {
+                    let mut configured = copy_command(command);
+                    let mut flags = Some(flags.to_vec());
+
+                    rewrite_args(&mut configured, |arg| {
+                        let mut args = flags.take().unwrap_or_default();
+                        args.push(match arg.to_str() {
+                            Some(arg) => arg.replace(&stem, &variant).into(),
+                            None => arg.to_os_string(),
+                        });
+
+                        args
+                    });
+
+                    configured
+                };
+                let flags = enabled
+                    .iter()
+                    .map(|define| OsString::from(format!("-D{}", define)))
+                    .collect::<Vec<_>>();
+
+                self.compiler.command = configure(&compiler, &flags);
+
+                for (slice, command) in self.slices.iter_mut().zip(&slices) {
+                    slice.command = configure(command, &flags);
+                }
+
+                self.pre_build_steps = pre_build_steps
+                    .iter()
+                    .map(|step| configure(step, &[]))
+                    .collect();
+                self.build_steps = build_steps
+                    .iter()
+                    .map(|step| configure(step, &[]))
+                    .collect();
+
+                for ((step, _), command) in self.post_link_steps.iter_mut().zip(&post_link_steps) {
+                    *step = configure(command, &[]);
+                }
+
+                self.executable =
+                    PathBuf::from(executable.to_string_lossy().replace(&stem, &variant));
+                self.remove_later(&self.executable.clone());
+                self.remove_later(&self.executable.with_extension("obj"));
+                self.compilation = None;
+                self.compile_time = None;
+                self.cache_hit = false;
+
+                let result = panic::catch_unwind(panic::AssertUnwindSafe(|| check(self)));
+
+                if self.compiled() && !self.freestanding {
+                    self.runner.cleanup(&self.executable);
+                }
+
+                if let Err(error) = result {
+                    let message = error
+                        .downcast_ref::<String>()
+                        .map(String::as_str)
+                        .or_else(|| error.downcast_ref::<&str>().copied())
+                        .unwrap_or("<no message>")
+                        .to_string();
+
+                    failures.push(format!("defines {:?}: {}", enabled, message));
+                }
+            }
+
+            self.executable = executable;
+            self.compiler.command = compiler;
+
+            for (slice, command) in self.slices.iter_mut().zip(slices) {
+                slice.command = command;
+            }
+
+            self.pre_build_steps = pre_build_steps;
+            self.build_steps = build_steps;
+
+            for ((step, _), command) in self.post_link_steps.iter_mut().zip(post_link_steps) {
+                *step = command;
+            }
+
+            self.compilation = compilation;
+            self.compile_time = compile_time;
+            self.cache_hit = cache_hit;
+
+            assert!(
+                failures.is_empty(),
+                "{} of {} configurations have failed:\n\n{}",
+                failures.len(),
+                configurations,
+                failures.join("\n\n")
+            );
+
+            self
+        }
+
+        /// Run the program `runs` times, up to `parallelism` of them at
+        /// once, until one of them fails, e.g. to catch a race condition
+        /// occurring once in a thousand runs. It panics with the output
+        /// of the first failing run, and the remaining runs are not
+        /// started.
+        pub fn stress(&mut self, runs: usize, parallelism: usize) -> &mut Self {
+            self.executable();
+
+            let next = AtomicUsize::new(0);
+            let failure = Mutex::new(None::<(usize, String)>);
+            let stdin = self.stdin.as_deref();
+            let commands = (0..parallelism.clamp(1, runs.max(1)))
+                .map(|_| self.command())
+                .collect::<Vec<_>>();
+
+            thread::scope(|scope| {
+                for mut command in commands {
+                    let (next, failure) = (&next, &failure);
+
+                    scope.spawn(move || loop {
+                        let run = next.fetch_add(1, Ordering::SeqCst);
+
+                        if run >= runs || failure.lock().unwrap().is_some() {
+                            break;
+                        }
+
+                        let report = match output::capture(&mut command, stdin, false, false) {
+                            Ok((output, _)) if output.status.success() => continue,
+                            Ok((output, _)) => format!(
+                                "{}\n\n=== stdout\n{}\n\n=== stderr\n{}",
+                                output.status,
+                                String::from_utf8_lossy(&output.stdout).trim_end(),
+                                String::from_utf8_lossy(&output.stderr).trim_end()
+                            ),
+                            Err(error) => format!("failed to run `{:?}`: {}", command, error),
+                        };
+
+                        // The runs started concurrently may fail after a
+                        // later one.
+                        let mut failure = failure.lock().unwrap();
+
+                        if failure.as_ref().is_none_or(|(first, _)| run < *first) {
+                            *failure = Some((run, report));
+                        }
+
+                        break;
+                    });
+                }
+            });
+
+            if let Some((run, report)) = failure.into_inner().unwrap() {
+                panic!("The run {} of {} has failed with {}", run + 1, runs, report);
+            }
+
+            self
+        }
+
+        /// Run the program and return its raw result. The generated
+        /// files are still removed when `Assert` is dropped.
+        pub fn output(&mut self) -> io::Result<RunResult> {
+            let (output, duration, chunks) = self.execute()?;
+
+            Ok(RunResult {
+                exit_code: output.status.code(),
+                stdout: output.stdout,
+                stderr: output.stderr,
+                duration,
+                chunks,
+            })
+        }
+
+        fn compiled(&self) -> bool {
+            matches!(&self.compilation, Some(output) if output.status.success())
+        }
+
+        /// Add to the compiler commands what the build hooks have
+        /// configured: the `cc::Build` of each target is found again,
+        /// and compared with the one configured by the hooks.
+        fn configure_builds(&mut self) -> io::Result<()> {
+            let language = self.language.unwrap_or(Language::C);
+            let host = target_lexicon::HOST.to_string();
+
+            // `cc` compiles its probes, e.g. of `flag_if_supported`, in
+            // its output directory, the `OUT_DIR` of the build scripts
+            // by default, which the tests do not have.
+            let probes = tempfile::Builder::new()
+                .prefix("inline-c-rs-probes-")
+                .tempdir_in(self.executable.parent().unwrap_or_else(|| Path::new(".")))?;
+
+            for compiler in std::iter::once(&mut self.compiler).chain(self.slices.iter_mut()) {
+                let (mut build, _) =
+                    run::configured_build(language, &self.envs, &host, &compiler.target)
+                        .map_err(|error| io::Error::other(error.to_string()))?;
+                build.out_dir(probes.path());
+
+                let base = build
+                    .try_get_compiler()
+                    .map_err(|error| io::Error::other(error.to_string()))?;
+
+                for hook in self.build_hooks.iter_mut() {
+                    hook(&mut build);
+                }
+
+                let configured = build
+                    .try_get_compiler()
+                    .map_err(|error| io::Error::other(error.to_string()))?;
+
+                if configured.path() != base.path() {
+                    let mut command = Command::new(configured.path());
+                    command.args(compiler.command.get_args());
+
+                    for (key, value) in compiler.command.get_envs() {
+                        match value {
+                            Some(value) => command.env(key, value),
+                            None => command.env_remove(key),
+                        };
+                    }
+
+                    if let Some(directory) = compiler.command.get_current_dir() {
+                        command.current_dir(directory);
+                    }
+
+                    compiler.command = command;
+                }
+
+                // The arguments of the base build are found in order
+                // among the configured ones, so that the repeated ones,
+                // e.g. a second `-I` and its directory, are kept.
+                let mut base_args = base.args().iter().peekable();
+
+                for arg in configured.args() {
+                    if base_args.peek() == Some(&arg) {
+                        base_args.next();
+                    } else {
+                        compiler.command.arg(arg);
+                    }
+                }
+
+                for (key, value) in configured.get_envs() {
+                    if !base.get_envs().any(|env| env == (key, value)) {
+                        compiler.command.env(key, value);
+                    }
+                }
+
+                // E.g. the assembly files, compiled and linked with the
+                // program.
+                compiler.command.args(build.get_files());
+            }
+
+            Ok(())
+        }
+
+        /// Compile the program if it has not been compiled yet.
+        fn ensure_compiled(&mut self) -> io::Result<()> {
+            if self.compilation.is_some() {
+                return Ok(());
+            }
+
+            if let Some(bundle) = &self.replay {
+                let replay = replay::load(bundle, &self.source)?;
+                self.compilation = Some(replay.compilation);
+                self.replayed_run = replay.run;
+
+                return Ok(());
+            }
+
+            let _permit = self.compile_jobs.map(jobs::acquire);
+            let crt_leak_check = self.crt_leak_check && self.compiler.msvc && !self.freestanding;
+            let format_source = self.format_source && self.language != Some(Language::Assembler);
+            let mut source = template::render(&self.source, &self.template);
+
+            if !self.source_hooks.is_empty()
+                || crt_leak_check
+                || format_source
+                || !self.template.is_empty()
+            {
+                for hook in self.source_hooks.iter_mut() {
+                    hook(&mut source);
+                }
+
+                // The shims are prepended after the formatting, which
+                // would move their `#line` directive.
+                if format_source {
+                    source = format::clang_format(
+                        &source,
+                        &self.source_path,
+                        self.clang_format_file.as_deref(),
+                    )?;
+                }
+
+                if crt_leak_check {
+                    source.insert_str(0, leak::CRT_SHIM);
+                }
+
+                fs::write(&self.source_path, &source)?;
+            }
+
+            if let Some(output) = self.compile_resources()? {
+                self.compilation = Some(output);
+
+                return Ok(());
+            }
+
+            for step in self.pre_build_steps.iter_mut() {
+                let output = step.output()?;
+
+                if !output.status.success() {
+                    self.compilation = Some(output);
+
+                    return Ok(());
+                }
+            }
+
+            if !self.build_hooks.is_empty() {
+                self.configure_builds()?;
+            }
+
+            let (gnu_flags, msvc_flags) = self.flags();
+            let (gnu_libraries, msvc_libraries) = self.library_args();
+
+            for compiler in std::iter::once(&mut self.compiler).chain(self.slices.iter_mut()) {
+                compiler.command.args(if compiler.msvc {
+                    &msvc_flags
+                } else {
+                    &gnu_flags
+                });
+
+                isolate_system_includes(
+                    &mut compiler.command,
+                    compiler.msvc,
+                    &self.system_includes,
+                );
+
+                if !self.freestanding {
+                    compiler.command.args(&compiler.link_args);
+
+                    if compiler.msvc {
+                        if !msvc_libraries.is_empty()
+                            && !compiler.link_args.iter().any(|arg| arg == "/link")
+                        {
+                            compiler.command.arg("/link");
+                        }
+
+                        compiler.command.args(&msvc_libraries);
+                    } else {
+                        compiler.command.args(&gnu_libraries);
+                    }
+                }
+            }
+
+            if self.linker_map && !self.freestanding {
+                let map = self.executable.with_extension("map");
+                let compiler = &mut self.compiler;
+
+                if compiler.msvc {
+                    if !compiler
+                        .link_args
+                        .iter()
+                        .any(|argument| argument == "/link")
+                    {
+                        compiler.command.arg("/link");
+                    }
+
+                    let mut argument = OsString::from("/MAP:");
+                    argument.push(&map);
+                    compiler.command.arg(argument);
+                } else {
+                    // The Apple linker spells it `-map`.
+                    let mut argument = OsString::from(if compiler.target.contains("apple") {
+                        "-Wl,-map,"
+                    } else {
+                        "-Wl,-Map,"
+                    });
+                    argument.push(&map);
+                    compiler.command.arg(argument);
+                }
+
+                self.remove_later(&map);
+            }
+
+            for compiler in std::iter::once(&mut self.compiler).chain(self.slices.iter_mut()) {
+                flags::normalize_command(&mut compiler.command, compiler.family)?;
+
+                // The raw arguments are not normalized.
+                insert_raw_args(
+                    compiler,
+                    &self.source_path,
+                    &self.raw_compiler_args,
+                    if self.freestanding {
+                        &[]
+                    } else {
+                        &self.raw_linker_args
+                    },
+                );
+            }
+
+            // The stamp records the final flags, without the paths of
+            // the source and of the executable, which change at each
+            // run.
+            if self.stamp && self.language != Some(Language::Assembler) {
+                let source_path = self.source_path.to_string_lossy().into_owned();
+                let stem = self
+                    .executable
+                    .with_extension("")
+                    .to_string_lossy()
+                    .into_owned();
+                let mut version = Command::new(self.compiler.command.get_program());
+
+                for (key, value) in self.compiler.command.get_envs() {
+                    match value {
+                        Some(value) => version.env(key, value),
+                        None => version.env_remove(key),
+                    };
+                }
+
+                let stamp = Stamp {
+                    hash: report::hash(self.source.as_bytes()),
+                    name: self.envs.get("NAME").cloned(),
+                    binary: build_dir::crate_name(),
+                    test: thread::current().name().map(ToString::to_string),
+                    compiler: self
+                        .compiler
+                        .command
+                        .get_program()
+                        .to_string_lossy()
+                        .into_owned(),
+                    version: run::compiler_version(version, self.compiler.family),
+                    flags: self
+                        .compiler
+                        .command
+                        .get_args()
+                        .map(|arg| arg.to_string_lossy().into_owned())
+                        .filter(|arg| {
+                            arg.starts_with(['-', '/'])
+                                && arg != "-o"
+                                && !arg.contains(&source_path)
+                                && !arg.contains(&stem)
+                        })
+                        .collect(),
+                };
+
+                source.push_str(&stamp.source());
+                fs::write(&self.source_path, &source)?;
+            }
+
+            // Universal binaries, compiled-only programs, and programs
+            // with resources or a linker map are not cached, nor
+            // batched. Without its dependencies, the program cannot be
+            // cached safely either.
+            let linked_alone = self.slices.is_empty()
+                && self.pre_build_steps.is_empty()
+                && self.language != Some(Language::Assembler)
+                && !self.freestanding
+                && !self.linker_map
+                && self.resources.is_empty()
+                && self.manifest.is_none();
+            let dependencies = cache::dependencies(&self.compiler.command, &source).ok();
+            let cache = match (&self.cache, &dependencies) {
+                (Some(directory), Some(dependencies)) if linked_alone => Some(cache::Key::new(
+                    directory,
+                    &self.compiler.command,
+                    &source,
+                    &self.source_path,
+                    &self.executable,
+                    dependencies,
+                )?),
+                _ => None,
+            };
+            self.dependencies = dependencies.unwrap_or_default();
+
+            // The programs compiled again with other optimizations must
+            // be compiled from their source.
+            if self.batch && linked_alone && !self.compare_optimizations {
+                if let Some(object) = batch::object(&self.compiler, &source, &self.source_path) {
+                    batch::link(&mut self.compiler.command, &self.source_path, &object);
+                }
+            }
+
+            let start = Instant::now();
+            let mut attempt = 0;
+
+            let mut output = match &cache {
+                Some(cache) if cache.restore(&self.executable)? => {
+                    self.cache_hit = true;
+
+                    Output {
+                        status: ExitStatus::default(),
+                        stdout: Vec::new(),
+                        stderr: Vec::new(),
+                    }
+                }
+                _ => loop {
+                    let output = self.compiler.command.output()?;
+
+                    if let Some(cache) = &cache {
+                        if output.status.success() {
+                            cache.store(&self.executable, &self.dependencies)?;
+                        }
+                    }
+
+                    if output.status.success()
+                        || attempt >= self.retries
+                        || !retry::is_transient_output(&output)
+                    {
+                        break output;
+                    }
+
+                    attempt += 1;
+                    retry::wait(attempt);
+                },
+            };
+
+            let steps = self
+                .slices
+                .iter_mut()
+                .map(|compiler| &mut compiler.command)
+                .chain(self.build_steps.iter_mut());
+
+            for step in steps {
+                if !output.status.success() {
+                    break;
+                }
+
+                output = step.output()?;
+            }
+
+            if !self.freestanding {
+                let executable = self.executable.to_string_lossy().into_owned();
+                let with_executable = |arg: &str| arg.replace("{executable}", &executable);
+
+                for (step, outputs) in &mut self.post_link_steps {
+                    if !output.status.success() {
+                        break;
+                    }
+
+                    rewrite_args(step, |arg| {
+                        vec![arg
+                            .to_str()
+                            .map_or_else(|| arg.to_os_string(), |arg| with_executable(arg).into())]
+                    });
+
+                    for path in outputs.iter() {
+                        if let Some(files_to_remove) = &mut self.files_to_remove {
+                            files_to_remove.push(PathBuf::from(with_executable(path)));
+                        }
+                    }
+
+                    output = step.output()?;
+                }
+
+                if output.status.success()
+                    && self.stage_dlls
+                    && self.compiler.target.contains("windows")
+                {
+                    let directories = self
+                        .dll_directories
+                        .iter()
+                        .cloned()
+                        .chain(self.runtime_directories())
+                        .collect::<Vec<_>>();
+                    let staged = pe::stage_dependencies(&self.executable, &directories)?;
+
+                    if let Some(files_to_remove) = &mut self.files_to_remove {
+                        files_to_remove.extend(staged);
+                    }
+                }
+            }
+
+            let compile_time = start.elapsed();
+            self.compile_time = Some(compile_time);
+
+            if let Some(report) = &mut self.report {
+                report.record.compiler(&self.compiler.command);
+                report
+                    .record
+                    .compiled(output.status.success(), compile_time, self.cache_hit);
+            }
+
+            if output.status.success() && !self.freestanding {
+                for hook in self.compiled_hooks.iter_mut() {
+                    hook(&self.executable);
+                }
+
+                self.runner.prepare(&self.executable)?;
+            }
+
+            self.compilation = Some(output);
+
+            if self.compiled() && !self.denied_warnings.is_empty() {
+                let denied = compiler_diagnostics(self.compilation.as_ref().unwrap())
+                    .into_iter()
+                    .filter(|diagnostic| {
+                        diagnostic.severity >= Severity::Warning
+                            && diagnostic.code.as_deref().is_some_and(|code| {
+                                self.denied_warnings
+                                    .iter()
+                                    .any(|denied| is_warning(code, denied))
+                            })
+                    })
+                    .map(|diagnostic| format!("  {}", diagnostic))
+                    .collect::<Vec<_>>();
+
+                if !denied.is_empty() {
+                    panic!(
+                        "The compiler has reported denied warnings:\n{}",
+                        denied.join("\n")
+                    );
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Compile the resources, and give them to the compiler. Return
+        /// the output of the resource compiler if it has failed.
+        fn compile_resources(&mut self) -> io::Result<Option<Output>> {
+            let mut scripts = self.resources.clone();
+
+            if let Some(manifest) = &self.manifest {
+                let script = self.executable.with_extension("manifest.rc");
+                fs::write(&script, resource::manifest_script(manifest))?;
+                self.remove_later(&script);
+                scripts.push(script);
+            }
+
+            for (index, script) in scripts.iter().enumerate() {
+                let compiled = self.executable.with_extension(format!(
+                    "{}.{}",
+                    index,
+                    if self.compiler.msvc { "res" } else { "res.o" }
+                ));
+
+                let output = resource::command(&self.compiler, script, &compiled).output()?;
+                self.remove_later(&compiled);
+
+                if !output.status.success() {
+                    return Ok(Some(output));
+                }
+
+                self.compiler.command.arg(compiled);
+            }
+
+            Ok(None)
+        }
+
+        fn remove_later(&mut self, path: &Path) {
+            if let Some(files_to_remove) = &mut self.files_to_remove {
+                files_to_remove.push(path.to_path_buf());
+            }
+        }
+
+        /// The flags enabled by the options of `Assert`, for GNU-style
+        /// compilers and for MSVC.
+        fn flags(&self) -> (Vec<OsString>, Vec<OsString>) {
+            let mut gnu = Vec::<OsString>::new();
+            let mut msvc = Vec::<OsString>::new();
+
+            if self.freestanding {
+                gnu.extend(["-ffreestanding".into(), "-nostdlib".into(), "-c".into()]);
+                msvc.extend(["/kernel".into(), "/c".into()]);
+            }
+
+            if self.openmp {
+                gnu.push("-fopenmp".into());
+                msvc.push("/openmp".into());
+            }
+
+            if self.threads {
+                gnu.push("-pthread".into());
+            }
+
+            // `-lm` has to come after the input file, and it is only a
+            // link flag.
+            if self.math && !self.freestanding {
+                gnu.push("-lm".into());
+            }
+
+            if let Some(framework) = &self.framework {
+                gnu.extend(framework.flags(false));
+                msvc.extend(framework.flags(true));
+            }
+
+            if !self.freestanding {
+                for library in &self.workspace_crates {
+                    gnu.extend(library.flags(false, &self.compiler.target));
+                    msvc.extend(library.flags(true, &self.compiler.target));
+                }
+            }
+
+            if self.fuzz.is_some() {
+                gnu.push("-fsanitize=fuzzer".into());
+                msvc.push("/fsanitize=fuzzer".into());
+            }
+
+            if self.crt_leak_check && !self.freestanding {
+                msvc.push("/MDd".into());
+            }
+
+            if self.leak_sanitizer && !self.freestanding {
+                gnu.push("-fsanitize=leak".into());
+            }
+
+            if self.thread_sanitizer && !self.freestanding {
+                gnu.push("-fsanitize=thread".into());
+            }
+
+            (gnu, msvc)
+        }
+
+        /// The arguments linking the libraries of
+        /// [`Assert::link_library`], for GNU-style compilers and for the
+        /// MSVC linker, after `/link`.
+        fn library_args(&self) -> (Vec<OsString>, Vec<OsString>) {
+            let mut gnu = Vec::<OsString>::new();
+            let mut msvc = Vec::<OsString>::new();
+
+            for directory in &self.library_directories {
+                let mut search_path = OsString::from("-L");
+                search_path.push(directory);
+                gnu.push(search_path);
+
+                let mut search_path = OsString::from("/LIBPATH:");
+                search_path.push(directory);
+                msvc.push(search_path);
+            }
+
+            let group = self.link_group
+                && !self.libraries.is_empty()
+                && !self.compiler.target.contains("apple");
+
+            if group {
+                gnu.push("-Wl,--start-group".into());
+            }
+
+            let apple = self.compiler.target.contains("apple");
+            let find = |file_name: String| {
+                self.library_directories
+                    .iter()
+                    .map(|directory| directory.join(&file_name))
+                    .find(|path| path.is_file())
+            };
+
+            for (library, kind) in &self.libraries {
+                match kind {
+                    LinkKind::Static if apple => match find(format!("lib{}.a", library)) {
+                        Some(archive) => gnu.push(archive.into()),
+                        None => gnu.push(format!("-l{}", library).into()),
+                    },
+                    LinkKind::Static => gnu.extend([
+                        "-Wl,-Bstatic".into(),
+                        format!("-l{}", library).into(),
+                        "-Wl,-Bdynamic".into(),
+                    ]),
+                    LinkKind::Default | LinkKind::Dynamic => {
+                        gnu.push(format!("-l{}", library).into())
+                    }
+                }
+
+                msvc.push(match kind {
+                    LinkKind::Dynamic => find(format!("{}.dll.lib", library))
+                        .map(OsString::from)
+                        .unwrap_or_else(|| format!("{}.lib", library).into()),
+                    LinkKind::Default | LinkKind::Static => format!("{}.lib", library).into(),
+                });
+            }
+
+            if group {
+                gnu.push("-Wl,--end-group".into());
+            }
+
+            if self.embeds_rpath() {
+                let origin = if apple { "@loader_path" } else { "$ORIGIN" };
+                let executable_directory = self.executable.parent().unwrap_or(Path::new("."));
+
+                for directory in self.runtime_directories() {
+                    let mut rpath = OsString::from("-Wl,-rpath,");
+
+                    match relative_path(&directory, executable_directory) {
+                        Some(relative) => {
+                            rpath.push(origin);
+                            rpath.push("/");
+                            rpath.push(relative);
+                        }
+                        None => rpath.push(&directory),
+                    }
+
+                    gnu.push(rpath);
+                }
+            }
+
+            (gnu, msvc)
+        }
+
+        /// Run the program, or return the output of the compiler if the
+        /// compilation has failed or if the program is only compiled.
+        fn execute(&mut self) -> io::Result<(Output, Duration, Vec<Chunk>)> {
+            self.ensure_compiled()?;
+
+            if !self.compiled() || self.freestanding {
+                let output = self.compilation.clone().unwrap();
+
+                if let Some(bundle) = &self.record {
+                    replay::record(bundle, &self.source, &self.compiler.command, &output, None)?;
+                }
+
+                return Ok((output, Duration::default(), Vec::new()));
+            }
+
+            if let Some(bundle) = &self.replay {
+                let output = self.replayed_run.clone().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("the bundle `{}` has no run", bundle.display()),
+                    )
+                })?;
+
+                return Ok(self.process_output(output, Duration::default(), Vec::new()));
+            }
+
+            let (output, duration, chunks) =
+                self.capture(|assert| assert.command(), self.stdin.as_deref())?;
+
+            if let Some(bundle) = &self.record {
+                replay::record(
+                    bundle,
+                    &self.source,
+                    &self.compiler.command,
+                    self.compilation.as_ref().unwrap(),
+                    Some((&self.command(), &output)),
+                )?;
+            }
+
+            Ok(self.process_output(output, duration, chunks))
+        }
+
+        /// Run the command returned by `command`, given `stdin`, and
+        /// retry it on transient errors, see [`Assert::retries`].
+        fn capture<F>(
+            &self,
+            command: F,
+            stdin: Option<&[u8]>,
+        ) -> io::Result<(Output, Duration, Vec<Chunk>)>
+        where
+            F: Fn(&Self) -> Command,
+        {
+            network::check(self.network, &self.runner)?;
+
+            if self.sandbox {
+                sandbox::check(&self.runner)?;
+            }
+
+            scheduling::check(&self.runner, self.priority, &self.cpu_affinity)?;
+
+            let mut attempt = 0;
+
+            loop {
+                let start = Instant::now();
+                let output = output::capture(&mut command(self), stdin, self.tee, self.interleaved);
+
+                match output {
+                    Ok((output, chunks)) => return Ok((output, start.elapsed(), chunks)),
+                    Err(error) if attempt < self.retries && retry::is_transient_error(&error) => {
+                        attempt += 1;
+                        retry::wait(attempt);
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+        }
+
+        /// Decode the output of the program according to
+        /// [`Assert::output_encoding`] and [`Assert::strip_ansi`].
+        fn decode(&self, output: &mut Output, chunks: &mut [Chunk]) {
+            if let Some(encoding) = self.output_encoding {
+                output.stdout = output::decode(&output.stdout, encoding);
+                output.stderr = output::decode(&output.stderr, encoding);
+            }
+
+            if self.strip_ansi {
+                output.stdout = output::strip_ansi(&output.stdout);
+                output.stderr = output::strip_ansi(&output.stderr);
+
+                for chunk in chunks.iter_mut() {
+                    chunk.bytes = output::strip_ansi(&chunk.bytes);
+                }
+            }
+        }
+
+        /// Decode the `output` of the program, and report it.
+        fn process_output(
+            &mut self,
+            mut output: Output,
+            duration: Duration,
+            mut chunks: Vec<Chunk>,
+        ) -> (Output, Duration, Vec<Chunk>) {
+            self.decode(&mut output, &mut chunks);
+
+            if let Some(report) = &mut self.report {
+                report.record.ran(duration, &output);
+            }
+
+            if !self.finished_hooks.is_empty() {
+                let result = RunResult {
+                    exit_code: output.status.code(),
+                    stdout: output.stdout.clone(),
+                    stderr: output.stderr.clone(),
+                    duration,
+                    chunks: chunks.clone(),
+                };
+
+                for hook in self.finished_hooks.iter_mut() {
+                    hook(&result);
+                }
+            }
+
+            (output, duration, chunks)
+        }
+
+        /// Assert that the first line of the `first` stream containing
+        /// its text has been received before the first line of the
+        /// `then` stream containing its text, e.g. that an error is
+        /// printed before the result:
+        /// `printed_before((Stream::Stderr, "error"), (Stream::Stdout, "result"))`.
+        /// It enables [`Assert::interleaved`].
+        pub fn printed_before(&mut self, first: (Stream, &str), then: (Stream, &str)) -> &mut Self {
+            self.interleaved();
+
+            let result = self
+                .output()
+                .unwrap_or_else(|error| panic!("Failed to run `{:?}`: {}", self.command(), error));
+            let listing = || {
+                result
+                    .chunks
+                    .iter()
+                    .map(|chunk| {
+                        format!(
+                            "[{:?} +{:?}] {}",
+                            chunk.stream,
+                            chunk.elapsed,
+                            String::from_utf8_lossy(&chunk.bytes).trim_end()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            match (
+                result.position(first.0, first.1),
+                result.position(then.0, then.1),
+            ) {
+                (Some(first_position), Some(then_position)) if first_position < then_position => {}
+                (first_position, then_position) => panic!(
+                    "Expected {:?} in {:?} before {:?} in {:?}, found them at {:?} and {:?} in:\n{}",
+                    first.1,
+                    first.0,
+                    then.1,
+                    then.0,
+                    first_position,
+                    then_position,
+                    listing()
+                ),
+            }
+
+            self
+        }
+
+        /// Shortcut to `self.assert().success()`.
+        pub fn success(&mut self) -> assert_cmd::assert::Assert {
+            self.assert().success()
+        }
+
+        /// Same as [`Assert::success`], but on failure, panic with all
+        /// the context needed to debug the program: its source, the
+        /// command and the diagnostics of the compiler, the command,
+        /// the environment and the output of the program, and its
+        /// artifacts, which are then kept.
+        pub fn success_or_dump(&mut self) -> assert_cmd::assert::Assert {
+            let assert = self.assert();
+
+            if assert.get_output().status.success() {
+                return assert;
+            }
+
+            self.keep_artifacts();
+
+            panic!("{}", self.dump(assert.get_output()));
+        }
+
+        /// Describe the program and the `output` of its failed run.
+        fn dump(&self, output: &Output) -> String {
+            let source =
+                fs::read_to_string(&self.source_path).unwrap_or_else(|_| self.source.clone());
+            let text = |bytes: &[u8]| {
+                if bytes.is_empty() {
+                    "(empty)".to_string()
+                } else {
+                    String::from_utf8_lossy(bytes).trim_end().to_string()
+                }
+            };
+
+            let mut dump = format!(
+                "The program has failed.\n\n=== source `{}`\n",
+                self.source_path.display()
+            );
+
+            for (number, line) in source.lines().enumerate() {
+                dump.push_str(&format!("{:>4} | {}\n", number + 1, line));
+            }
+
+            dump.push_str(&format!(
+                "\n=== compiler command\n{:?}\n",
+                self.compiler.command
+            ));
+
+            if let Some(compilation) = &self.compilation {
+                dump.push_str(&format!(
+                    "\n=== compiler output ({})\n{}\n{}\n",
+                    compilation.status,
+                    text(&compilation.stdout),
+                    text(&compilation.stderr)
+                ));
+            }
+
+            if self.compiled() && !self.freestanding {
+                let mut envs = self.envs.iter().collect::<Vec<_>>();
+                envs.sort();
+
+                dump.push_str(&format!(
+                    "\n=== command\n{:?}\n\n=== environment\n",
+                    self.command()
+                ));
+
+                for (key, value) in envs {
+                    dump.push_str(&format!("{}={}\n", key, value));
+                }
+
+                dump.push_str(&format!(
+                    "\n=== exit status\n{}\n\n=== stdout\n{}\n\n=== stderr\n{}\n",
+                    output.status,
+                    text(&output.stdout),
+                    text(&output.stderr)
+                ));
+            }
+
+            dump.push_str("\n=== artifacts (kept)\n");
+
+            // The objects of MSVC are not written by the other compilers.
+            for artifact in self.artifacts().iter().filter(|artifact| artifact.exists()) {
+                dump.push_str(&format!("{}\n", artifact.display()));
+            }
+
+            dump
+        }
+
+        /// Shortcut to `self.assert().failure()`.
+        pub fn failure(&mut self) -> assert_cmd::assert::Assert {
+            self.assert().failure()
+        }
+
+        /// Shortcut to
+        /// `self.assert().stdout(predicates::unordered_lines_eq(expected))`.
+        pub fn stdout_lines_unordered<S>(&mut self, expected: S) -> assert_cmd::assert::Assert
+        where
+            S: Into<String>,
+        {
+            self.assert()
+                .stdout(predicates::unordered_lines_eq(expected))
+        }
+
+        /// Shortcut to `self.assert().stdout(predicates::eq_diff(expected))`,
+        /// which shows a line diff of the standard output when it
+        /// differs from `expected`.
+        pub fn stdout_eq<S>(&mut self, expected: S) -> assert_cmd::assert::Assert
+        where
+            S: Into<String>,
+        {
+            self.assert().stdout(predicates::eq_diff(expected))
+        }
+
+        /// Shortcut to `self.assert().stderr(predicates::eq_diff(expected))`,
+        /// which shows a line diff of the standard error when it differs
+        /// from `expected`.
+        pub fn stderr_eq<S>(&mut self, expected: S) -> assert_cmd::assert::Assert
+        where
+            S: Into<String>,
+        {
+            self.assert().stderr(predicates::eq_diff(expected))
+        }
+
+        /// Shortcut to `self.assert().stdout(predicates::bytes_eq(expected))`.
+        pub fn stdout_bytes_eq(&mut self, expected: &[u8]) -> assert_cmd::assert::Assert {
+            self.assert().stdout(predicates::bytes_eq(expected))
+        }
+
+        /// Shortcut to `self.assert().stdout(predicates::sha256(expected))`.
+        pub fn stdout_sha256(&mut self, expected: &str) -> assert_cmd::assert::Assert {
+            self.assert().stdout(predicates::sha256(expected))
+        }
+
+        /// Assert that the standard output is equal to the content of
+        /// the golden file at `path`.
+        ///
+        /// When the `UPDATE_GOLDEN` variable is set, e.g. with
+        /// `INLINE_C_RS_UPDATE_GOLDEN=1`, the golden file is written
+        /// with the standard output instead.
+        pub fn stdout_matches_file<P>(&mut self, path: P) -> assert_cmd::assert::Assert
+        where
+            P: AsRef<Path>,
+        {
+            let path = path.as_ref();
+            let assert = self.assert();
+
+            if self.update_golden {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).unwrap_or_else(|error| {
+                        panic!("Failed to create `{}`: {}", parent.display(), error)
+                    });
+                }
+
+                fs::write(path, &assert.get_output().stdout).unwrap_or_else(|error| {
+                    panic!(
+                        "Failed to write the golden file `{}`: {}",
+                        path.display(),
+                        error
+                    )
+                });
+
+                return assert;
+            }
+
+            let expected = fs::read(path).unwrap_or_else(|error| {
+                panic!(
+                    "Failed to read the golden file `{}`: {}; set `INLINE_C_RS_UPDATE_GOLDEN=1` to create it",
+                    path.display(),
+                    error
+                )
+            });
+
+            match String::from_utf8(expected) {
+                Ok(expected) => assert.stdout(predicates::eq_diff(expected)),
+                Err(expected) => assert.stdout(predicates::bytes_eq(expected.into_bytes())),
+            }
+        }
+
+        /// Assert that the program fails to compile, with the
+        /// diagnostics of the expectation file at `path`, e.g.
+        /// `tests/compile-fail/missing-init.stderr`, like `trybuild`.
+        ///
+        /// The output of the compiler is normalized before it is
+        /// compared: the path of the source is replaced by `$SOURCE`,
+        /// its directory by `$DIR`, and the line endings and trailing
+        /// spaces are removed. When the `UPDATE_GOLDEN` variable is
+        /// set, e.g. with `INLINE_C_RS_UPDATE_GOLDEN=1`, the
+        /// expectation file is written instead.
+        pub fn compile_fail_matches_file<P>(&mut self, path: P) -> assert_cmd::assert::Assert
+        where
+            P: AsRef<Path>,
+        {
+            let path = path.as_ref();
+            let assert = self.assert();
+
+            if self.compiled() {
+                panic!(
+                    "The program has compiled, but it was expected to fail\n{}",
+                    assert
+                );
+            }
+
+            let actual = self.normalized_diagnostics();
+
+            if self.update_golden {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent).unwrap_or_else(|error| {
+                        panic!("Failed to create `{}`: {}", parent.display(), error)
+                    });
+                }
+
+                fs::write(path, &actual).unwrap_or_else(|error| {
+                    panic!(
+                        "Failed to write the expectation file `{}`: {}",
+                        path.display(),
+                        error
+                    )
+                });
+
+                return assert;
+            }
+
+            let expected = fs::read_to_string(path).unwrap_or_else(|error| {
+                panic!(
+                    "Failed to read the expectation file `{}`: {}; set `INLINE_C_RS_UPDATE_GOLDEN=1` to create it",
+                    path.display(),
+                    error
+                )
+            });
+            let expected = expected.replace("\r\n", "\n").trim_end().to_string() + "\n";
+
+            if expected != actual {
+                panic!(
+                    "The diagnostics differ from `{}`:\n{}",
+                    path.display(),
+                    crate::diff::colored_lines(&expected, &actual)
+                );
+            }
+
+            assert
+        }
+
+        /// The output of the compiler, without the paths and the line
+        /// endings of the machine.
+        fn normalized_diagnostics(&self) -> String {
+            let compilation = self.compilation.as_ref().unwrap();
+            // `cl.exe` writes its diagnostics to the standard output.
+            let output = format!(
+                "{}{}",
+                String::from_utf8_lossy(&compilation.stdout),
+                String::from_utf8_lossy(&compilation.stderr)
+            );
+
+            let mut replacements = vec![(self.source_path.display().to_string(), "$SOURCE")];
+
+            // `cl.exe` also writes the name of the source alone.
+            if let Some(file_name) = self.source_path.file_name() {
+                replacements.push((file_name.to_string_lossy().into_owned(), "$SOURCE"));
+            }
+
+            if let Some(directory) = self.source_path.parent() {
+                replacements.push((directory.display().to_string(), "$DIR"));
+            }
+
+            let mut normalized = String::new();
+
+            for line in output.lines() {
+                let line = replacements
+                    .iter()
+                    .fold(line.to_string(), |line, (from, to)| line.replace(from, to));
+
+                normalized.push_str(line.trim_end());
+                normalized.push('\n');
+            }
+
+            normalized.trim_end().to_string() + "\n"
+        }
+    }
+
+    /// A compiled program, returned by [`Assert::compile`].
+    pub struct Compiled<'a> {
+        assert: &'a Assert,
+    }
+
+    impl<'a> Compiled<'a> {
+        /// The path of the executable.
+        pub fn executable(&self) -> &Path {
+            &self.assert.executable
+        }
+
+        /// Prepare a run of the program.
+        pub fn run(&self) -> Run<'a> {
+            Run::new(Program::Borrowed(self.assert))
+        }
+    }
+
+    /// The program of a [`Run`].
+    enum Program<'a> {
+        Borrowed(&'a Assert),
+        Shared(&'a Mutex<Option<Assert>>),
+    }
+
+    impl Program<'_> {
+        /// Call `f` with the assertion of the program, locked if it is
+        /// shared.
+        fn with<F, R>(&self, f: F) -> R
+        where
+            F: FnOnce(&Assert) -> R,
+        {
+            match self {
+                Self::Borrowed(assert) => f(assert),
+                Self::Shared(program) => f(program
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .as_ref()
+                    .expect("The shared program has been removed")),
+            }
+        }
+    }
+
+    /// A run of a [`Compiled`] program, with its own arguments,
+    /// environment and standard input.
+    pub struct Run<'a> {
+        program: Program<'a>,
+        args: Vec<OsString>,
+        envs: HashMap<String, String>,
+        stdin: Option<Vec<u8>>,
+    }
+
+    impl<'a> Run<'a> {
+        fn new(program: Program<'a>) -> Self {
+            Self {
+                program,
+                args: Vec::new(),
+                envs: HashMap::new(),
+                stdin: None,
+            }
+        }
+
+        /// A run of the shared `program`, see [`crate::shared`].
+        pub(crate) fn shared(program: &'a Mutex<Option<Assert>>) -> Self {
+            Self::new(Program::Shared(program))
+        }
+
+        /// Add an argument to pass to the program, after the ones of
+        /// the assertion.
+        pub fn arg<S>(&mut self, argument: S) -> &mut Self
+        where
+            S: AsRef<OsStr>,
+        {
+            self.args.push(argument.as_ref().to_os_string());
+
+            self
+        }
+
+        /// Add arguments to pass to the program, after the ones of the
+        /// assertion.
+        pub fn args<I, S>(&mut self, arguments: I) -> &mut Self
+        where
+            I: IntoIterator<Item = S>,
+            S: AsRef<OsStr>,
+        {
+            for argument in arguments {
+                self.arg(argument);
+            }
+
+            self
+        }
+
+        /// Set the environment variable `key` of the program to
+        /// `value`, over the environment of the assertion.
+        pub fn env<K, V>(&mut self, key: K, value: V) -> &mut Self
+        where
+            K: Into<String>,
+            V: Into<String>,
+        {
+            self.envs.insert(key.into(), value.into());
+
+            self
+        }
+
+        /// Write `stdin` to the standard input of the program, instead
+        /// of the one of the assertion.
+        pub fn stdin<B>(&mut self, stdin: B) -> &mut Self
+        where
+            B: Into<Vec<u8>>,
+        {
+            self.stdin = Some(stdin.into());
+
+            self
+        }
+
+        /// The command running the program.
+        fn command(&self) -> Command {
+            self.program
+                .with(|assert| assert.command_with(&self.args, &self.envs))
+        }
+
+        fn execute(&self) -> io::Result<(Output, Duration, Vec<Chunk>)> {
+            self.program.with(|assert| {
+                if assert.replay.is_some() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "a replayed program cannot be run again",
+                    ));
+                }
+
+                let stdin = self.stdin.as_deref().or(assert.stdin.as_deref());
+                let (mut output, duration, mut chunks) =
+                    assert.capture(|assert| assert.command_with(&self.args, &self.envs), stdin)?;
+                assert.decode(&mut output, &mut chunks);
+
+                Ok((output, duration, chunks))
+            })
+        }
+
+        /// Run the program and return its raw result.
+        pub fn output(&self) -> io::Result<RunResult> {
+            let (output, duration, chunks) = self.execute()?;
+
+            Ok(RunResult {
+                exit_code: output.status.code(),
+                stdout: output.stdout,
+                stderr: output.stderr,
+                duration,
+                chunks,
+            })
+        }
+
+        pub fn assert(&self) -> assert_cmd::assert::Assert {
+            let (output, duration, _) = self
+                .execute()
+                .unwrap_or_else(|error| panic!("Failed to run `{:?}`: {}", self.command(), error));
+
+            let assert = assert_cmd::assert::Assert::new(output)
+                .append_context("command", format!("{:?}", self.command()));
+
+            self.program
+                .with(|program| program.check_finishes_within(duration, &assert));
+
+            assert
+        }
+
+        /// Shortcut to `self.assert().success()`.
+        pub fn success(&self) -> assert_cmd::assert::Assert {
+            self.assert().success()
+        }
+
+        /// Shortcut to `self.assert().failure()`.
+        pub fn failure(&self) -> assert_cmd::assert::Assert {
+            self.assert().failure()
+        }
+    }
+
+    impl Drop for Assert {
+        fn drop(&mut self) {
+            if self.compiled() && !self.freestanding {
+                self.runner.cleanup(&self.executable);
+            }
+
+            if let Some(report) = self.report.take() {
+                // An assertion failure panics while `Assert` is still
+                // alive, and an assertion dropped before compiling its
+                // program, e.g. by `skip_if`, has not run.
+                if self.compilation.is_none() && !thread::panicking() {
+                    report.not_run();
+                } else {
+                    report.finish(!thread::panicking());
+                }
+            }
+
+            if self.keep_artifacts {
+                return;
+            }
+
+            if let Some(files_to_remove) = &self.files_to_remove {
+                for file in files_to_remove.iter() {
+                    if fs::metadata(file).is_ok() {
+                        fs::remove_file(file)
+                            .unwrap_or_else(|_| panic!("Failed to remove `{:?}`", file));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The diagnostics in the `output` of the compiler. MSVC writes them
+    /// to the standard output.
+    fn compiler_diagnostics(output: &Output) -> Vec<Diagnostic> {
+        diagnostic::parse(&format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+
+    /// Whether the diagnostic `code` is the `warning`, given by its
+    /// code or name, e.g. `-Wimplicit-function-declaration` or
+    /// `-Werror,-Wimplicit-function-declaration` is
+    /// `implicit-function-declaration`.
+    fn is_warning(code: &str, warning: &str) -> bool {
+        let warning = warning.strip_prefix("-W").unwrap_or(warning);
+        let code = code.rsplit([',', '=']).next().unwrap_or(code);
+
+        code.strip_prefix("-W").unwrap_or(code) == warning
+    }
+
+    /// Replace the paths of the `moved` artifacts in the arguments of
+    /// `command`, including the ones given as the value of a flag, e.g.
+    /// `-Fo<path>`.
+    fn relocate_args(command: &mut Command, moved: &[(PathBuf, PathBuf)]) {
+        rewrite_args(command, |arg| {
+            vec![match arg.to_str() {
+                Some(arg) => moved
+                    .iter()
+                    .fold(arg.to_string(), |arg, (artifact, moved)| {
+                        arg.replace(&*artifact.to_string_lossy(), &moved.to_string_lossy())
+                    })
+                    .into(),
+                None => arg.to_os_string(),
+            }]
+        });
+    }
+
+    /// The path of `directory` relative to `base`, with `/` separators,
+    /// if both exist and have a common root.
+    fn relative_path(directory: &Path, base: &Path) -> Option<String> {
+        let directory = fs::canonicalize(directory).ok()?;
+        let base = fs::canonicalize(base).ok()?;
+
+        let mut directory_components = directory.components().peekable();
+        let mut base_components = base.components().peekable();
+
+        if directory_components.peek() != base_components.peek() {
+            return None;
+        }
+
+        while directory_components.peek().is_some()
+            && directory_components.peek() == base_components.peek()
+        {
+            directory_components.next();
+            base_components.next();
+        }
+
+        let relative = base_components
+            .map(|_| "..".to_string())
+            .chain(
+                directory_components
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned()),
+            )
+            .collect::<Vec<_>>();
+
+        Some(if relative.is_empty() {
+            ".".to_string()
+        } else {
+            relative.join("/")
+        })
+    }
+
+    /// Insert the raw `compiler_args` of `compiler` before the input
+    /// `source_path`, or before `/link` on MSVC, and append its raw
+    /// `linker_args`.
+    fn insert_raw_args(
+        compiler: &mut Compiler,
+        source_path: &Path,
+        compiler_args: &[OsString],
+        linker_args: &[OsString],
+    ) {
+        let msvc = compiler.msvc;
+
+        if !compiler_args.is_empty() {
+            let mut inserted = false;
+
+            rewrite_args(&mut compiler.command, |arg| {
+                let position = if msvc {
+                    arg == "/link" || arg == "-link"
+                } else {
+                    arg == source_path.as_os_str()
+                };
+
+                if position && !inserted {
+                    inserted = true;
+
+                    compiler_args
+                        .iter()
+                        .cloned()
+                        .chain(std::iter::once(arg.to_os_string()))
+                        .collect()
+                } else {
+                    vec![arg.to_os_string()]
+                }
+            });
+
+            if !inserted {
+                compiler.command.args(compiler_args);
+            }
+        }
+
+        if !linker_args.is_empty() {
+            if msvc
+                && !compiler
+                    .command
+                    .get_args()
+                    .any(|arg| arg == "/link" || arg == "-link")
+            {
+                compiler.command.arg("/link");
+            }
+
+            compiler.command.args(linker_args);
+            // So that the batches compile without them.
+            compiler.link_args.extend(linker_args.iter().cloned());
+        }
+    }
+
+    /// Turn the include directories of `command` which are in
+    /// `system_includes` into system include directories, whose headers
+    /// do not trigger warnings, and add the missing ones.
+    fn isolate_system_includes(command: &mut Command, msvc: bool, system_includes: &[PathBuf]) {
+        if system_includes.is_empty() {
+            return;
+        }
+
+        let mut isolated = Vec::new();
+
+        rewrite_args(command, |arg| {
+            let directory = arg
+                .to_str()
+                .and_then(|arg| {
+                    arg.strip_prefix("-I")
+                        .or_else(|| arg.strip_prefix("/I").filter(|_| msvc))
+                })
+                .map(Path::new);
+
+            match directory {
+                Some(directory) if system_includes.iter().any(|system| system == directory) => {
+                    isolated.push(directory.to_path_buf());
+                    system_include(directory, msvc)
+                }
+                _ => vec![arg.to_os_string()],
+            }
+        });
+
+        for directory in system_includes {
+            if !isolated.contains(directory) {
+                command.args(system_include(directory, msvc));
+            }
+        }
+
+        // MSVC still warns in the external headers by default.
+        if msvc {
+            command.arg("/external:W0");
+        }
+    }
+
+    /// The arguments adding the system include `directory`.
+    fn system_include(directory: &Path, msvc: bool) -> Vec<OsString> {
+        if msvc {
+            let mut arg = OsString::from("/external:I");
+            arg.push(directory);
+
+            vec![arg]
+        } else {
+            vec!["-isystem".into(), directory.into()]
+        }
+    }
+
+    /// A copy of `command`, which cannot be cloned.
+    pub(crate) fn copy_command(command: &Command) -> Command {
+        let mut copy = Command::new(command.get_program());
+        copy.args(command.get_args());
+
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => copy.env(key, value),
+                None => copy.env_remove(key),
+            };
+        }
+
+        if let Some(directory) = command.get_current_dir() {
+            copy.current_dir(directory);
+        }
+
+        copy
+    }
+
+    /// Rebuild `command`, with each of its arguments replaced by the
+    /// ones returned by `rewrite`.
+    pub(crate) fn rewrite_args<F>(command: &mut Command, mut rewrite: F)
+    where
+        F: FnMut(&OsStr) -> Vec<OsString>,
+    {
+        let mut rewritten = Command::new(command.get_program());
+
+        for arg in command.get_args() {
+            rewritten.args(rewrite(arg));
+        }
+
+        for (key, value) in command.get_envs() {
+            match value {
+                Some(value) => rewritten.env(key, value),
+                None => rewritten.env_remove(key),
+            };
+        }
+
+        if let Some(directory) = command.get_current_dir() {
+            rewritten.current_dir(directory);
+        }
+
+        *command = rewritten;
+    }
+}
+
+pub mod run {
+
+    pub use crate::backend::Family;
+    use crate::{
+        android::Ndk,
+        assert::{self, Compiler},
+        build_dir, env_file, flags,
+        framework::Framework,
+        fuzz::Fuzz,
+        includes,
+        preflight::Preflight,
+        report, requirement,
+        runner::{self, Runner, Wasi},
+        template, Assert,
+    };
+    use lazy_static::lazy_static;
+    use regex::Regex;
+    use std::{
+        borrow::Cow,
+        collections::HashMap,
+        env,
+        error::Error,
+        ffi::OsString,
+        fmt, fs,
+        io::{self, prelude::*},
+        path::{Component, Path, PathBuf, Prefix},
+        process::Command,
+        time::Duration,
+    };
+
+    /// The language of a program, which selects the extension of its
+    /// source, its compiler, and its flags. More languages may be
+    /// added.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[non_exhaustive]
+    pub enum Language {
+        /// C, compiled by the compiler of the `CC` variable with the
+        /// `CFLAGS`.
+        C,
+
+        /// C++, compiled by the compiler of the `CXX` variable with the
+        /// `CXXFLAGS`.
+        Cxx,
+
+        /// Objective-C, compiled by the compiler of the `CC` variable
+        /// with the `OBJCFLAGS`, and linked with the Objective-C
+        /// runtime.
+        ObjectiveC,
+
+        /// CUDA, compiled by `nvcc`, or the compiler of the `NVCC`
+        /// variable, with the `CUDAFLAGS`.
+        Cuda,
+
+        /// Assembly, preprocessed and assembled by the compiler of the
+        /// `CC` variable with the `ASFLAGS`, or by MASM with MSVC, and
+        /// linked with a C driver calling its `inline_c_main` function,
+        /// see `assert_asm!`.
+        Assembler,
+    }
+
+    impl Language {
+        /// All the languages.
+        pub const ALL: &'static [Language] = &[
+            Self::C,
+            Self::Cxx,
+            Self::ObjectiveC,
+            Self::Cuda,
+            Self::Assembler,
+        ];
+
+        /// The extension of the sources, without the dot.
+        pub fn extension(self) -> &'static str {
+            match self {
+                Self::C => "c",
+                Self::Cxx => "cpp",
+                Self::ObjectiveC => "m",
+                Self::Cuda => "cu",
+                Self::Assembler => "S",
+            }
+        }
+
+        /// The language of the sources with `extension`, without the
+        /// dot, e.g. `cc` is C++.
+        pub fn from_extension(extension: &str) -> Option<Self> {
+            Some(match extension {
+                "c" => Self::C,
+                "cpp" | "cc" | "cxx" | "c++" | "C" => Self::Cxx,
+                "m" => Self::ObjectiveC,
+                "cu" => Self::Cuda,
+                "S" | "s" | "asm" => Self::Assembler,
+                _ => return None,
+            })
+        }
+
+        /// The variable naming the compiler.
+        pub(crate) fn compiler_variable(self) -> &'static str {
+            match self {
+                Self::C | Self::ObjectiveC | Self::Assembler => "CC",
+                Self::Cxx => "CXX",
+                Self::Cuda => "NVCC",
+            }
+        }
+
+        /// The variable holding the flags of the language, given in
+        /// addition to the `CPPFLAGS`.
+        pub(crate) fn flags_variable(self) -> &'static str {
+            match self {
+                Self::C => "CFLAGS",
+                Self::Cxx => "CXXFLAGS",
+                Self::ObjectiveC => "OBJCFLAGS",
+                Self::Cuda => "CUDAFLAGS",
+                Self::Assembler => "ASFLAGS",
+            }
+        }
+
+        /// Configure `build` to find the compiler of the language.
+        pub(crate) fn configure(self, build: &mut cc::Build) {
+            match self {
+                Self::Cxx => {
+                    build.cpp(true);
+                }
+                Self::Cuda => {
+                    build.cuda(true);
+                }
+                Self::C | Self::ObjectiveC | Self::Assembler => (),
+            }
+        }
+
+        /// The flags linking the runtime of the language for `target`,
+        /// with GCC or Clang.
+        pub(crate) fn link_flags(self, target: &str) -> &'static [&'static str] {
+            match self {
+                Self::ObjectiveC if target.contains("apple") => &["-framework", "Foundation"],
+                Self::ObjectiveC => &["-lobjc"],
+                _ => &[],
+            }
+        }
+    }
+
+    impl fmt::Display for Language {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.extension())
+        }
+    }
+
+    /// The C driver of the assembly snippets, unless the `DRIVER`
+    /// variable gives another one: it calls their `inline_c_main`
+    /// function, `_inline_c_main` on macOS and 32-bit Windows, or the
+    /// one given by the `ENTRY` variable, and exits with its result.
+    const DEFAULT_ASSEMBLER_DRIVER: &str = "int inline_c_main(void);
+
+int main(void) {
+    return inline_c_main();
+}
+";
+
+    #[doc(hidden)]
+    pub fn run(language: Language, program: &str) -> Result<Assert, Box<dyn Error>> {
+        run_with(language, program, &Options::default())
+    }
+
+    /// The options given to the macros.
+    #[doc(hidden)]
+    #[derive(Debug, Default)]
+    pub struct Options<'a> {
+        /// The test target expanding the macro, e.g. `integration` for
+        /// `tests/integration.rs`, selecting the
+        /// `INLINE_C_RS_<TARGET>__*` variables.
+        pub target: Option<&'a str>,
+
+        /// The values of the `{{name}}` placeholders of the program.
+        pub template: &'a [(&'a str, &'a str)],
+
+        /// The name of the snippet, see [`is_selected`].
+        pub name: Option<&'a str>,
+
+        /// The C driver of an assembly snippet, given by the `DRIVER`
+        /// variable.
+        pub driver: Option<&'a str>,
+
+        /// The directory of the crate expanding the macro, its
+        /// `CARGO_MANIFEST_DIR`, which the relative paths of the
+        /// variables are resolved against.
+        pub manifest_dir: Option<&'a str>,
+    }
+
+    /// Whether the snippet `name` is selected by the `INLINE_C_RS_ONLY`
+    /// variable, a comma-separated list of names. All the snippets are
+    /// selected when it is not set. The macros turn the assertions of
+    /// the named snippets that are not selected into no-ops, by
+    /// returning [`Skip::skip`] from the enclosing test, which must then
+    /// return `()` or a `Result`. The snippets without a name cannot be
+    /// told apart, and are always run.
+    #[doc(hidden)]
+    pub fn is_selected(name: &str) -> bool {
+        match env::var("INLINE_C_RS_ONLY") {
+            Ok(only) if !only.trim().is_empty() => {
+                only.split(',').any(|selected| selected.trim() == name)
+            }
+            _ => true,
+        }
+    }
+
+    /// The first of the `tags` of a snippet skipped by the
+    /// `INLINE_C_RS_SKIP_TAGS` variable, a comma-separated list of tags,
+    /// e.g. `slow,gpu`, if any. The macros turn the assertions of the
+    /// snippets with such a tag, given with the `tags = ["slow", …]`
+    /// option, into no-ops, like the snippets not selected by
+    /// [`is_selected`].
+    #[doc(hidden)]
+    pub fn skipped_tag<'t>(tags: &[&'t str]) -> Option<&'t str> {
+        let skipped = env::var("INLINE_C_RS_SKIP_TAGS").ok()?;
+
+        tags.iter()
+            .find(|tag| skipped.split(',').any(|skipped| skipped.trim() == **tag))
+            .copied()
+    }
+
+    /// The value returned by a test whose snippet is skipped, see
+    /// [`is_selected`] and [`skip_reason`]. The macros return it from
+    /// the enclosing function of a snippet with a name, tags, or a
+    /// `SKIP_IF_ENV` or `REQUIRES` directive, which must then return
+    /// `()` or a `Result`.
+    #[doc(hidden)]
+    pub trait Skip {
+        fn skip() -> Self;
+    }
+
+    impl Skip for () {
+        fn skip() -> Self {}
+    }
+
+    impl<T, E> Skip for Result<T, E>
+    where
+        T: Skip,
+    {
+        fn skip() -> Self {
+            Ok(T::skip())
+        }
+    }
+
+    /// A skipped program of [`once_compiled!`](crate::once_compiled).
+    impl<T> Skip for Option<T> {
+        fn skip() -> Self {
+            None
+        }
+    }
+
+    /// Like [`run`], with the `options` given to the macros.
+    #[doc(hidden)]
+    pub fn run_with(
+        language: Language,
+        program: &str,
+        options: &Options<'_>,
+    ) -> Result<Assert, Box<dyn Error>> {
+        let template = options
+            .template
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        let program = template::render(program, &template);
+        let (program, mut variables) = collect_environment_variables(&program, options.target)?;
+
+        if let Some(name) = options.name {
+            variables.insert("NAME".to_string(), name.to_string());
+        }
+
+        if let Some(driver) = options.driver {
+            variables.insert("DRIVER".to_string(), driver.to_string());
+        }
+
+        if let Some(directory) = options.manifest_dir {
+            resolve_paths(&mut variables, Path::new(directory));
+        }
+
+        build(language, &program, variables)
+    }
+
+    /// The compiler the snippets are compiled with.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct CompilerInfo {
+        pub family: Family,
+
+        /// The path of the compiler, or its name when it is found in
+        /// the `PATH`.
+        pub path: PathBuf,
+
+        /// The version of the compiler, e.g. `19.29.30133` with MSVC, if
+        /// it could be found.
+        pub version: Option<String>,
+
+        /// The target the compiler compiles for.
+        pub target: String,
+    }
+
+    /// Detect the compiler the snippets written in `language` are
+    /// compiled with, according to the `INLINE_C_RS_*` variables, e.g.
+    /// to skip the tests of C11 atomics with old versions of MSVC.
+    pub fn detect_compiler(language: Language) -> Result<CompilerInfo, Box<dyn Error>> {
+        let (_, variables) = collect_environment_variables("", None)?;
+
+        compiler_info(language, &variables)
+    }
+
+    /// Check the toolchain once, e.g. before the tests run, according
+    /// to the `INLINE_C_RS_*` variables: the variables themselves, the
+    /// include and library directories given to the compilers, the C
+    /// and C++ compilers, the environment of MSVC, and a minimal program
+    /// of each language, compiled and run. A misconfigured machine then
+    /// fails once, with `assert!(preflight.is_ok(), "{}", preflight)`.
+    pub fn preflight() -> Preflight {
+        const PROGRAM: &str = "int main(void) {\n    return 0;\n}\n";
+
+        let mut preflight = Preflight::default();
+        let variables = match collect_environment_variables("", None) {
+            Ok((_, variables)) => variables,
+            Err(error) => {
+                preflight.check("variables", Err(error.to_string()));
+
+                return preflight;
+            }
+        };
+
+        preflight.check(
+            "variables",
+            Ok(format!("{} variables are set", variables.len())),
+        );
+
+        for (kind, variable, directory) in flag_directories(&variables) {
+            let outcome = if directory.is_dir() {
+                Ok(format!(
+                    "`{}`, given by {}",
+                    directory.display(),
+                    origin(&variables, variable)
+                ))
+            } else {
+                Err(format!(
+                    "`{}`, given by {}, does not exist",
+                    directory.display(),
+                    origin(&variables, variable)
+                ))
+            };
+
+            preflight.check(format!("{} directory", kind), outcome);
+        }
+
+        let host = target_lexicon::HOST.to_string();
+
+        for (language, name) in [(Language::C, "C"), (Language::Cxx, "C++")] {
+            let compiler = match compiler_info(language, &variables) {
+                Ok(compiler) => compiler,
+                Err(error) => {
+                    preflight.check(format!("{} compiler", name), Err(error.to_string()));
+
+                    continue;
+                }
+            };
+            let tool = match find_compiler(language, &variables, &host, &compiler.target) {
+                Ok((tool, _)) => tool,
+                Err(error) => {
+                    preflight.check(format!("{} compiler", name), Err(error.to_string()));
+
+                    continue;
+                }
+            };
+
+            // The compiler must print its version successfully, e.g. not
+            // be a broken wrapper script.
+            let outcome = match Command::new(tool.path())
+                .envs(tool.get_envs())
+                .args(version_args(compiler.family))
+                .output()
+            {
+                Ok(output) if !output.status.success() => Err(format!(
+                    "`{}` has failed with {}: {}",
+                    compiler.path.display(),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                )),
+                Ok(_) if compiler.version.is_none() => Err(format!(
+                    "`{}` has not printed its version",
+                    compiler.path.display()
+                )),
+                Ok(_) => Ok(format!(
+                    "`{}` ({:?}, version {}), for `{}`",
+                    compiler.path.display(),
+                    compiler.family,
+                    compiler.version.as_deref().unwrap_or("unknown"),
+                    compiler.target
+                )),
+                Err(error) => Err(format!(
+                    "`{}` cannot be run: {}",
+                    compiler.path.display(),
+                    error
+                )),
+            };
+
+            if !preflight.check(format!("{} compiler", name), outcome) {
+                continue;
+            }
+
+            // MSVC finds the headers and the libraries with the
+            // `INCLUDE` and `LIB` variables of a developer prompt, unless
+            // `cc` has found them, and links the snippets against the
+            // `.dll` of the `LDFLAGS`.
+            if compiler.family.is_msvc() {
+                let missing = ["INCLUDE", "LIB"]
+                    .into_iter()
+                    .filter(|variable| {
+                        env::var_os(variable).is_none()
+                            && !tool.get_envs().any(|(key, _)| key == *variable)
+                    })
+                    .collect::<Vec<_>>();
+                let outcome = if missing.is_empty() {
+                    Ok("`INCLUDE` and `LIB` are set".to_string())
+                } else {
+                    Err(format!(
+                        "`{}` not set, e.g. outside of a developer prompt",
+                        missing.join("` and `")
+                    ))
+                };
+
+                preflight.check(format!("{} environment", name), outcome);
+
+                let outcome = msvc_link_library(&get_env_flags(&variables, "LDFLAGS")).map(
+                    |(directory, library)| {
+                        format!(
+                            "`{}`, in `{}`",
+                            library.to_string_lossy(),
+                            directory.display()
+                        )
+                    },
+                );
+
+                if !preflight.check(format!("{} link library", name), outcome) {
+                    continue;
+                }
+            }
+
+            let outcome = match run(language, PROGRAM).and_then(|mut assert| Ok(assert.output()?)) {
+                Ok(result) if result.success() => Ok("compiled and run".to_string()),
+                Ok(result) => Err(format!(
+                    "the compilation or the run has failed with the exit code {:?}: {}{}",
+                    result.exit_code,
+                    String::from_utf8_lossy(&result.stdout),
+                    String::from_utf8_lossy(&result.stderr).trim_end()
+                )),
+                Err(error) => Err(error.to_string()),
+            };
+
+            preflight.check(format!("{} program", name), outcome);
+        }
+
+        preflight
+    }
+
+    /// The reason why the assertion of `program` is skipped, if any:
+    /// one of the environment variables of its `SKIP_IF_ENV` variable is
+    /// set, or the compiler does not satisfy its `REQUIRES` variable,
+    /// see the [`requirement`](crate::requirement) module. The macros
+    /// skip the assertion of a snippet with these directives in these
+    /// cases.
+    #[doc(hidden)]
+    pub fn skip_reason(language: Language, program: &str, options: &Options<'_>) -> Option<String> {
+        // The other errors are reported by `run_with`.
+        let (_, variables) = collect_environment_variables(program, options.target).ok()?;
+
+        skipped_by_environment(&variables)
+            .and_then(|()| unsatisfied(language, &variables))
+            .err()
+    }
+
+    /// Check the `SKIP_IF_ENV` variable, a comma-separated list of
+    /// environment variables, e.g. `CI_NO_GPU`, against the environment:
+    /// the assertion is skipped when one of them is set to a value other
+    /// than an empty one, `0`, `false`, `no` or `off`.
+    fn skipped_by_environment(variables: &HashMap<String, String>) -> Result<(), String> {
+        let Some(names) = variables.get("SKIP_IF_ENV") else {
+            return Ok(());
+        };
+        // The first variable which is set, in the order of the directive.
+        let set = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .find(|name| {
+                let environment = env::var(name)
+                    .map(|value| HashMap::from([(name.to_string(), value)]))
+                    .unwrap_or_default();
+
+                is_enabled(&environment, name)
+            });
+
+        match set {
+            Some(name) => Err(format!(
+                "the environment variable `{}` is set, see `SKIP_IF_ENV: {}`",
+                name, names
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the `REQUIRES` variable against the compiler.
+    fn unsatisfied(language: Language, variables: &HashMap<String, String>) -> Result<(), String> {
+        let Some(requirement) = variables.get("REQUIRES") else {
+            return Ok(());
+        };
+        let compiler = compiler_info(language, variables).map_err(|error| error.to_string())?;
+
+        if requirement::is_satisfied(requirement, &compiler)? {
+            Ok(())
+        } else {
+            Err(format!(
+                "the compiler `{}` ({:?}, version {}) does not satisfy `REQUIRES: {}`",
+                compiler.path.display(),
+                compiler.family,
+                compiler.version.as_deref().unwrap_or("unknown"),
+                requirement
+            ))
+        }
+    }
+
+    fn compiler_info(
+        language: Language,
+        variables: &HashMap<String, String>,
+    ) -> Result<CompilerInfo, Box<dyn Error>> {
+        let host = target_lexicon::HOST.to_string();
+        let target = variables
+            .get("TARGET")
+            .cloned()
+            .unwrap_or_else(|| host.clone());
+        let (compiler, _) = find_compiler(language, variables, &host, &target)?;
+        let family = Family::detect(&compiler);
+        let mut command = Command::new(compiler.path());
+        command.envs(compiler.get_envs());
+
+        Ok(CompilerInfo {
+            family,
+            path: compiler.path().to_path_buf(),
+            version: compiler_version(command, family),
+            target,
+        })
+    }
+
+    /// The arguments making a compiler of the `family` print its version:
+    /// MSVC prints it in its banner when it is run without arguments, and
+    /// TinyCC with `-v`.
+    fn version_args(family: Family) -> &'static [&'static str] {
+        match family {
+            Family::Msvc | Family::Intel { msvc: true } => &[],
+            Family::Tiny => &["-v"],
+            _ => &["--version"],
+        }
+    }
+
+    /// The version of the compiler of the `family`, run by `command`,
+    /// if it could be found.
+    pub(crate) fn compiler_version(mut command: Command, family: Family) -> Option<String> {
+        lazy_static! {
+            static ref VERSION: Regex = Regex::new(r"\d+(?:\.\d+)+").unwrap();
+        }
+
+        command.args(version_args(family));
+
+        command.output().ok().and_then(|output| {
+            let output = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+
+            VERSION
+                .find(&output)
+                .map(|version| version.as_str().to_string())
+        })
+    }
+
+    /// Prepare the compilation of `program`, once its variables have
+    /// been collected.
+    pub(crate) fn build(
+        language: Language,
+        program: &str,
+        variables: HashMap<String, String>,
+    ) -> Result<Assert, Box<dyn Error>> {
+        // The macros skip these assertions with `skip_reason` before
+        // they get there, so only `run` reports them as errors.
+        skipped_by_environment(&variables)?;
+        unsatisfied(language, &variables)?;
+
+        // A missing directory, e.g. a typo in the `CFLAGS`, is reported
+        // with the variable giving it, rather than by the compiler,
+        // unless the `CHECK_PATHS` variable disables the check.
+        if !variables.contains_key("CHECK_PATHS") || is_enabled(&variables, "CHECK_PATHS") {
+            check_directories(&variables)?;
+        }
+
+        let build_directory = match variables.get("ARTIFACT_DIR") {
+            Some(directory) => {
+                fs::create_dir_all(directory)?;
+                PathBuf::from(directory)
+            }
+            None => build_dir::directory(),
+        };
+
+        // The artifacts of a named snippet are easier to find.
+        let prefix = match variables.get("NAME") {
+            Some(name) => format!(
+                "inline-c-rs-{}-",
+                name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_")
+            ),
+            None => "inline-c-rs-".to_string(),
+        };
+
+        let host = target_lexicon::HOST.to_string();
+        let target = variables.get("TARGET").unwrap_or(&host);
+
+        let wasm = target.starts_with("wasm");
+        let android = target.contains("android");
+
+        let universal = is_enabled(&variables, "UNIVERSAL");
+
+        if universal && !target.ends_with("-apple-darwin") {
+            return Err(format!(
+                "universal binaries can only be built for macOS, not for `{}`",
+                target
+            )
+            .into());
+        }
+
+        // WebAssembly modules are run with the `wasmer` CLI, or the one
+        // given by the `WASMER` variable. Windows executables built on
+        // another host are run with Wine, Android executables with adb,
+        // iOS executables in the simulator given by the `SIMULATOR`
+        // variable, and executables for another architecture with QEMU.
+        let runner = if let Some(runner) = variables.get("RUNNER") {
+            Runner::parse(runner)?
+        } else if wasm {
+            Runner::Wasmer {
+                program: variables
+                    .get("WASMER")
+                    .map_or_else(|| PathBuf::from("wasmer"), PathBuf::from),
+                wasi: Wasi::default(),
+            }
+        } else if target.contains("windows") && !host.contains("windows") {
+            Runner::Wine {
+                program: PathBuf::from("wine"),
+            }
+        } else if android {
+            Runner::Adb {
+                program: PathBuf::from("adb"),
+                serial: variables.get("ANDROID_SERIAL").cloned(),
+            }
+        } else if runner::is_ios_simulator(target) {
+            Runner::Simctl {
+                device: variables
+                    .get("SIMULATOR")
+                    .cloned()
+                    .unwrap_or_else(|| "booted".to_string()),
+            }
+        } else if let Some(program) = runner::qemu_for(target, &host) {
+            // The sysroot can be given per target, like
+            // `QEMU_SYSROOT_AARCH64_UNKNOWN_LINUX_GNU`, or for all
+            // targets with `QEMU_SYSROOT`.
+            let per_target = format!(
+                "QEMU_SYSROOT_{}",
+                target.to_uppercase().replace(['-', '.'], "_")
+            );
+
+            Runner::Qemu {
+                program,
+                sysroot: variables
+                    .get(&per_target)
+                    .or_else(|| variables.get("QEMU_SYSROOT"))
+                    .map(PathBuf::from),
+            }
+        } else {
+            Runner::Native
+        };
+
+        let mut program_file = tempfile::Builder::new()
+            .prefix(&prefix)
+            .suffix(&format!(".{}", language))
+            .tempfile_in(&build_directory)?;
+
+        // The entry point of a snippet other than `main`, e.g.
+        // `example_main` in a snippet copied from an example, is renamed
+        // `main` by the preprocessor, whatever its parameters. The
+        // driver of an assembly snippet calls it instead.
+        let entry = match variables.get("ENTRY") {
+            Some(entry)
+                if entry.is_empty()
+                    || entry.starts_with(|c: char| c.is_ascii_digit())
+                    || !entry.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            {
+                return Err(format!(
+                    "invalid `ENTRY` variable `{}`, expected the name of a function",
+                    entry
+                )
+                .into());
+            }
+            entry => entry.map(String::as_str),
+        };
+
+        // It is part of the source of the assertion, which is written
+        // again before the compilation, e.g. by the source hooks.
+        let program = match entry {
+            Some(entry) if entry != "main" && language != Language::Assembler => {
+                Cow::Owned(format!("#define {} main\n#line 1\n{}", entry, program))
+            }
+            _ => Cow::Borrowed(program),
+        };
+        let program = &*program;
+
+        program_file.write_all(program.as_bytes())?;
+
+        let input_path = program_file.path().to_path_buf();
+        let mut output_temp = tempfile::Builder::new();
+        let output_temp = output_temp.prefix(&prefix);
+
+        if wasm {
+            output_temp.suffix(".wasm");
+        } else if target.contains("windows") {
+            output_temp.suffix(".exe");
+        }
+
+        let output_file = output_temp.tempfile_in(&build_directory)?;
+        let output_path = output_file.path().to_path_buf();
+
+        let mut slice_compilers = Vec::new();
+        let mut build_steps = Vec::new();
+        let mut slices = Vec::new();
+
+        // A universal binary is built from one slice per architecture,
+        // merged with `lipo`. The native slice is run, and the other one
+        // is only compiled, which catches the `#if` breakage specific to
+        // an architecture.
+        let mut compiler = if universal {
+            let other_target = if target.starts_with("aarch64") {
+                "x86_64-apple-darwin"
+            } else {
+                "aarch64-apple-darwin"
+            };
+
+            for slice_target in [target.as_str(), other_target] {
+                let mut slice = output_path.clone().into_os_string();
+                slice.push(".");
+                slice.push(slice_target.split('-').next().unwrap_or_default());
+                slices.push(PathBuf::from(slice));
+            }
+
+            slice_compilers.push(compiler_command(
+                language,
+                program,
+                &variables,
+                &host,
+                other_target,
+                &input_path,
+                &slices[1],
+            )?);
+
+            let mut lipo = Command::new("lipo");
+            lipo.arg("-create")
+                .arg("-output")
+                .arg(&output_path)
+                .args(&slices);
+
+            build_steps.push(lipo);
+
+            compiler_command(
+                language,
+                program,
+                &variables,
+                &host,
+                target,
+                &input_path,
+                &slices[0],
+            )?
+        } else {
+            compiler_command(
+                language,
+                program,
+                &variables,
+                &host,
+                target,
+                &input_path,
+                &output_path,
+            )?
+        };
+
+        // An assembly snippet is linked with a C driver calling it.
+        let mut pre_build_steps = Vec::new();
+        let mut driver_files = Vec::new();
+
+        if language == Language::Assembler {
+            let driver_path = input_path.with_extension("driver.c");
+            let object_path = input_path.with_extension("asm.obj");
+
+            let driver = match (variables.get("DRIVER"), entry) {
+                (Some(driver), _) => driver.clone(),
+                (None, Some(entry)) => DEFAULT_ASSEMBLER_DRIVER.replace("inline_c_main", entry),
+                (None, None) => DEFAULT_ASSEMBLER_DRIVER.to_string(),
+            };
+
+            fs::write(&driver_path, driver)?;
+
+            for compiler in std::iter::once(&mut compiler).chain(slice_compilers.iter_mut()) {
+                // MSVC cannot assemble the snippet, so it is assembled
+                // by MASM first, and its object is linked.
+                let inputs = if compiler.msvc {
+                    pre_build_steps.push(masm_command(
+                        compiler,
+                        &variables,
+                        target,
+                        &input_path,
+                        &object_path,
+                    ));
 
-    /// `Assert` is a wrapper around the [`assert_cmd::assert::Assert`]
-    /// struct.
-    pub struct Assert {
-        command: assert_cmd::Command,
-        files_to_remove: Option<Vec<PathBuf>>,
-    }
+                    vec![driver_path.clone(), object_path.clone()]
+                } else {
+                    vec![input_path.clone(), driver_path.clone()]
+                };
 
-    impl Assert {
-        pub(crate) fn new(command: Command, files_to_remove: Option<Vec<PathBuf>>) -> Self {
-            Self {
-                command: assert_cmd::Command::from_std(command),
-                files_to_remove,
+                assert::rewrite_args(&mut compiler.command, |arg| {
+                    if arg == input_path.as_os_str() {
+                        inputs.iter().map(OsString::from).collect()
+                    } else {
+                        vec![arg.to_os_string()]
+                    }
+                });
             }
+
+            driver_files.extend([driver_path, object_path]);
         }
 
-        pub fn assert(&mut self) -> assert_cmd::assert::Assert {
-            self.command.assert()
+        compiler.command.envs(&variables);
+
+        let mut intermediate_path = output_path.clone();
+        intermediate_path.set_extension("obj");
+
+        for compiler in &mut slice_compilers {
+            compiler.command.envs(&variables);
         }
 
-        /// Shortcut to `self.assert().success()`.
-        pub fn success(&mut self) -> assert_cmd::assert::Assert {
-            self.assert().success()
+        for step in pre_build_steps.iter_mut().chain(build_steps.iter_mut()) {
+            step.envs(&variables);
         }
 
-        /// Shortcut to `self.assert().failure()`.
-        pub fn failure(&mut self) -> assert_cmd::assert::Assert {
-            self.assert().failure()
+        let report = report::Pending::new(
+            variables.get("REPORT").map(PathBuf::from),
+            variables.get("JUNIT").map(PathBuf::from),
+            report::Record::new(program, variables.get("NAME").map(String::as_str)),
+        );
+
+        // The files are removed on the errors above, and by the
+        // assertion from now on.
+        program_file.keep()?;
+        output_file.keep()?;
+
+        let mut assert = Assert::new(
+            input_path.clone(),
+            program.to_string(),
+            output_path.clone(),
+            compiler,
+            runner,
+            variables.clone(),
+            Some(
+                [input_path, output_path, intermediate_path]
+                    .into_iter()
+                    .chain(slices)
+                    .chain(driver_files)
+                    .collect(),
+            ),
+        );
+
+        for compiler in slice_compilers {
+            assert.slice(compiler);
         }
-    }
 
-    impl Drop for Assert {
-        fn drop(&mut self) {
-            if let Some(files_to_remove) = &self.files_to_remove {
-                for file in files_to_remove.iter() {
-                    if fs::metadata(file).is_ok() {
-                        fs::remove_file(file)
-                            .unwrap_or_else(|_| panic!("Failed to remove `{:?}`", file));
-                    }
-                }
+        for step in pre_build_steps {
+            assert.pre_build_step(step);
+        }
+
+        for step in build_steps {
+            assert.build_step(step);
+        }
+
+        if is_enabled(&variables, "FREESTANDING") {
+            assert.freestanding();
+        }
+
+        assert.openmp(is_enabled(&variables, "OPENMP"));
+        assert.threads(is_enabled(&variables, "THREADS"));
+        assert.math(is_enabled(&variables, "MATH"));
+        assert.linker_map(is_enabled(&variables, "LINKER_MAP"));
+        assert.batch(is_enabled(&variables, "BATCH"));
+
+        if let Some(names) = variables.get("WORKSPACE_CRATES") {
+            for name in names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+            {
+                assert.link_workspace_crate(name);
             }
         }
-    }
-}
 
-pub mod run {
+        if let Some(directories) = variables.get("SYSTEM_INCLUDES") {
+            for directory in env::split_paths(directories) {
+                assert.system_include(directory);
+            }
+        }
 
-    use crate::Assert;
-    use lazy_static::lazy_static;
-    use regex::Regex;
-    use std::{
-        borrow::Cow, collections::HashMap, env, error::Error, ffi::OsString, io::prelude::*,
-        path::PathBuf, process::Command,
-    };
+        if let Some(libraries) = variables.get("LINK_LIBRARIES") {
+            for library in libraries
+                .split(',')
+                .map(str::trim)
+                .filter(|library| !library.is_empty())
+            {
+                if let Some(library) = library.strip_prefix("static=") {
+                    assert.link_static(library);
+                } else if let Some(library) = library.strip_prefix("dylib=") {
+                    assert.link_dylib(library);
+                } else {
+                    assert.link_library(library);
+                }
+            }
+        }
 
-    static INCLUDE_REGEX: &str = "#include \"(.*)\"";
+        if let Some(directories) = variables.get("LINK_SEARCH_PATHS") {
+            for directory in env::split_paths(directories) {
+                assert.link_search_path(directory);
+            }
+        }
 
-    #[doc(hidden)]
-    pub enum Language {
-        C,
-        Cxx,
-    }
+        assert.link_group(is_enabled(&variables, "LINK_GROUP"));
+        assert.rpath(is_enabled(&variables, "RPATH"));
+        assert.stage_dlls(is_enabled(&variables, "STAGE_DLLS"));
 
-    impl ToString for Language {
-        fn to_string(&self) -> String {
-            match self {
-                Self::C => String::from("c"),
-                Self::Cxx => String::from("cpp"),
+        if let Some(directories) = variables.get("DLL_SEARCH_PATHS") {
+            for directory in env::split_paths(directories) {
+                assert.dll_search_path(directory);
             }
         }
-    }
 
-    #[doc(hidden)]
-    pub fn run(language: Language, program: &str) -> Result<Assert, Box<dyn Error>> {
-        let (program, variables) = collect_environment_variables(program);
+        if let Some(warnings) = variables.get("DENY_WARNINGS") {
+            assert.deny_warnings(&warnings.split(',').collect::<Vec<_>>());
+        }
 
-        let mut program_file = tempfile::Builder::new()
-            .prefix("inline-c-rs-")
-            .suffix(&format!(".{}", language.to_string()))
-            .tempfile()?;
+        if let Some(directory) = variables.get("CACHE") {
+            assert.cache(directory);
+        }
+        assert.crt_leak_check(is_enabled(&variables, "CRT_LEAK_CHECK"));
+        assert.leak_sanitizer(is_enabled(&variables, "LEAK_SANITIZER"));
 
-        program_file.write_all(program.as_bytes())?;
+        if let Some(suppressions) = variables.get("LSAN_SUPPRESSIONS") {
+            assert.lsan_suppressions(suppressions);
+        }
 
-        let host = target_lexicon::HOST.to_string();
-        let target = &host;
+        assert.thread_sanitizer(is_enabled(&variables, "THREAD_SANITIZER"));
 
-        let msvc = target.contains("msvc");
-        if !msvc {
-            panic!("This crate only works with MSVC and Wasmer on Windows!");
+        if let Some(directory) = variables.get("RECORD") {
+            assert.record(directory);
         }
 
-        let (_, input_path) = program_file.keep()?;
-        let mut output_temp = tempfile::Builder::new();
-        let output_temp = output_temp.prefix("inline-c-rs-");
-        output_temp.suffix(".exe");
+        if let Some(directory) = variables.get("REPLAY") {
+            assert.replay(directory);
+        }
+
+        if let Some(suppressions) = variables.get("TSAN_SUPPRESSIONS") {
+            assert.tsan_suppressions(suppressions);
+        }
+
+        if let Some(directory) = variables.get("UNITY") {
+            assert.framework(Framework::Unity(PathBuf::from(directory)));
+        } else if let Some(prefix) = variables.get("GTEST") {
+            assert.framework(Framework::GoogleTest(PathBuf::from(prefix)));
+        }
+
+        if is_enabled(&variables, "FUZZ") {
+            assert.fuzz(Fuzz {
+                runs: variables
+                    .get("FUZZ_RUNS")
+                    .map(|runs| runs.trim().parse())
+                    .transpose()?,
+                max_total_time: variables
+                    .get("FUZZ_TIME")
+                    .map(|seconds| seconds.trim().parse().map(Duration::from_secs))
+                    .transpose()?,
+                corpus: variables.get("FUZZ_CORPUS").map(PathBuf::from),
+            });
+        }
+
+        if let Some(script) = variables.get("RESOURCE") {
+            assert.resource(script);
+        }
+
+        if let Some(manifest) = variables.get("MANIFEST") {
+            assert.manifest(manifest);
+        }
+
+        if let Some(directory) = variables.get("CWD") {
+            assert.current_dir(directory);
+        }
+
+        if let Some(report) = report {
+            assert.report(report);
+        }
+
+        if is_enabled(&variables, "STRIP_ANSI") {
+            assert.strip_ansi();
+        }
+
+        if let Some(encoding) = variables.get("OUTPUT_ENCODING") {
+            assert.output_encoding(encoding.parse()?);
+        }
+
+        if let Some(network) = variables.get("NETWORK") {
+            assert.network(network.parse()?);
+        }
+
+        assert.sandbox(is_enabled(&variables, "SANDBOX"));
+
+        if let Some(priority) = variables.get("PRIORITY") {
+            assert.priority(priority.trim().parse()?);
+        }
+
+        if let Some(cpus) = variables.get("CPU_AFFINITY") {
+            assert.cpu_affinity(
+                &cpus
+                    .split(',')
+                    .map(|cpu| cpu.trim().parse())
+                    .collect::<Result<Vec<_>, _>>()?,
+            );
+        }
+
+        if is_enabled(&variables, "TEE") {
+            assert.tee();
+        }
+
+        if is_enabled(&variables, "INTERLEAVED") {
+            assert.interleaved();
+        }
+
+        if is_enabled(&variables, "DETERMINISTIC") {
+            assert.deterministic();
+        }
+
+        assert.update_golden(is_enabled(&variables, "UPDATE_GOLDEN"));
+
+        if let Some(retries) = variables.get("RETRIES") {
+            assert.retries(retries.trim().parse()?);
+        }
+
+        if let Some(jobs) = variables.get("COMPILE_JOBS") {
+            assert.compile_jobs(jobs.trim().parse()?);
+        }
+
+        assert.compare_optimizations(is_enabled(&variables, "COMPARE_OPTIMIZATIONS"));
+        assert.format_source(is_enabled(&variables, "FORMAT_SOURCE"));
+        assert.stamp(is_enabled(&variables, "STAMP"));
+
+        if let Some(file) = variables.get("CLANG_FORMAT_FILE") {
+            assert.clang_format_file(file);
+        }
 
-        let (_, output_path) = output_temp.tempfile()?.keep()?;
+        if let Some(seconds) = variables.get("FINISHES_WITHIN") {
+            assert.finishes_within(Duration::try_from_secs_f64(seconds.trim().parse()?)?);
+        }
+
+        assert.language(language);
+
+        Ok(assert)
+    }
+
+    /// The compiler of `language` for `target`, selected by the
+    /// variables, and the Android NDK it comes from, if any.
+    fn find_compiler(
+        language: Language,
+        variables: &HashMap<String, String>,
+        host: &str,
+        target: &str,
+    ) -> Result<(cc::Tool, Option<Ndk>), Box<dyn Error>> {
+        let (build, ndk) = configured_build(language, variables, host, target)?;
+
+        Ok((build.try_get_compiler()?, ndk))
+    }
+
+    /// The `cc::Build` finding the compiler of `language` for `target`,
+    /// and the NDK when targeting Android.
+    pub(crate) fn configured_build(
+        language: Language,
+        variables: &HashMap<String, String>,
+        host: &str,
+        target: &str,
+    ) -> Result<(cc::Build, Option<Ndk>), Box<dyn Error>> {
+        let android = target.contains("android");
 
         let mut build = cc::Build::new();
         let mut build = build
@@ -105,80 +4720,216 @@ pub mod run {
             .extra_warnings(true)
             .warnings_into_errors(true)
             .debug(false)
-            .host(&host)
+            .host(host)
             .target(target)
             .opt_level(1);
 
-        if let Language::Cxx = language {
-            build = build.cpp(true);
+        language.configure(build);
+
+        let ndk = if android {
+            Some(Ndk::find(target, language, variables)?)
+        } else {
+            None
+        };
+
+        if let Some(ndk) = &ndk {
+            build = build.compiler(&ndk.compiler);
+        }
+
+        // TinyCC compiles small C programs much faster than the other
+        // compilers, to iterate on them locally. The real compiler is
+        // kept on CI, for C++, and to cross-compile.
+        if is_enabled(variables, "TCC")
+            && language == Language::C
+            && target == host
+            && env::var_os("CI").is_none()
+        {
+            build = build.compiler("tcc");
+        }
+
+        // `cc` also reads the `CC` and `CXX` environment variables.
+        if let Some(program) = variables.get(language.compiler_variable()) {
+            build = build.compiler(program);
+        }
+
+        Ok((build.clone(), ndk))
+    }
+
+    /// The command assembling `input_path` into `object_path` with the
+    /// MASM of the MSVC `compiler` for `target`, or the assembler of the
+    /// `AS` variable, with the `ASFLAGS`.
+    fn masm_command(
+        compiler: &Compiler,
+        variables: &HashMap<String, String>,
+        target: &str,
+        input_path: &Path,
+        object_path: &Path,
+    ) -> Command {
+        let armasm = target.starts_with("aarch64");
+        let assembler = if armasm {
+            "armasm64.exe"
+        } else if target.starts_with("x86_64") {
+            "ml64.exe"
+        } else {
+            "ml.exe"
+        };
+
+        // The assemblers are installed next to `cl.exe`.
+        let program = variables.get("AS").map(PathBuf::from).unwrap_or_else(|| {
+            Path::new(compiler.command.get_program())
+                .parent()
+                .map(|directory| directory.join(assembler))
+                .filter(|assembler| assembler.is_file())
+                .unwrap_or_else(|| PathBuf::from(assembler))
+        });
+
+        let mut command = Command::new(program);
+
+        if armasm {
+            command.args(["-nologo", "-o"]).arg(object_path);
+        } else {
+            let mut output = OsString::from("/Fo");
+            output.push(object_path);
+            command.args(["/nologo", "/c"]).arg(output);
+        }
+
+        command
+            .args(get_env_flags(variables, "ASFLAGS"))
+            .arg(input_path);
+
+        for (key, value) in compiler.command.get_envs() {
+            match value {
+                Some(value) => command.env(key, value),
+                None => command.env_remove(key),
+            };
         }
 
-        let compiler = build.try_get_compiler()?;
+        command
+    }
+
+    /// The command compiling and linking `input_path` for `target`
+    /// into `output_path`.
+    fn compiler_command(
+        language: Language,
+        program: &str,
+        variables: &HashMap<String, String>,
+        host: &str,
+        target: &str,
+        input_path: &Path,
+        output_path: &Path,
+    ) -> Result<Compiler, Box<dyn Error>> {
+        let (compiler, ndk) = find_compiler(language, variables, host, target)?;
+
+        // The flags depend on the family of the compiler, not on the
+        // target: e.g. `clang` can target MSVC with GNU-style flags, and
+        // `clang-cl` is like MSVC.
+        let family = Family::detect(&compiler);
+        let msvc = family.is_msvc();
         let mut command = compiler.to_command();
 
-        let cflags = get_env_flags(&variables, "CFLAGS");
+        if let Some(ndk) = &ndk {
+            let mut sysroot = OsString::from("--sysroot=");
+            sysroot.push(&ndk.sysroot);
+            command.arg(sysroot);
+
+            // The device has no C++ runtime to link against dynamically.
+            if let Language::Cxx = language {
+                command.arg("-static-libstdc++");
+            }
+        }
+
+        // The `CPPFLAGS` are given to all the languages, the `CFLAGS`
+        // to C only, the `CXXFLAGS` to C++ only, and so on.
+        let mut cflags = get_env_flags(variables, "CPPFLAGS");
+
+        // MASM is given the `ASFLAGS` of the assembly snippets, and
+        // `cl.exe` only compiles their driver.
+        if !(msvc && language == Language::Assembler) {
+            cflags.extend(get_env_flags(variables, language.flags_variable()));
+        }
 
         // MSVC cannot follow symlinks for some reason
-        let mut log = String::new();
-        let include_paths = cflags
-            .iter()
-            .filter(|s| s.starts_with("-I"))
-            .cloned()
-            .collect::<Vec<_>>();
-        fixup_symlinks(include_paths.as_ref(), &mut log)?;
+        if msvc {
+            let include_paths = cflags
+                .iter()
+                .filter_map(|s| s.strip_prefix("-I"))
+                .map(PathBuf::from)
+                .collect::<Vec<_>>();
+            fixup_symlinks(&include_paths, program)?;
+        }
 
-        let regex = regex::Regex::new(INCLUDE_REGEX).unwrap();
-        let filepaths = regex
-            .captures_iter(&program)
-            .map(|c| c[1].to_string())
-            .collect::<Vec<_>>();
-        log.push_str(&format!("regex captures (program): {:#?}\n", filepaths));
-        let joined_filepaths = filepaths
-            .iter()
-            .filter_map(|s| {
-                let path =
-                    std::path::Path::new(&include_paths.first().unwrap().replacen("-I", "", 1))
-                        .join(s);
-                Some(format!("{}", path.display()))
-            })
-            .collect::<Vec<_>>();
-        fixup_symlinks_inner(&joined_filepaths, &mut log)?;
+        let ldflags = get_env_flags(variables, "LDFLAGS");
 
-        let cppflags = get_env_flags(&variables, "CPPFLAGS");
-        let cxxflags = get_env_flags(&variables, "CXXFLAGS");
-        let ldflags = get_env_flags(&variables, "LDFLAGS");
+        // The flags written for GCC are translated for MSVC, while
+        // the `RAW_CFLAGS` are given as they are.
+        command.args(if msvc { flags::to_msvc(cflags) } else { cflags });
+        command.args(get_env_flags(variables, "RAW_CFLAGS"));
 
-        command.args(cflags);
+        command_add_output_file(&mut command, output_path, msvc, compiler.is_like_clang());
+        command.arg(input_path);
 
-        let link_path = ldflags
-            .get(0)
-            .expect("no link path for .dll")
-            .replace("-rpath,", "");
-        let mut dll_path = ldflags.get(1).expect("no .dll").clone();
-        if dll_path.ends_with(".dll") {
-            dll_path = format!("{}.lib", dll_path);
-        }
-        command_add_output_file(&mut command, &output_path, msvc, compiler.is_like_clang());
-        command.arg(input_path.clone());
-        command.arg("/link");
-        command.arg(dll_path);
-        command.arg(format!("/LIBPATH:{}", link_path));
+        let mut link_args = Vec::new();
 
-        command.envs(variables.clone());
+        if msvc {
+            let (link_path, dll_path) = msvc_link_library(&ldflags)?;
+            let mut libpath = OsString::from("/LIBPATH:");
+            libpath.push(&link_path);
+            link_args.push("/link".into());
+            link_args.push(dll_path);
+            link_args.push(libpath);
+        } else {
+            link_args.extend(ldflags.into_iter().map(OsString::from));
+            link_args.extend(language.link_flags(target).iter().map(OsString::from));
+        }
 
-        let mut files_to_remove = vec![input_path, output_path.clone()];
+        Ok(Compiler {
+            command,
+            link_args,
+            msvc,
+            family,
+            target: target.to_string(),
+        })
+    }
 
-        let mut intermediate_path = output_path.clone();
-        intermediate_path.set_extension("obj");
+    /// The directory and the import library of the `.dll` the snippets
+    /// are linked against with MSVC, given by the `LDFLAGS` variable,
+    /// e.g. `-rpath,C:\lib example.dll`.
+    fn msvc_link_library(ldflags: &[String]) -> Result<(PathBuf, OsString), String> {
+        let [link_path, dll_path, ..] = ldflags else {
+            return Err(format!(
+                "MSVC links the snippets against the `.dll` given by the `LDFLAGS` variable, \
+                 like `-rpath,<directory> <library>.dll`, but it has {} flag(s)",
+                ldflags.len()
+            ));
+        };
+        let mut dll_path = OsString::from(dll_path);
 
-        files_to_remove.push(intermediate_path);
+        if Path::new(&dll_path)
+            .extension()
+            .is_some_and(|extension| extension == "dll")
+        {
+            dll_path.push(".lib");
+        }
 
-        Ok(Assert::new(command, Some(files_to_remove)))
+        Ok((PathBuf::from(link_path.replace("-rpath,", "")), dll_path))
     }
 
+    /// A program without its `#inline_c_rs` directives, and its
+    /// variables.
+    type Environment<'p> = (Cow<'p, str>, HashMap<String, String>);
+
+    /// The variables of the `.env` file, the `INLINE_C_RS_*` environment
+    /// variables, and the `#inline_c_rs` directives of `program`, in
+    /// increasing precedence. The variables scoped to the test `target`,
+    /// like `INLINE_C_RS_<TARGET>__CFLAGS`, take precedence over the
+    /// unscoped ones, except the directives. The directives like
+    /// `#inline_c_rs unset CFLAGS LDFLAGS` remove the variables set by
+    /// the file or the environment, e.g. to compile a snippet without
+    /// the flags given to all of them.
     fn collect_environment_variables<'p>(
         program: &'p str,
-    ) -> (Cow<'p, str>, HashMap<String, String>) {
+        target: Option<&str>,
+    ) -> Result<Environment<'p>, Box<dyn Error>> {
         const ENV_VAR_PREFIX: &str = "INLINE_C_RS_";
 
         lazy_static! {
@@ -186,9 +4937,14 @@ pub mod run {
                 r#"#inline_c_rs (?P<variable_name>[^:]+):\s*"(?P<variable_value>[^"]+)"\r?\n"#
             )
             .unwrap();
+            static ref UNSET: Regex =
+                Regex::new(r#"#inline_c_rs unset[ \t]+(?P<variable_names>[\w \t,]+?)[ \t]*\r?\n"#)
+                    .unwrap();
         }
 
-        let mut variables = HashMap::new();
+        let mut variables = env_file::load(ENV_VAR_PREFIX)?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
 
         for (variable_name, variable_value) in env::vars().filter_map(|(mut name, value)| {
             if name.starts_with(ENV_VAR_PREFIX) {
@@ -200,28 +4956,61 @@ pub mod run {
             variables.insert(variable_name, variable_value);
         }
 
-        for captures in REGEX.captures_iter(program) {
+        if let Some(target) = target {
+            let scope = format!(
+                "{}__",
+                target
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() {
+                        c.to_ascii_uppercase()
+                    } else {
+                        '_'
+                    })
+                    .collect::<String>()
+            );
+            let scoped = variables
+                .iter()
+                .filter_map(|(name, value)| {
+                    name.strip_prefix(&scope)
+                        .map(|name| (name.to_string(), value.clone()))
+                })
+                .collect::<Vec<_>>();
+
+            variables.extend(scoped);
+        }
+
+        for captures in UNSET.captures_iter(program) {
+            for variable_name in captures["variable_names"]
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|variable_name| !variable_name.is_empty())
+            {
+                variables.remove(variable_name);
+            }
+        }
+
+        // They are removed first, since the other directives would take
+        // them for the beginning of their name.
+        let program = UNSET.replace_all(program, "");
+
+        for captures in REGEX.captures_iter(&program) {
             variables.insert(
                 captures["variable_name"].trim().to_string(),
                 captures["variable_value"].to_string(),
             );
         }
 
-        let program = REGEX.replace_all(program, "");
+        let program = match program {
+            Cow::Borrowed(program) => REGEX.replace_all(program, ""),
+            Cow::Owned(program) => Cow::Owned(REGEX.replace_all(&program, "").into_owned()),
+        };
 
-        (program, variables)
+        Ok((program, variables))
     }
 
     // This is copy-pasted and edited from `cc-rs`.
-    fn command_add_output_file(
-        command: &mut Command,
-        output_path: &PathBuf,
-        msvc: bool,
-        clang: bool,
-    ) {
+    fn command_add_output_file(command: &mut Command, output_path: &Path, msvc: bool, clang: bool) {
         if msvc && !clang {
-            let mut intermediate_path = output_path.clone();
-            intermediate_path.set_extension("obj");
+            let intermediate_path = output_path.with_extension("obj");
 
             let mut fo_arg = OsString::from("-Fo");
             fo_arg.push(intermediate_path);
@@ -235,6 +5024,215 @@ pub mod run {
         }
     }
 
+    /// Whether a boolean variable is set to a truthy value.
+    fn is_enabled(variables: &HashMap<String, String>, name: &str) -> bool {
+        variables
+            .get(name)
+            .map(|value| !matches!(value.trim(), "" | "0" | "false" | "no" | "off"))
+            .unwrap_or(false)
+    }
+
+    /// The variables of flags, whose `-I`, `-isystem` and `-L`
+    /// directories are checked and resolved.
+    const FLAGS_VARIABLES: [&str; 7] = [
+        "CPPFLAGS",
+        "CFLAGS",
+        "CXXFLAGS",
+        "OBJCFLAGS",
+        "CUDAFLAGS",
+        "ASFLAGS",
+        "LDFLAGS",
+    ];
+
+    /// The variables listing directories, separated like in `PATH`.
+    const PATH_LIST_VARIABLES: [&str; 3] =
+        ["SYSTEM_INCLUDES", "LINK_SEARCH_PATHS", "DLL_SEARCH_PATHS"];
+
+    /// The variables naming a file or a directory.
+    const PATH_VARIABLES: [&str; 12] = [
+        "ARTIFACT_DIR",
+        "CLANG_FORMAT_FILE",
+        "CWD",
+        "FUZZ_CORPUS",
+        "JUNIT",
+        "LSAN_SUPPRESSIONS",
+        "MANIFEST",
+        "RECORD",
+        "REPLAY",
+        "REPORT",
+        "RESOURCE",
+        "TSAN_SUPPRESSIONS",
+    ];
+
+    /// Resolve the relative paths of the variables against `base`, the
+    /// directory of the crate expanding the macro, rather than against
+    /// the current directory of the test: the directories of the flags,
+    /// the variables listing directories, and the variables naming a
+    /// file or a directory, e.g. a fixture given to `CWD`.
+    fn resolve_paths(variables: &mut HashMap<String, String>, base: &Path) {
+        let resolve = |path: &str| -> String {
+            if path.is_empty() || Path::new(path).is_absolute() {
+                path.to_string()
+            } else {
+                base.join(path).to_string_lossy().into_owned()
+            }
+        };
+
+        // The flags are separated by whitespaces, which the directory
+        // cannot contain then.
+        if !base.to_string_lossy().contains(char::is_whitespace) {
+            for variable in FLAGS_VARIABLES {
+                let Some(flags) = variables.get_mut(variable) else {
+                    continue;
+                };
+                let mut resolved = Vec::new();
+                let mut split = flags.split_ascii_whitespace();
+
+                while let Some(flag) = split.next() {
+                    match flag {
+                        "-I" | "-isystem" | "-L" => {
+                            resolved.push(flag.to_string());
+                            resolved.extend(split.next().map(resolve));
+                        }
+                        _ => match ["-isystem", "-I", "-L"]
+                            .into_iter()
+                            .find(|prefix| flag.len() > prefix.len() && flag.starts_with(prefix))
+                        {
+                            Some(prefix) => resolved.push(format!(
+                                "{}{}",
+                                prefix,
+                                resolve(&flag[prefix.len()..])
+                            )),
+                            None => resolved.push(flag.to_string()),
+                        },
+                    }
+                }
+
+                *flags = resolved.join(" ");
+            }
+        }
+
+        for variable in PATH_LIST_VARIABLES {
+            if let Some(paths) = variables.get_mut(variable) {
+                let resolved = env::split_paths(paths)
+                    .map(|path| base.join(path))
+                    .collect::<Vec<_>>();
+
+                if let Ok(resolved) = env::join_paths(resolved) {
+                    *paths = resolved.to_string_lossy().into_owned();
+                }
+            }
+        }
+
+        for variable in PATH_VARIABLES {
+            if let Some(path) = variables.get_mut(variable) {
+                *path = resolve(path);
+            }
+        }
+    }
+
+    /// The directories given to the compilers by the variables: the
+    /// `-I`, `-isystem` and `-L` flags, and the directories of the
+    /// `SYSTEM_INCLUDES`, `LINK_SEARCH_PATHS` and `DLL_SEARCH_PATHS`
+    /// variables, with their kind, `include` or `library`, and the
+    /// variable giving them.
+    fn flag_directories(
+        variables: &HashMap<String, String>,
+    ) -> Vec<(&'static str, &'static str, PathBuf)> {
+        let mut directories = Vec::new();
+
+        for variable in FLAGS_VARIABLES {
+            let mut flags = get_env_flags(variables, variable).into_iter();
+
+            while let Some(flag) = flags.next() {
+                let kind = if flag.starts_with("-L") {
+                    "library"
+                } else {
+                    "include"
+                };
+                let directory = match flag.as_str() {
+                    "-I" | "-isystem" | "-L" => flags.next(),
+                    _ => ["-isystem", "-I", "-L"]
+                        .iter()
+                        .find_map(|prefix| flag.strip_prefix(prefix))
+                        .filter(|directory| !directory.is_empty())
+                        .map(str::to_string),
+                };
+
+                if let Some(directory) = directory {
+                    directories.push((kind, variable, PathBuf::from(directory)));
+                }
+            }
+        }
+
+        for variable in PATH_LIST_VARIABLES {
+            let kind = if variable == "SYSTEM_INCLUDES" {
+                "include"
+            } else {
+                "library"
+            };
+
+            if let Some(paths) = variables.get(variable) {
+                directories.extend(env::split_paths(paths).map(|path| (kind, variable, path)));
+            }
+        }
+
+        directories
+    }
+
+    /// Check that the directories given by the variables exist, and
+    /// name the missing ones with the variable giving them otherwise.
+    fn check_directories(variables: &HashMap<String, String>) -> Result<(), String> {
+        let missing = flag_directories(variables)
+            .into_iter()
+            .filter(|(_, _, directory)| !directory.is_dir())
+            .map(|(kind, variable, directory)| {
+                format!(
+                    "the {} directory `{}`, given by {}, does not exist",
+                    kind,
+                    directory.display(),
+                    origin(variables, variable)
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{}; set the `CHECK_PATHS` variable to `0` to skip this check",
+                missing.join("; ")
+            ))
+        }
+    }
+
+    /// Where the variable `name` comes from: an environment variable,
+    /// scoped to a test target or not, or a directive or the `.env`
+    /// file otherwise.
+    fn origin(variables: &HashMap<String, String>, name: &str) -> String {
+        let Some(value) = variables.get(name) else {
+            return format!("the `{}` environment variable", name);
+        };
+        let scoped = format!("__{}", name);
+
+        env::vars()
+            .find(|(key, set)| {
+                set == value
+                    && key
+                        .strip_prefix("INLINE_C_RS_")
+                        .is_some_and(|key| key == name || key.ends_with(&scoped))
+            })
+            .map_or_else(
+                || {
+                    format!(
+                        "the `{}` variable, of an `#inline_c_rs` directive or of the `.env` file",
+                        name
+                    )
+                },
+                |(key, _)| format!("the `{}` environment variable", key),
+            )
+    }
+
     fn get_env_flags(variables: &HashMap<String, String>, env_name: &str) -> Vec<String> {
         variables
             .get(env_name)
@@ -246,64 +5244,153 @@ pub mod run {
             .collect()
     }
 
-    fn fixup_symlinks(include_paths: &[String], log: &mut String) -> Result<(), Box<dyn Error>> {
-        log.push_str(&format!("include paths: {include_paths:?}"));
+    /// The path with the `\\?\` prefix on Windows, so that the paths
+    /// longer than `MAX_PATH` can be opened. It is returned as it is
+    /// if it is relative, already prefixed, or if it contains `..`.
+    fn extended_length(path: &Path) -> Cow<'_, Path> {
+        if !cfg!(windows) || !path.is_absolute() {
+            return Cow::Borrowed(path);
+        }
+
+        let mut components = path.components();
+        let mut extended = OsString::from(r"\\?\");
+
+        match components.next() {
+            Some(Component::Prefix(prefix)) => match prefix.kind() {
+                Prefix::Disk(_) => extended.push(prefix.as_os_str()),
+                // `\\server\share` becomes `\\?\UNC\server\share`.
+                Prefix::UNC(server, share) => {
+                    extended.push(r"UNC\");
+                    extended.push(server);
+                    extended.push(r"\");
+                    extended.push(share);
+                }
+                _ => return Cow::Borrowed(path),
+            },
+            _ => return Cow::Borrowed(path),
+        }
+
+        // Extended-length paths are not normalized by Windows, so they
+        // can only contain backslashes and no `..`.
+        for component in components {
+            match component {
+                Component::Normal(name) => {
+                    extended.push(r"\");
+                    extended.push(name);
+                }
+                Component::RootDir | Component::CurDir => {}
+                Component::ParentDir | Component::Prefix(_) => return Cow::Borrowed(path),
+            }
+        }
+
+        Cow::Owned(PathBuf::from(extended))
+    }
+
+    /// Replace the headers checked out as symlinks, i.e. as files
+    /// containing the path of their target, by their target: the
+    /// headers of the include directories, and the ones included by
+    /// the program.
+    fn fixup_symlinks(include_paths: &[PathBuf], program: &str) -> Result<(), Box<dyn Error>> {
+        let mut walker = includes::Walker::new(include_paths, fixup_symlink);
+
         for i in include_paths {
-            let i = i.replacen("-I", "", 1);
-            let mut paths_headers = Vec::new();
-            for entry in std::fs::read_dir(&i)? {
-                let entry = entry?;
-                let path = entry.path();
-                let path_display = format!("{}", path.display());
-                if path_display.ends_with("h") {
-                    paths_headers.push(path_display);
+            for entry in fs::read_dir(extended_length(i))? {
+                let path = entry?.path();
+                if path.extension().is_some_and(|extension| extension == "h") {
+                    walker.header(&path)?;
                 }
             }
-            fixup_symlinks_inner(&paths_headers, log)?;
         }
 
+        walker.source(program)?;
+
         Ok(())
     }
 
-    fn fixup_symlinks_inner(
-        include_paths: &[String],
-        log: &mut String,
-    ) -> Result<(), Box<dyn Error>> {
-        log.push_str(&format!("fixup symlinks: {include_paths:#?}"));
-        let regex = regex::Regex::new(INCLUDE_REGEX).unwrap();
-        for path in include_paths.iter() {
-            let file = std::fs::read_to_string(&path)?;
-            let lines_3 = file.lines().take(3).collect::<Vec<_>>();
-            log.push_str(&format!("first 3 lines of {path:?}: {:#?}\n", lines_3));
+    fn fixup_symlink(path: &Path) -> io::Result<()> {
+        let file = fs::read_to_string(extended_length(path))?;
 
-            let parent = std::path::Path::new(&path).parent().unwrap();
-            if let Ok(symlink) = std::fs::read_to_string(parent.clone().join(&file)) {
-                log.push_str(&format!("symlinking {path:?}\n"));
-                std::fs::write(&path, symlink)?;
+        if let Some(parent) = path.parent() {
+            if let Ok(symlink) = fs::read_to_string(extended_length(&parent.join(&file))) {
+                fs::write(extended_length(path), symlink)?;
             }
-
-            // follow #include directives and recurse
-            let filepaths = regex
-                .captures_iter(&file)
-                .map(|c| c[1].to_string())
-                .collect::<Vec<_>>();
-            log.push_str(&format!("regex captures: ({path:?}): {:#?}\n", filepaths));
-            let joined_filepaths = filepaths
-                .iter()
-                .filter_map(|s| {
-                    let path = parent.clone().join(s);
-                    Some(format!("{}", path.display()))
-                })
-                .collect::<Vec<_>>();
-            fixup_symlinks_inner(&joined_filepaths, log)?;
         }
+
         Ok(())
     }
 }
 
-pub use crate::run::{run, Language};
-pub use assert::Assert;
-pub use wasmer_inline_c_macro::{assert_c, assert_cxx};
-pub mod predicates {
-    pub use predicates::prelude::*;
+mod android;
+mod backend;
+pub mod batch;
+mod build_dir;
+pub mod cache;
+pub mod diagnostic;
+pub mod diff;
+pub mod doc;
+mod env_file;
+mod flags;
+mod format;
+pub mod framework;
+pub mod fuzz;
+pub mod includes;
+mod jobs;
+mod leak;
+pub mod network;
+pub mod output;
+mod pe;
+pub mod predicates;
+pub mod preflight;
+#[cfg(feature = "proptest")]
+pub mod property;
+mod race;
+pub mod replay;
+pub mod report;
+pub mod requirement;
+mod resource;
+mod retry;
+pub mod runner;
+pub mod sandbox;
+pub mod scheduling;
+pub mod shared;
+pub mod stamp;
+mod template;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod workspace;
+
+pub use crate::run::{preflight, run, Language};
+pub use assert::{Assert, Compiled, Run, RunResult};
+pub use wasmer_inline_c_macro::{assert_asm, assert_c, assert_c_str, assert_cxx, assert_cxx_str};
+
+/// The path of `path`, relative to the directory of the crate invoking
+/// the macro, its `CARGO_MANIFEST_DIR`, e.g.
+/// `manifest_path!("tests/fixtures/input.txt")`, whatever the current
+/// directory of the test. The relative paths of the variables of the
+/// snippets, e.g. `#inline_c_rs CWD: "tests/fixtures"`, are resolved
+/// the same way.
+#[macro_export]
+macro_rules! manifest_path {
+    ($path:expr) => {
+        ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path)
+    };
+}
+
+/// Compile a C program once for all the tests of the test binary, and
+/// return a [`shared::Shared`] handle running it, e.g.
+/// `once_compiled!("helper", { … }).run().arg("--list").success()`.
+/// The program is written like with [`assert_c!`], and named by the
+/// first argument, which must be unique in the test binary. A skipped
+/// program, e.g. by its `SKIP_IF_ENV` variable, skips the test using
+/// it.
+#[macro_export]
+macro_rules! once_compiled {
+    ($name:expr, { $($program:tt)* }) => {
+        // The guards of `assert_c!` return from the closure, and the
+        // test is skipped here.
+        match $crate::shared::once_compiled($name, || Some($crate::assert_c! { $($program)* })) {
+            Some(shared) => shared,
+            None => return $crate::run::Skip::skip(),
+        }
+    };
 }
@@ -0,0 +1,149 @@
+//! The access of the programs to the network, see `Assert::network`.
+//!
+//! The network is denied to a program run natively, with Wine or with
+//! QEMU by running it in a new network namespace with `unshare --net
+//! --map-root-user` on Linux, or in a sandbox denying the network with
+//! `sandbox-exec` on macOS, and to a program run in a container with
+//! `--network none`. WebAssembly modules have no access to the network
+//! anyway. It cannot be denied on the other hosts and runners.
+
+use crate::{assert::rewrite_args, runner::Runner};
+use std::{ffi::OsStr, io, net::UdpSocket, process::Command, str::FromStr};
+
+/// The profile of `sandbox-exec` denying the network.
+const SANDBOX_PROFILE: &str = "(version 1) (allow default) (deny network*)";
+
+/// The access of a program to the network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Network {
+    /// The program has the access of the tests.
+    #[default]
+    Inherit,
+
+    /// The program has no access to the network, not even to the
+    /// loopback interface, e.g. to check that a code path never
+    /// touches it.
+    Denied,
+
+    /// The program needs the network: the assertion fails early if the
+    /// host cannot reach it.
+    Required,
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    /// Parse `inherit`, `deny` or `require`.
+    fn from_str(network: &str) -> Result<Self, Self::Err> {
+        match network.trim().to_ascii_lowercase().as_str() {
+            "inherit" | "allow" => Ok(Self::Inherit),
+            "deny" | "denied" | "none" | "off" => Ok(Self::Denied),
+            "require" | "required" => Ok(Self::Required),
+            network => Err(format!("unknown network access `{}`", network)),
+        }
+    }
+}
+
+/// Check that `network` can be enforced for the programs run by
+/// `runner`.
+pub(crate) fn check(network: Network, runner: &Runner) -> io::Result<()> {
+    match network {
+        Network::Inherit => Ok(()),
+
+        Network::Denied if can_deny(runner) => Ok(()),
+        Network::Denied => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "the network cannot be denied to the programs run by {:?} on this host",
+                runner
+            ),
+        )),
+
+        Network::Required if matches!(runner, Runner::Wasmer { .. }) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the network is required, but WebAssembly modules have no access to it",
+        )),
+        Network::Required if is_reachable() => Ok(()),
+        Network::Required => Err(io::Error::new(
+            io::ErrorKind::NotConnected,
+            "the network is required, but the host cannot reach it",
+        )),
+    }
+}
+
+/// Change `command`, run by `runner`, so that the program has no access
+/// to the network, if [`check`] allows it.
+pub(crate) fn deny(command: &mut Command, runner: &Runner) {
+    match runner {
+        Runner::Docker { .. } => {
+            let mut inserted = false;
+
+            rewrite_args(command, |arg| {
+                if arg == "run" && !inserted {
+                    inserted = true;
+
+                    vec![arg.to_os_string(), "--network".into(), "none".into()]
+                } else {
+                    vec![arg.to_os_string()]
+                }
+            });
+        }
+
+        Runner::Native | Runner::Wine { .. } | Runner::Qemu { .. } => {
+            if cfg!(target_os = "linux") {
+                wrap(command, "unshare", ["--net", "--map-root-user", "--"]);
+            } else if cfg!(target_os = "macos") {
+                wrap(command, "sandbox-exec", ["-p", SANDBOX_PROFILE]);
+            }
+        }
+
+        _ => (),
+    }
+}
+
+fn can_deny(runner: &Runner) -> bool {
+    match runner {
+        Runner::Wasmer { .. } | Runner::Docker { .. } => true,
+        Runner::Native | Runner::Wine { .. } | Runner::Qemu { .. } => {
+            cfg!(any(target_os = "linux", target_os = "macos"))
+        }
+        Runner::Ssh { .. } | Runner::Adb { .. } | Runner::Simctl { .. } => false,
+    }
+}
+
+/// Whether the host has a route to the Internet. Connecting a UDP
+/// socket sends nothing, but fails without a route.
+fn is_reachable() -> bool {
+    let Ok(socket) = UdpSocket::bind("0.0.0.0:0") else {
+        return false;
+    };
+
+    socket.connect("192.0.2.1:9").is_ok()
+}
+
+/// Run `command` through `program`, given `args` and then the command
+/// line of `command`.
+pub(crate) fn wrap<I, S>(command: &mut Command, program: &str, args: I)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut wrapper = Command::new(program);
+    wrapper
+        .args(args)
+        .arg(command.get_program())
+        .args(command.get_args());
+
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => wrapper.env(key, value),
+            None => wrapper.env_remove(key),
+        };
+    }
+
+    if let Some(directory) = command.get_current_dir() {
+        wrapper.current_dir(directory);
+    }
+
+    *command = wrapper;
+}
@@ -0,0 +1,401 @@
+//! Processing of the captured output of a program, before it is
+//! asserted.
+
+use std::{
+    io::{self, prelude::*, BufReader},
+    process::{Command, Output, Stdio},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// The stream a [`Chunk`] has been written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// A line written by the program, recorded when the output is captured
+/// in the interleaved mode, see `Assert::interleaved`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    /// The stream the line has been written to.
+    pub stream: Stream,
+
+    /// The time the line has been received at, since the program has
+    /// started.
+    pub elapsed: Duration,
+
+    /// The line, with its line feed if any.
+    pub bytes: Vec<u8>,
+}
+
+/// Run `command` and capture its output, like [`Command::output`],
+/// while writing `stdin` to its standard input. With `tee`, the output
+/// is also streamed line by line to the standard error of the current
+/// process. With `interleave`, the lines of both streams are also
+/// returned in the order they have been received.
+pub(crate) fn capture(
+    command: &mut Command,
+    stdin: Option<&[u8]>,
+    tee: bool,
+    interleave: bool,
+) -> io::Result<(Output, Vec<Chunk>)> {
+    let start = Instant::now();
+    let mut child = command
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // Both streams push their lines into the same list, so that its
+    // order is the order of arrival.
+    let chunks = interleave.then(|| Arc::new(Mutex::new(Vec::new())));
+
+    fn stream<R>(
+        reader: R,
+        origin: Stream,
+        prefix: Option<&'static str>,
+        chunks: Option<(Arc<Mutex<Vec<Chunk>>>, Instant)>,
+    ) -> thread::JoinHandle<io::Result<Vec<u8>>>
+    where
+        R: Read + Send + 'static,
+    {
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut captured = Vec::new();
+            let mut line = Vec::new();
+
+            while reader.read_until(b'\n', &mut line)? > 0 {
+                if let Some((chunks, start)) = &chunks {
+                    let chunk = Chunk {
+                        stream: origin,
+                        elapsed: start.elapsed(),
+                        bytes: line.clone(),
+                    };
+
+                    chunks
+                        .lock()
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .push(chunk);
+                }
+
+                if let Some(prefix) = prefix {
+                    // `eprint!` is captured by the test harness, like
+                    // any other output of the test.
+                    eprint!("{}{}", prefix, String::from_utf8_lossy(&line));
+
+                    if !line.ends_with(b"\n") {
+                        eprintln!();
+                    }
+                }
+
+                captured.append(&mut line);
+            }
+
+            Ok(captured)
+        })
+    }
+
+    let stdout = stream(
+        child.stdout.take().unwrap(),
+        Stream::Stdout,
+        tee.then_some("[stdout] "),
+        chunks.clone().map(|chunks| (chunks, start)),
+    );
+    let stderr = stream(
+        child.stderr.take().unwrap(),
+        Stream::Stderr,
+        tee.then_some("[stderr] "),
+        chunks.clone().map(|chunks| (chunks, start)),
+    );
+
+    if let (Some(mut child_stdin), Some(stdin)) = (child.stdin.take(), stdin) {
+        // The program may exit without reading its whole input.
+        match child_stdin.write_all(stdin) {
+            Err(error) if error.kind() != io::ErrorKind::BrokenPipe => return Err(error),
+            _ => (),
+        }
+    }
+
+    let status = child.wait()?;
+
+    let join = |handle: thread::JoinHandle<io::Result<Vec<u8>>>| {
+        handle
+            .join()
+            .unwrap_or_else(|_| Err(io::Error::other("failed to capture the output")))
+    };
+
+    let output = Output {
+        status,
+        stdout: join(stdout)?,
+        stderr: join(stderr)?,
+    };
+    let chunks = chunks
+        .map(|chunks| {
+            let mut chunks = chunks
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            std::mem::take(&mut *chunks)
+        })
+        .unwrap_or_default();
+
+    Ok((output, chunks))
+}
+
+/// The encoding of the output of a program, decoded to UTF-8 before it
+/// is asserted, see `Assert::output_encoding`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Detect the encoding: UTF-16 from its byte order mark, or from
+    /// the null bytes of its ASCII characters, UTF-8, or else the OEM
+    /// code page of the console on Windows.
+    Auto,
+
+    /// UTF-16, little-endian, as written by `WriteConsoleW` or by
+    /// `_setmode(_fileno(stdout), _O_U16TEXT)` when redirected.
+    Utf16Le,
+
+    /// UTF-16, big-endian.
+    Utf16Be,
+
+    /// A Windows code page, e.g. `437` or `1252`. Only the UTF-8 and
+    /// Latin-1 code pages, `65001` and `28591`, are decoded on the
+    /// other hosts.
+    CodePage(u32),
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    /// Parse `auto`, `utf-16le`, `utf-16be`, or a code page, e.g. `437`
+    /// or `cp437`.
+    fn from_str(encoding: &str) -> Result<Self, Self::Err> {
+        let encoding = encoding.trim().to_ascii_lowercase();
+
+        match encoding.as_str() {
+            "auto" => Ok(Self::Auto),
+            "utf-16le" | "utf16le" | "utf-16" | "utf16" => Ok(Self::Utf16Le),
+            "utf-16be" | "utf16be" => Ok(Self::Utf16Be),
+            "utf-8" | "utf8" => Ok(Self::CodePage(65001)),
+            code_page => code_page
+                .strip_prefix("cp")
+                .unwrap_or(code_page)
+                .parse()
+                .map(Self::CodePage)
+                .map_err(|_| format!("unknown output encoding `{}`", encoding)),
+        }
+    }
+}
+
+/// Decode `bytes`, written in `encoding`, to UTF-8. The bytes that
+/// cannot be decoded are replaced by `U+FFFD`, and the bytes are left
+/// unchanged if the code page is not supported.
+pub fn decode(bytes: &[u8], encoding: Encoding) -> Vec<u8> {
+    let decoded = match encoding {
+        Encoding::Auto => {
+            if let Some(bytes) = bytes.strip_prefix(&[0xff, 0xfe]) {
+                utf16(bytes, u16::from_le_bytes)
+            } else if let Some(bytes) = bytes.strip_prefix(&[0xfe, 0xff]) {
+                utf16(bytes, u16::from_be_bytes)
+            } else if let Some(bytes) = bytes.strip_prefix(&[0xef, 0xbb, 0xbf]) {
+                String::from_utf8_lossy(bytes).into_owned()
+            } else if looks_like_utf16le(bytes) {
+                utf16(bytes, u16::from_le_bytes)
+            } else if std::str::from_utf8(bytes).is_ok() {
+                return bytes.to_vec();
+            } else {
+                match code_page(bytes, oem_code_page()) {
+                    Some(decoded) => decoded,
+                    None => return bytes.to_vec(),
+                }
+            }
+        }
+        Encoding::Utf16Le => utf16(
+            bytes.strip_prefix(&[0xff, 0xfe]).unwrap_or(bytes),
+            u16::from_le_bytes,
+        ),
+        Encoding::Utf16Be => utf16(
+            bytes.strip_prefix(&[0xfe, 0xff]).unwrap_or(bytes),
+            u16::from_be_bytes,
+        ),
+        Encoding::CodePage(code_page_number) => match code_page(bytes, code_page_number) {
+            Some(decoded) => decoded,
+            None => return bytes.to_vec(),
+        },
+    };
+
+    decoded.into_bytes()
+}
+
+fn utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> String {
+    let units = bytes
+        .chunks(2)
+        .map(|unit| from_bytes([unit[0], *unit.get(1).unwrap_or(&0)]))
+        .collect::<Vec<_>>();
+
+    String::from_utf16_lossy(&units)
+}
+
+/// Whether `bytes` is mostly ASCII text in UTF-16LE, i.e. whether most
+/// of its odd bytes are null, and none of its even ones.
+fn looks_like_utf16le(bytes: &[u8]) -> bool {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return false;
+    }
+
+    let (even_nulls, odd_nulls) = bytes
+        .chunks(2)
+        .fold((0, 0), |(even_nulls, odd_nulls), unit| {
+            (
+                even_nulls + usize::from(unit[0] == 0),
+                odd_nulls + usize::from(unit[1] == 0),
+            )
+        });
+
+    even_nulls == 0 && odd_nulls * 2 >= bytes.len() / 2
+}
+
+#[cfg(windows)]
+fn oem_code_page() -> u32 {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetOEMCP() -> u32;
+    }
+
+    // SAFETY: `GetOEMCP` has no precondition.
+    unsafe { GetOEMCP() }
+}
+
+#[cfg(not(windows))]
+fn oem_code_page() -> u32 {
+    // The consoles of the other hosts are UTF-8.
+    65001
+}
+
+#[cfg(windows)]
+fn code_page(bytes: &[u8], code_page: u32) -> Option<String> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn MultiByteToWideChar(
+            code_page: u32,
+            flags: u32,
+            multi_byte: *const u8,
+            multi_byte_length: i32,
+            wide: *mut u16,
+            wide_length: i32,
+        ) -> i32;
+    }
+
+    if bytes.is_empty() {
+        return Some(String::new());
+    }
+
+    let length = i32::try_from(bytes.len()).ok()?;
+
+    // SAFETY: the input is `length` bytes long, and the first call only
+    // computes the length of the output.
+    let wide_length = unsafe {
+        MultiByteToWideChar(
+            code_page,
+            0,
+            bytes.as_ptr(),
+            length,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+
+    if wide_length <= 0 {
+        return None;
+    }
+
+    let mut wide = vec![0u16; wide_length as usize];
+
+    // SAFETY: the output has room for the `wide_length` units computed
+    // by the first call.
+    let wide_length = unsafe {
+        MultiByteToWideChar(
+            code_page,
+            0,
+            bytes.as_ptr(),
+            length,
+            wide.as_mut_ptr(),
+            wide_length,
+        )
+    };
+
+    if wide_length <= 0 {
+        return None;
+    }
+
+    wide.truncate(wide_length as usize);
+
+    Some(String::from_utf16_lossy(&wide))
+}
+
+#[cfg(not(windows))]
+fn code_page(bytes: &[u8], code_page: u32) -> Option<String> {
+    match code_page {
+        65001 => Some(String::from_utf8_lossy(bytes).into_owned()),
+        28591 => Some(bytes.iter().map(|byte| char::from(*byte)).collect()),
+        _ => None,
+    }
+}
+
+/// Remove the ANSI escape sequences (colors, cursor movements, window
+/// titles…) from `bytes`.
+pub fn strip_ansi(bytes: &[u8]) -> Vec<u8> {
+    const ESC: u8 = 0x1b;
+    const BEL: u8 = 0x07;
+
+    let mut output = Vec::with_capacity(bytes.len());
+    let mut iterator = bytes.iter().copied().peekable();
+
+    while let Some(byte) = iterator.next() {
+        if byte != ESC {
+            output.push(byte);
+
+            continue;
+        }
+
+        match iterator.next() {
+            // Control Sequence Introducer: parameters and intermediate
+            // bytes, up to a final byte in `@`–`~`.
+            Some(b'[') => {
+                for byte in iterator.by_ref() {
+                    if (0x40..=0x7e).contains(&byte) {
+                        break;
+                    }
+                }
+            }
+
+            // Operating System Command: up to `BEL` or `ESC \`.
+            Some(b']') => {
+                while let Some(byte) = iterator.next() {
+                    if byte == BEL {
+                        break;
+                    }
+
+                    if byte == ESC && iterator.peek() == Some(&b'\\') {
+                        iterator.next();
+
+                        break;
+                    }
+                }
+            }
+
+            // Any other escape sequence is 2 bytes long.
+            _ => (),
+        }
+    }
+
+    output
+}
@@ -0,0 +1,153 @@
+//! The DLLs imported by Windows executables, read from their import
+//! table, so that the ones a program depends on can be staged next to
+//! it, see `Assert::stage_dlls`.
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The index of the import table in the data directories.
+const IMPORT_DIRECTORY: usize = 1;
+
+/// The index of the delay-load import table in the data directories.
+const DELAY_IMPORT_DIRECTORY: usize = 13;
+
+/// Copy the DLLs imported by `executable`, and the ones they import in
+/// turn, from the first of `directories` containing them to the
+/// directory of `executable`. The DLLs found in none of them, like the
+/// ones of the system, are left out. The copied files are returned.
+pub(crate) fn stage_dependencies(
+    executable: &Path,
+    directories: &[PathBuf],
+) -> io::Result<Vec<PathBuf>> {
+    let Some(destination) = executable.parent() else {
+        return Ok(Vec::new());
+    };
+    let mut staged = Vec::new();
+    let mut seen = HashSet::new();
+    let mut pending = vec![executable.to_path_buf()];
+
+    while let Some(module) = pending.pop() {
+        for name in imports(&fs::read(&module)?) {
+            if !seen.insert(name.to_ascii_lowercase()) {
+                continue;
+            }
+
+            let Some(source) = directories
+                .iter()
+                .map(|directory| directory.join(&name))
+                .find(|path| path.is_file())
+            else {
+                continue;
+            };
+            let target = destination.join(&name);
+
+            // A DLL built next to the executable is not replaced.
+            if !target.exists() {
+                fs::copy(&source, &target)?;
+                staged.push(target);
+            }
+
+            pending.push(source);
+        }
+    }
+
+    Ok(staged)
+}
+
+/// The names of the DLLs imported by the PE image `bytes`, including
+/// the delay-loaded ones. A malformed image imports nothing.
+fn imports(bytes: &[u8]) -> Vec<String> {
+    parse_imports(bytes).unwrap_or_default()
+}
+
+fn parse_imports(bytes: &[u8]) -> Option<Vec<String>> {
+    if bytes.get(..2)? != b"MZ" {
+        return None;
+    }
+
+    let pe = u32_at(bytes, 0x3c)? as usize;
+
+    if bytes.get(pe..pe + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    let coff = pe + 4;
+    let section_count = u16_at(bytes, coff + 2)? as usize;
+    let optional_header_size = u16_at(bytes, coff + 16)? as usize;
+    let optional_header = coff + 20;
+
+    // The data directories follow the fields of PE32, or of the wider
+    // PE32+.
+    let data_directories = match u16_at(bytes, optional_header)? {
+        0x10b => optional_header + 96,
+        0x20b => optional_header + 112,
+        _ => return None,
+    };
+    let sections = optional_header + optional_header_size;
+
+    let offset = |rva: u32| -> Option<usize> {
+        (0..section_count).find_map(|index| {
+            let section = sections + index * 40;
+            let virtual_size = u32_at(bytes, section + 8)?;
+            let virtual_address = u32_at(bytes, section + 12)?;
+            let raw_size = u32_at(bytes, section + 16)?;
+            let raw_offset = u32_at(bytes, section + 20)?;
+
+            (rva >= virtual_address && rva - virtual_address < virtual_size.max(raw_size))
+                .then(|| (rva - virtual_address + raw_offset) as usize)
+        })
+    };
+    let name = |rva: u32| -> Option<String> {
+        let start = offset(rva)?;
+        let length = bytes.get(start..)?.iter().position(|byte| *byte == 0)?;
+
+        Some(String::from_utf8_lossy(&bytes[start..start + length]).into_owned())
+    };
+
+    let mut names = Vec::new();
+
+    // The descriptors of the imports are 20 bytes long, with the name
+    // at 12, and the ones of the delay-loaded imports 32 bytes long,
+    // with the name at 4. Both lists end with a null descriptor.
+    for (directory, descriptor_size, name_offset) in
+        [(IMPORT_DIRECTORY, 20, 12), (DELAY_IMPORT_DIRECTORY, 32, 4)]
+    {
+        let Some(rva) = u32_at(bytes, data_directories + directory * 8) else {
+            continue;
+        };
+
+        if rva == 0 {
+            continue;
+        }
+
+        let Some(mut descriptor) = offset(rva) else {
+            continue;
+        };
+
+        while let Some(name_rva) = u32_at(bytes, descriptor + name_offset) {
+            if name_rva == 0 {
+                break;
+            }
+
+            names.extend(name(name_rva));
+            descriptor += descriptor_size;
+        }
+    }
+
+    Some(names)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        bytes.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        bytes.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
@@ -0,0 +1,512 @@
+//! Predicates to assert the output of a program, in addition to the
+//! ones from the `predicates` crate.
+
+pub use ::predicates::prelude::*;
+
+use crate::diagnostic::{Diagnostic, Severity};
+use ::predicates::reflection;
+use lazy_static::lazy_static;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+lazy_static! {
+    static ref NUMBER: Regex = Regex::new(r"[-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?").unwrap();
+}
+
+/// Predicate that compares two strings where numbers are allowed to
+/// differ by a tolerance.
+///
+/// This is created by [`numbers_close`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumbersClosePredicate {
+    expected: String,
+    tolerance: f64,
+}
+
+impl NumbersClosePredicate {
+    /// Return the first number that is too far from the expected one,
+    /// or `None` if the text and the numbers all match.
+    fn mismatch(&self, variable: &str) -> Option<String> {
+        let (expected_text, expected_numbers) = split_numbers(&self.expected);
+        let (variable_text, variable_numbers) = split_numbers(variable);
+
+        if expected_text != variable_text {
+            return Some("the text around the numbers differs".to_string());
+        }
+
+        if expected_numbers.len() != variable_numbers.len() {
+            return Some(format!(
+                "expected {} numbers, found {}",
+                expected_numbers.len(),
+                variable_numbers.len()
+            ));
+        }
+
+        expected_numbers
+            .iter()
+            .zip(variable_numbers.iter())
+            .find(|(expected, variable)| (*expected - *variable).abs() > self.tolerance)
+            .map(|(expected, variable)| {
+                format!(
+                    "{} is not within {} of {}",
+                    variable, self.tolerance, expected
+                )
+            })
+    }
+}
+
+impl Predicate<str> for NumbersClosePredicate {
+    fn eval(&self, variable: &str) -> bool {
+        self.mismatch(variable).is_none()
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &str) -> Option<reflection::Case<'a>> {
+        let mismatch = self.mismatch(variable);
+
+        if mismatch.is_none() == expected {
+            Some(
+                reflection::Case::new(Some(self), expected).add_product(reflection::Product::new(
+                    "mismatch",
+                    mismatch.unwrap_or_else(|| "none".to_string()),
+                )),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl reflection::PredicateReflection for NumbersClosePredicate {
+    fn parameters<'a>(&'a self) -> Box<dyn Iterator<Item = reflection::Parameter<'a>> + 'a> {
+        Box::new(
+            vec![
+                reflection::Parameter::new("expected", &self.expected),
+                reflection::Parameter::new("tolerance", &self.tolerance),
+            ]
+            .into_iter(),
+        )
+    }
+}
+
+impl fmt::Display for NumbersClosePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var ≈ {:?} (± {})", self.expected, self.tolerance)
+    }
+}
+
+/// Create a predicate that compares the output with `expected`, where
+/// every number is allowed to differ by `tolerance`, e.g.
+/// `numbers_close("result: 3.1416", 1e-4)` accepts `"result: 3.14159"`.
+/// The text around the numbers must be identical.
+///
+/// It is useful because `printf` formats floating-point numbers
+/// differently depending on the C runtime.
+pub fn numbers_close<S>(expected: S, tolerance: f64) -> NumbersClosePredicate
+where
+    S: Into<String>,
+{
+    NumbersClosePredicate {
+        expected: expected.into(),
+        tolerance,
+    }
+}
+
+/// Predicate that compares two strings line by line, regardless of the
+/// order of the lines.
+///
+/// This is created by [`unordered_lines_eq`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnorderedLinesPredicate {
+    expected: String,
+}
+
+impl UnorderedLinesPredicate {
+    /// Return the lines that are missing from `variable`, and the lines
+    /// that are unexpected in `variable`.
+    fn differences<'a>(&'a self, variable: &'a str) -> (Vec<&'a str>, Vec<&'a str>) {
+        let mut missing = self.expected.lines().collect::<Vec<_>>();
+        let mut unexpected = Vec::new();
+
+        for line in variable.lines() {
+            match missing.iter().position(|expected| *expected == line) {
+                Some(position) => {
+                    missing.swap_remove(position);
+                }
+                None => unexpected.push(line),
+            }
+        }
+
+        (missing, unexpected)
+    }
+}
+
+impl Predicate<str> for UnorderedLinesPredicate {
+    fn eval(&self, variable: &str) -> bool {
+        let (missing, unexpected) = self.differences(variable);
+
+        missing.is_empty() && unexpected.is_empty()
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &str) -> Option<reflection::Case<'a>> {
+        let (missing, unexpected) = self.differences(variable);
+        let result = missing.is_empty() && unexpected.is_empty();
+
+        if result == expected {
+            Some(
+                reflection::Case::new(Some(self), result)
+                    .add_product(reflection::Product::new(
+                        "missing lines",
+                        missing.join("\n"),
+                    ))
+                    .add_product(reflection::Product::new(
+                        "unexpected lines",
+                        unexpected.join("\n"),
+                    )),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl reflection::PredicateReflection for UnorderedLinesPredicate {
+    fn parameters<'a>(&'a self) -> Box<dyn Iterator<Item = reflection::Parameter<'a>> + 'a> {
+        Box::new(vec![reflection::Parameter::new("expected", &self.expected)].into_iter())
+    }
+}
+
+impl fmt::Display for UnorderedLinesPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "var has the same lines as {:?}, in any order",
+            self.expected
+        )
+    }
+}
+
+/// Create a predicate that compares the output with `expected` line by
+/// line, regardless of the order of the lines, e.g. for programs that
+/// iterate over hash-ordered containers.
+pub fn unordered_lines_eq<S>(expected: S) -> UnorderedLinesPredicate
+where
+    S: Into<String>,
+{
+    UnorderedLinesPredicate {
+        expected: expected.into(),
+    }
+}
+
+/// Predicate that compares raw bytes, and reports the first differing
+/// offset instead of the whole content.
+///
+/// This is created by [`bytes_eq`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytesEqPredicate {
+    expected: Vec<u8>,
+}
+
+impl Predicate<[u8]> for BytesEqPredicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        self.expected == variable
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<reflection::Case<'a>> {
+        let result = self.expected == variable;
+
+        if result != expected {
+            return None;
+        }
+
+        let mut case = reflection::Case::new(Some(self), result)
+            .add_product(reflection::Product::new("var size", variable.len()));
+
+        if let Some(offset) = self
+            .expected
+            .iter()
+            .zip(variable.iter())
+            .position(|(expected, variable)| expected != variable)
+            .or_else(|| {
+                if self.expected.len() != variable.len() {
+                    Some(self.expected.len().min(variable.len()))
+                } else {
+                    None
+                }
+            })
+        {
+            case = case.add_product(reflection::Product::new("first difference at", offset));
+        }
+
+        Some(case)
+    }
+}
+
+impl reflection::PredicateReflection for BytesEqPredicate {}
+
+impl fmt::Display for BytesEqPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var == <{} bytes>", self.expected.len())
+    }
+}
+
+/// Create a predicate that compares the output with `expected`, byte
+/// for byte, e.g. for programs writing binary data.
+pub fn bytes_eq<B>(expected: B) -> BytesEqPredicate
+where
+    B: Into<Vec<u8>>,
+{
+    BytesEqPredicate {
+        expected: expected.into(),
+    }
+}
+
+/// Predicate that compares the SHA-256 digest of raw bytes.
+///
+/// This is created by [`sha256`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sha256Predicate {
+    expected: String,
+}
+
+impl Predicate<[u8]> for Sha256Predicate {
+    fn eval(&self, variable: &[u8]) -> bool {
+        sha256_hex(variable) == self.expected
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &[u8]) -> Option<reflection::Case<'a>> {
+        let digest = sha256_hex(variable);
+        let result = digest == self.expected;
+
+        if result == expected {
+            Some(
+                reflection::Case::new(Some(self), result)
+                    .add_product(reflection::Product::new("var sha256", digest))
+                    .add_product(reflection::Product::new("var size", variable.len())),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl reflection::PredicateReflection for Sha256Predicate {
+    fn parameters<'a>(&'a self) -> Box<dyn Iterator<Item = reflection::Parameter<'a>> + 'a> {
+        Box::new(vec![reflection::Parameter::new("expected", &self.expected)].into_iter())
+    }
+}
+
+impl fmt::Display for Sha256Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sha256(var) == {}", self.expected)
+    }
+}
+
+/// Create a predicate that compares the SHA-256 digest of the output
+/// with `expected`, given in hexadecimal, e.g. for large binary outputs.
+pub fn sha256<S>(expected: S) -> Sha256Predicate
+where
+    S: AsRef<str>,
+{
+    Sha256Predicate {
+        expected: expected.as_ref().trim().to_ascii_lowercase(),
+    }
+}
+
+/// Predicate that compares two strings, and shows a line diff of them
+/// instead of the whole strings when they differ.
+///
+/// This is created by [`eq_diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEqPredicate {
+    expected: String,
+}
+
+impl Predicate<str> for DiffEqPredicate {
+    fn eval(&self, variable: &str) -> bool {
+        self.expected == variable
+    }
+
+    fn find_case<'a>(&'a self, expected: bool, variable: &str) -> Option<reflection::Case<'a>> {
+        let result = self.expected == variable;
+
+        if result != expected {
+            return None;
+        }
+
+        let mut case = reflection::Case::new(Some(self), result);
+
+        if !result {
+            let diff = crate::diff::colored_lines(&self.expected, variable);
+
+            // The lines may be the same, but not their endings.
+            case = if diff.lines().all(|line| line.starts_with(' ')) {
+                case.add_product(reflection::Product::new(
+                    "diff",
+                    "the lines are equal, but not their endings",
+                ))
+            } else {
+                case.add_product(reflection::Product::new("diff", format!("\n{}", diff)))
+            };
+        }
+
+        Some(case)
+    }
+}
+
+impl reflection::PredicateReflection for DiffEqPredicate {}
+
+impl fmt::Display for DiffEqPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var == <{} lines>", self.expected.lines().count())
+    }
+}
+
+/// Create a predicate that compares the output with `expected`, and
+/// shows a line diff, `-` for the expected lines and `+` for the actual
+/// ones, when they differ, e.g. for programs printing large outputs.
+pub fn eq_diff<S>(expected: S) -> DiffEqPredicate
+where
+    S: Into<String>,
+{
+    DiffEqPredicate {
+        expected: expected.into(),
+    }
+}
+
+/// Predicate that checks that the compiler diagnostics contain no
+/// warning and no error.
+///
+/// This is created by [`no_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoWarningsPredicate;
+
+impl Predicate<[Diagnostic]> for NoWarningsPredicate {
+    fn eval(&self, variable: &[Diagnostic]) -> bool {
+        !variable
+            .iter()
+            .any(|diagnostic| diagnostic.severity >= Severity::Warning)
+    }
+
+    fn find_case<'a>(
+        &'a self,
+        expected: bool,
+        variable: &[Diagnostic],
+    ) -> Option<reflection::Case<'a>> {
+        let result = self.eval(variable);
+
+        if result != expected {
+            return None;
+        }
+
+        Some(
+            variable
+                .iter()
+                .filter(|diagnostic| diagnostic.severity >= Severity::Warning)
+                .fold(
+                    reflection::Case::new(Some(self), result),
+                    |case, diagnostic| {
+                        case.add_product(reflection::Product::new(
+                            "diagnostic",
+                            diagnostic.to_string(),
+                        ))
+                    },
+                ),
+        )
+    }
+}
+
+impl reflection::PredicateReflection for NoWarningsPredicate {}
+
+impl fmt::Display for NoWarningsPredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var has no warnings")
+    }
+}
+
+/// Create a predicate that checks that the compiler has reported
+/// neither a warning nor an error, see [`Assert::diagnostics`].
+///
+/// [`Assert::diagnostics`]: crate::Assert::diagnostics
+pub fn no_warnings() -> NoWarningsPredicate {
+    NoWarningsPredicate
+}
+
+/// Predicate that checks that the compiler diagnostics contain a given
+/// code.
+///
+/// This is created by [`diagnostic_code`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticCodePredicate {
+    code: String,
+}
+
+impl Predicate<[Diagnostic]> for DiagnosticCodePredicate {
+    fn eval(&self, variable: &[Diagnostic]) -> bool {
+        variable
+            .iter()
+            .any(|diagnostic| diagnostic.code.as_deref() == Some(&*self.code))
+    }
+
+    fn find_case<'a>(
+        &'a self,
+        expected: bool,
+        variable: &[Diagnostic],
+    ) -> Option<reflection::Case<'a>> {
+        let result = self.eval(variable);
+
+        if result == expected {
+            Some(
+                reflection::Case::new(Some(self), result)
+                    .add_product(reflection::Product::new("var size", variable.len())),
+            )
+        } else {
+            None
+        }
+    }
+}
+
+impl reflection::PredicateReflection for DiagnosticCodePredicate {
+    fn parameters<'a>(&'a self) -> Box<dyn Iterator<Item = reflection::Parameter<'a>> + 'a> {
+        Box::new(vec![reflection::Parameter::new("code", &self.code)].into_iter())
+    }
+}
+
+impl fmt::Display for DiagnosticCodePredicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "var contains {}", self.code)
+    }
+}
+
+/// Create a predicate that checks that the compiler has reported a
+/// diagnostic with `code`, e.g. `C4996` with MSVC, or
+/// `-Wdeprecated-declarations` with GCC and Clang, see
+/// [`Assert::diagnostics`].
+///
+/// [`Assert::diagnostics`]: crate::Assert::diagnostics
+pub fn diagnostic_code<S>(code: S) -> DiagnosticCodePredicate
+where
+    S: Into<String>,
+{
+    DiagnosticCodePredicate { code: code.into() }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Split a string into its text, where numbers are replaced by a
+/// placeholder, and its numbers.
+fn split_numbers(string: &str) -> (String, Vec<f64>) {
+    let mut numbers = Vec::new();
+    let text = NUMBER.replace_all(string, |captures: &regex::Captures| {
+        numbers.push(captures[0].parse::<f64>().unwrap_or(f64::NAN));
+
+        "\u{0}"
+    });
+
+    (text.into_owned(), numbers)
+}
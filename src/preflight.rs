@@ -0,0 +1,60 @@
+//! The validation of the toolchain before the tests run, see
+//! [`preflight`](crate::preflight()), e.g. in a test running first, or
+//! in the `main` of a custom test harness, so that a misconfigured
+//! machine fails once with a clear report, instead of failing every
+//! snippet with the same cryptic error.
+
+use std::fmt;
+
+/// One check of the toolchain, e.g. `C compiler`, and its outcome: a
+/// description of what has been found, or the reason why it failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: String,
+    pub outcome: Result<String, String>,
+}
+
+/// The checks of the toolchain, displayed with one line per check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Preflight {
+    pub checks: Vec<Check>,
+}
+
+impl Preflight {
+    /// Whether all the checks have passed.
+    pub fn is_ok(&self) -> bool {
+        self.checks.iter().all(|check| check.outcome.is_ok())
+    }
+
+    /// The checks which have failed.
+    pub fn failures(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| check.outcome.is_err())
+    }
+
+    pub(crate) fn check<N>(&mut self, name: N, outcome: Result<String, String>) -> bool
+    where
+        N: Into<String>,
+    {
+        let passed = outcome.is_ok();
+
+        self.checks.push(Check {
+            name: name.into(),
+            outcome,
+        });
+
+        passed
+    }
+}
+
+impl fmt::Display for Preflight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.outcome {
+                Ok(found) => writeln!(f, "ok     {}: {}", check.name, found)?,
+                Err(reason) => writeln!(f, "FAILED {}: {}", check.name, reason)?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+//! Property-based testing of snippets with proptest.
+//!
+//! A strategy generates [`Case`]s, and the compiled program is run once
+//! per case, with its arguments and its standard input. A failing case
+//! is shrunk by proptest to a minimal one. See `Assert::property`.
+
+use std::ffi::OsString;
+
+/// The input of a single run of the program.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Case {
+    /// The arguments, after the ones of `Assert::args`.
+    pub args: Vec<OsString>,
+
+    /// The standard input, replacing the one of `Assert::stdin`.
+    pub stdin: Option<Vec<u8>>,
+}
+
+impl Case {
+    /// A case passing `args` to the program, e.g. with
+    /// `any::<u32>().prop_map(|n| Case::args([n.to_string()]))`.
+    pub fn args<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+            stdin: None,
+        }
+    }
+
+    /// A case writing `stdin` to the standard input of the program,
+    /// e.g. with `vec(any::<u8>(), 0..64).prop_map(Case::stdin)`.
+    pub fn stdin<B>(stdin: B) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        Self {
+            args: Vec::new(),
+            stdin: Some(stdin.into()),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+//! Data race detection, with ThreadSanitizer.
+//!
+//! With GCC and Clang, the program is built with `-fsanitize=thread`,
+//! optionally given a suppressions file. The reports of ThreadSanitizer
+//! fail the assertion, even if the program exits successfully, e.g.
+//! when `TSAN_OPTIONS` sets `exitcode=0`.
+//!
+//! On Linux, the runtime of ThreadSanitizer refuses to start when the
+//! address space layout is too randomized, which is the default of the
+//! recent kernels, so the program is run with `setarch -R`, disabling
+//! the randomization for it only.
+
+use std::path::Path;
+
+/// The prefix of the first line of each report of ThreadSanitizer.
+const TSAN_HEADER: &str = "WARNING: ThreadSanitizer:";
+
+/// The prefix of the last line of each report of ThreadSanitizer.
+const TSAN_FOOTER: &str = "SUMMARY: ThreadSanitizer:";
+
+/// The `TSAN_OPTIONS` of the program: the `options` already set, if
+/// any, followed by the `suppressions` file.
+pub(crate) fn tsan_options(options: Option<&str>, suppressions: Option<&Path>) -> String {
+    let mut options = options
+        .map(str::trim)
+        .filter(|options| !options.is_empty())
+        .map(ToString::to_string)
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    if let Some(suppressions) = suppressions {
+        options.push(format!("suppressions={}", suppressions.display()));
+    }
+
+    options.join(":")
+}
+
+/// The reports of ThreadSanitizer in `stderr`, from the first one to the
+/// end of the last one, if any.
+pub(crate) fn tsan_report(stderr: &str) -> Option<&str> {
+    let start = stderr.find(TSAN_HEADER)?;
+    let report = &stderr[start..];
+    let end = report.rfind(TSAN_FOOTER).map_or(report.len(), |end| {
+        end + report[end..].find('\n').unwrap_or(report.len() - end)
+    });
+
+    Some(&report[..end])
+}
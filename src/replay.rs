@@ -0,0 +1,151 @@
+//! Replay bundles, recording a compilation and a run of a program, see
+//! `Assert::record` and `Assert::replay`, e.g. to triage locally a
+//! failure only occurring in CI, without its toolchain.
+//!
+//! A bundle is a directory, easy to inspect or to upload as an
+//! artifact of a CI job, with one file per recorded item:
+//!
+//! * `source`, the source of the snippet,
+//! * `compiler`, the command of the compiler,
+//! * `compilation.status`, `compilation.stdout` and
+//!   `compilation.stderr`, the output of the compiler,
+//! * `command`, the command of the program, if it has run,
+//! * `status`, `stdout` and `stderr`, the output of the program, if it
+//!   has run.
+//!
+//! The statuses are written `exit <code>`, or `signal <number>` when
+//! the process has been terminated by a signal.
+
+use std::{
+    fs, io,
+    path::Path,
+    process::{Command, ExitStatus, Output},
+};
+
+/// A compilation and a run of a program, loaded from a bundle.
+pub(crate) struct Replay {
+    pub(crate) compilation: Output,
+    pub(crate) run: Option<Output>,
+}
+
+/// Record in the bundle `directory` the compilation of `source` by
+/// `compiler`, and its `run` by its command, if any.
+pub(crate) fn record(
+    directory: &Path,
+    source: &str,
+    compiler: &Command,
+    compilation: &Output,
+    run: Option<(&Command, &Output)>,
+) -> io::Result<()> {
+    fs::create_dir_all(directory)?;
+    fs::write(directory.join("source"), source)?;
+    fs::write(directory.join("compiler"), format!("{:?}\n", compiler))?;
+    write_output(directory, "compilation.", compilation)?;
+
+    for name in ["command", "status", "stdout", "stderr"] {
+        let path = directory.join(name);
+
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+
+    if let Some((command, output)) = run {
+        fs::write(directory.join("command"), format!("{:?}\n", command))?;
+        write_output(directory, "", output)?;
+    }
+
+    Ok(())
+}
+
+/// Load the bundle `directory`, which must have been recorded from
+/// `source`.
+pub(crate) fn load(directory: &Path, source: &str) -> io::Result<Replay> {
+    let recorded = fs::read_to_string(directory.join("source"))?;
+
+    if recorded != source {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "the bundle `{}` has been recorded from another source",
+                directory.display()
+            ),
+        ));
+    }
+
+    let compilation = read_output(directory, "compilation.")?;
+    let run = if directory.join("status").exists() {
+        Some(read_output(directory, "")?)
+    } else {
+        None
+    };
+
+    Ok(Replay { compilation, run })
+}
+
+fn write_output(directory: &Path, prefix: &str, output: &Output) -> io::Result<()> {
+    let status = match output.status.code() {
+        Some(code) => format!("exit {}\n", code),
+        None => format!("signal {}\n", signal(&output.status).unwrap_or_default()),
+    };
+
+    fs::write(directory.join(format!("{}status", prefix)), status)?;
+    fs::write(directory.join(format!("{}stdout", prefix)), &output.stdout)?;
+    fs::write(directory.join(format!("{}stderr", prefix)), &output.stderr)
+}
+
+fn read_output(directory: &Path, prefix: &str) -> io::Result<Output> {
+    let status = fs::read_to_string(directory.join(format!("{}status", prefix)))?;
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid status `{}` in the bundle", status.trim()),
+        )
+    };
+    let (kind, value) = status.trim().split_once(' ').ok_or_else(invalid)?;
+    let value = value.parse::<i32>().map_err(|_| invalid())?;
+    let status = match kind {
+        "exit" => exit_status(value),
+        "signal" => signal_status(value).ok_or_else(invalid)?,
+        _ => return Err(invalid()),
+    };
+
+    Ok(Output {
+        status,
+        stdout: fs::read(directory.join(format!("{}stdout", prefix)))?,
+        stderr: fs::read(directory.join(format!("{}stderr", prefix)))?,
+    })
+}
+
+#[cfg(unix)]
+fn signal(status: &ExitStatus) -> Option<i32> {
+    std::os::unix::process::ExitStatusExt::signal(status)
+}
+
+#[cfg(not(unix))]
+fn signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+fn exit_status(code: i32) -> ExitStatus {
+    std::os::unix::process::ExitStatusExt::from_raw((code & 0xff) << 8)
+}
+
+#[cfg(windows)]
+fn exit_status(code: i32) -> ExitStatus {
+    std::os::windows::process::ExitStatusExt::from_raw(code as u32)
+}
+
+#[cfg(unix)]
+fn signal_status(signal: i32) -> Option<ExitStatus> {
+    Some(std::os::unix::process::ExitStatusExt::from_raw(
+        signal & 0x7f,
+    ))
+}
+
+/// The signals do not exist on Windows.
+#[cfg(windows)]
+fn signal_status(_signal: i32) -> Option<ExitStatus> {
+    None
+}
@@ -0,0 +1,349 @@
+//! Machine-readable reports of the assertions.
+//!
+//! When the `REPORT` variable is set, e.g. with
+//! `INLINE_C_RS_REPORT=target/inline-c-report.jsonl`, one JSON record is
+//! appended to the given file for each assertion, once it is dropped.
+//! The file uses the JSON Lines format, so that records from concurrent
+//! tests and test binaries can be appended to the same file.
+//!
+//! When the `JUNIT` variable is set, e.g. with
+//! `INLINE_C_RS_JUNIT=target/junit.xml`, the records of the process
+//! are also aggregated into a JUnit XML file. The file is rewritten
+//! each time an assertion is dropped, so it is complete when the
+//! process exits; [`flush`] can also be called explicitly. Compilation
+//! failures are reported as `<error type="compile">`, other failures
+//! as `<failure type="run">`, and the assertions dropped without
+//! compiling their program, e.g. skipped ones, as `<skipped>`.
+
+use crate::build_dir;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, prelude::*},
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::Mutex,
+    thread,
+    time::Duration,
+};
+
+static JUNIT_RECORDS: Mutex<Vec<(PathBuf, Record)>> = Mutex::new(Vec::new());
+
+/// The record of a single assertion.
+#[derive(Debug, Clone)]
+pub struct Record {
+    /// The name of the test running the assertion, if known.
+    pub test: Option<String>,
+
+    /// The name given to the snippet, if any.
+    pub name: Option<String>,
+
+    /// A stable hash of the snippet, once directives have been removed.
+    pub snippet_hash: String,
+
+    /// The compiler program.
+    pub compiler: String,
+
+    /// The arguments passed to the compiler.
+    pub flags: Vec<String>,
+
+    /// Whether the compilation succeeded.
+    pub compiled: bool,
+
+    /// The duration of the compilation.
+    pub compile_time: Duration,
+
+    /// Whether the executable has been restored from the cache.
+    pub cache_hit: bool,
+
+    /// The duration of the last run, if any.
+    pub run_time: Option<Duration>,
+
+    /// The exit code of the last run, if any.
+    pub exit_code: Option<i32>,
+
+    /// The size of the standard output of the last run, in bytes.
+    pub stdout_size: usize,
+
+    /// The size of the standard error of the last run, in bytes.
+    pub stderr_size: usize,
+
+    /// Whether the assertion passed, i.e. it has been dropped without
+    /// panicking.
+    pub passed: bool,
+
+    /// Whether the assertion has been dropped without compiling the
+    /// program, e.g. because it has been skipped. It has not passed
+    /// then.
+    pub not_run: bool,
+}
+
+impl Record {
+    pub(crate) fn new(program: &str, name: Option<&str>) -> Self {
+        Self {
+            test: thread::current().name().map(ToString::to_string),
+            name: name.map(ToString::to_string),
+            snippet_hash: hash(program.as_bytes()),
+            compiler: String::new(),
+            flags: Vec::new(),
+            compiled: false,
+            compile_time: Duration::default(),
+            cache_hit: false,
+            run_time: None,
+            exit_code: None,
+            stdout_size: 0,
+            stderr_size: 0,
+            passed: false,
+            not_run: false,
+        }
+    }
+
+    pub(crate) fn compiler(&mut self, compiler: &Command) {
+        self.compiler = compiler.get_program().to_string_lossy().into_owned();
+        self.flags = compiler
+            .get_args()
+            .map(|argument| argument.to_string_lossy().into_owned())
+            .collect();
+    }
+
+    pub(crate) fn compiled(&mut self, compiled: bool, compile_time: Duration, cache_hit: bool) {
+        self.compiled = compiled;
+        self.compile_time = compile_time;
+        self.cache_hit = cache_hit;
+    }
+
+    pub(crate) fn ran(&mut self, run_time: Duration, output: &Output) {
+        self.run_time = Some(run_time);
+        self.exit_code = output.status.code();
+        self.stdout_size = output.stdout.len();
+        self.stderr_size = output.stderr.len();
+    }
+
+    /// Serialize the record as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"test\":{},\"name\":{},\"snippet_hash\":{},\"compiler\":{},\"flags\":[{}],\"compiled\":{},\"compile_time_ms\":{},\"cache_hit\":{},\"run_time_ms\":{},\"exit_code\":{},\"stdout_size\":{},\"stderr_size\":{},\"passed\":{},\"not_run\":{}}}",
+            self.test
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+            self.name
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_string()),
+            json_string(&self.snippet_hash),
+            json_string(&self.compiler),
+            self.flags
+                .iter()
+                .map(|flag| json_string(flag))
+                .collect::<Vec<_>>()
+                .join(","),
+            self.compiled,
+            self.compile_time.as_millis(),
+            self.cache_hit,
+            self.run_time
+                .map(|run_time| run_time.as_millis().to_string())
+                .unwrap_or_else(|| "null".to_string()),
+            self.exit_code
+                .map(|exit_code| exit_code.to_string())
+                .unwrap_or_else(|| "null".to_string()),
+            self.stdout_size,
+            self.stderr_size,
+            self.passed,
+            self.not_run,
+        )
+    }
+}
+
+/// A record waiting to be written into its report files.
+pub(crate) struct Pending {
+    json: Option<PathBuf>,
+    junit: Option<PathBuf>,
+    pub(crate) record: Record,
+}
+
+impl Pending {
+    /// Return `None` if no report has been requested.
+    pub(crate) fn new(
+        json: Option<PathBuf>,
+        junit: Option<PathBuf>,
+        record: Record,
+    ) -> Option<Self> {
+        if json.is_none() && junit.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            json,
+            junit,
+            record,
+        })
+    }
+
+    /// Record an assertion dropped without compiling the program.
+    pub(crate) fn not_run(mut self) {
+        self.record.not_run = true;
+        self.finish(false);
+    }
+
+    pub(crate) fn finish(mut self, passed: bool) {
+        self.record.passed = passed;
+
+        if let Some(path) = &self.json {
+            if let Err(error) = append(path, &self.record) {
+                eprintln!("Failed to write the report `{}`: {}", path.display(), error);
+            }
+        }
+
+        if let Some(path) = self.junit {
+            JUNIT_RECORDS
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .push((path, self.record));
+
+            if let Err(error) = flush() {
+                eprintln!("Failed to write the JUnit report: {}", error);
+            }
+        }
+    }
+}
+
+/// Append a record to a report file.
+pub(crate) fn append(path: &Path, record: &Record) -> io::Result<()> {
+    // Serialize writes from concurrent tests of the same process.
+    static LOCK: Mutex<()> = Mutex::new(());
+    let _guard = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format!("{}\n", record.to_json()).as_bytes())
+}
+
+/// Write all the JUnit reports of the process.
+pub fn flush() -> io::Result<()> {
+    let records = JUNIT_RECORDS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    let mut paths = records.iter().map(|(path, _)| path).collect::<Vec<_>>();
+    paths.sort();
+    paths.dedup();
+
+    for path in paths {
+        let records = records
+            .iter()
+            .filter(|(record_path, _)| record_path == path)
+            .map(|(_, record)| record)
+            .collect::<Vec<_>>();
+
+        fs::write(path, junit(&records))?;
+    }
+
+    Ok(())
+}
+
+fn junit(records: &[&Record]) -> String {
+    let suite = build_dir::crate_name().unwrap_or_else(|| "inline-c".to_string());
+
+    let skipped = records.iter().filter(|r| r.not_run).count();
+    let errors = records
+        .iter()
+        .filter(|r| !r.passed && !r.compiled && !r.not_run)
+        .count();
+    let failures = records.iter().filter(|r| !r.passed && r.compiled).count();
+    let time =
+        |record: &Record| (record.compile_time + record.run_time.unwrap_or_default()).as_secs_f64();
+
+    let mut output = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    output.push_str(&format!(
+        "<testsuites>\n  <testsuite name={} tests=\"{}\" errors=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        xml_attribute(&suite),
+        records.len(),
+        errors,
+        failures,
+        skipped,
+        records.iter().map(|record| time(record)).sum::<f64>(),
+    ));
+
+    for record in records {
+        output.push_str(&format!(
+            "    <testcase classname={} name={} time=\"{:.3}\"",
+            xml_attribute(&suite),
+            xml_attribute(&format!(
+                "{} ({})",
+                record.test.as_deref().unwrap_or("<unknown>"),
+                record.name.as_deref().unwrap_or(&record.snippet_hash)
+            )),
+            time(record),
+        ));
+
+        match (record.passed, record.compiled) {
+            (true, _) => output.push_str("/>\n"),
+            (false, false) if record.not_run => output.push_str(
+                ">\n      <skipped message=\"The snippet has not been run\"/>\n    </testcase>\n",
+            ),
+            (false, false) => output.push_str(
+                ">\n      <error type=\"compile\" message=\"The snippet failed to compile\"/>\n    </testcase>\n",
+            ),
+            (false, true) => output.push_str(&format!(
+                ">\n      <failure type=\"run\" message=\"The assertion failed (exit code: {})\"/>\n    </testcase>\n",
+                record
+                    .exit_code
+                    .map(|exit_code| exit_code.to_string())
+                    .unwrap_or_else(|| "none".to_string()),
+            )),
+        }
+    }
+
+    output.push_str("  </testsuite>\n</testsuites>\n");
+
+    output
+}
+
+fn xml_attribute(value: &str) -> String {
+    let mut output = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\n' => output.push_str("&#10;"),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+
+    output
+}
+
+/// FNV-1a, which is stable across platforms and Rust versions, unlike
+/// `DefaultHasher`.
+pub(crate) fn hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+pub(crate) fn json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len() + 2);
+    output.push('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+
+    output.push('"');
+
+    output
+}
@@ -0,0 +1,92 @@
+//! The compilers a snippet requires, given by the `REQUIRES` variable,
+//! e.g. `#inline_c_rs REQUIRES: "msvc>=19.30 || clang>=13"`.
+//!
+//! A requirement is a list of alternatives separated by `||`. Each one
+//! names a compiler family, `gcc`, `clang`, `msvc`, `intel`, `nvidia`
+//! or `tcc`, optionally followed by a comparison of its version with
+//! `>=`, `>`, `<=`, `<`, `==` or `!=`. The versions are compared
+//! component by component, the missing components being zeros.
+
+use crate::run::{CompilerInfo, Family};
+use std::cmp::Ordering;
+
+/// Whether `compiler` satisfies `requirement`, or the reason why the
+/// requirement is invalid.
+pub(crate) fn is_satisfied(requirement: &str, compiler: &CompilerInfo) -> Result<bool, String> {
+    let mut satisfied = false;
+
+    // All the alternatives are parsed, so that a typo never goes
+    // unnoticed.
+    for alternative in requirement.split("||") {
+        satisfied |= alternative_is_satisfied(alternative.trim(), compiler)?;
+    }
+
+    Ok(satisfied)
+}
+
+fn alternative_is_satisfied(alternative: &str, compiler: &CompilerInfo) -> Result<bool, String> {
+    let split = alternative
+        .find(['<', '>', '=', '!'])
+        .unwrap_or(alternative.len());
+    let (family, comparison) = alternative.split_at(split);
+
+    let family_matches = match family.trim().to_ascii_lowercase().as_str() {
+        "gcc" | "gnu" => compiler.family == Family::Gnu,
+        "clang" => compiler.family == Family::Clang,
+        "msvc" => compiler.family == Family::Msvc,
+        "intel" => matches!(compiler.family, Family::Intel { .. }),
+        "nvidia" | "nvc" => compiler.family == Family::Nvidia,
+        "tcc" => compiler.family == Family::Tiny,
+        family => {
+            return Err(format!(
+                "unknown compiler `{}` in the requirement `{}`",
+                family, alternative
+            ))
+        }
+    };
+
+    if comparison.is_empty() {
+        return Ok(family_matches);
+    }
+
+    let (operator, expected) = ["==", "!=", ">=", "<=", ">", "<"]
+        .iter()
+        .find_map(|operator| Some((*operator, comparison.strip_prefix(operator)?)))
+        .ok_or_else(|| format!("invalid comparison in the requirement `{}`", alternative))?;
+    let expected = parse_version(expected.trim())
+        .ok_or_else(|| format!("invalid version in the requirement `{}`", alternative))?;
+
+    // A compiler whose version is unknown cannot satisfy a comparison.
+    let Some(version) = compiler.version.as_deref().and_then(parse_version) else {
+        return Ok(false);
+    };
+    let ordering = compare(&version, &expected);
+
+    Ok(family_matches
+        && match operator {
+            "==" => ordering == Ordering::Equal,
+            "!=" => ordering != Ordering::Equal,
+            ">=" => ordering != Ordering::Less,
+            "<=" => ordering != Ordering::Greater,
+            ">" => ordering == Ordering::Greater,
+            _ => ordering == Ordering::Less,
+        })
+}
+
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .split('.')
+        .map(|component| component.parse().ok())
+        .collect()
+}
+
+fn compare(left: &[u64], right: &[u64]) -> Ordering {
+    (0..left.len().max(right.len()))
+        .map(|index| {
+            left.get(index)
+                .unwrap_or(&0)
+                .cmp(right.get(index).unwrap_or(&0))
+        })
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,50 @@
+//! Windows resources linked into the program: resource scripts, and
+//! application manifests.
+//!
+//! Resource scripts are compiled with `rc.exe` for MSVC, or with
+//! `windres` for MinGW, and the compiled resources are given to the
+//! compiler along the source file.
+
+use crate::assert::Compiler;
+use std::{path::Path, process::Command};
+
+/// `CREATEPROCESS_MANIFEST_RESOURCE_ID` and `RT_MANIFEST`.
+const MANIFEST_RESOURCE: &str = "1 24";
+
+/// The command compiling the resource `script` into `output`.
+pub(crate) fn command(compiler: &Compiler, script: &Path, output: &Path) -> Command {
+    if compiler.msvc {
+        let mut command = cc::windows_registry::find(&compiler.target, "rc.exe")
+            .unwrap_or_else(|| Command::new("rc.exe"));
+        command.arg("/nologo").arg("/fo").arg(output).arg(script);
+
+        command
+    } else {
+        let windres = if cfg!(windows) {
+            "windres".to_string()
+        } else {
+            format!(
+                "{}-w64-mingw32-windres",
+                compiler.target.split('-').next().unwrap_or_default()
+            )
+        };
+
+        let mut command = Command::new(windres);
+        command
+            .args(["--output-format", "coff", "--input"])
+            .arg(script)
+            .arg("--output")
+            .arg(output);
+
+        command
+    }
+}
+
+/// A resource script embedding the application `manifest`.
+pub(crate) fn manifest_script(manifest: &Path) -> String {
+    format!(
+        "{} \"{}\"\n",
+        MANIFEST_RESOURCE,
+        manifest.display().to_string().replace('\\', "\\\\")
+    )
+}
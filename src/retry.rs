@@ -0,0 +1,46 @@
+//! Detection of the transient failures worth retrying, e.g. a file
+//! locked by an antivirus right after it has been written.
+
+use std::{io, process::Output, thread, time::Duration};
+
+/// Diagnostics emitted by compilers and linkers when a file is busy,
+/// as opposed to genuine compilation errors.
+const TRANSIENT_DIAGNOSTICS: &[&str] = &[
+    // cannot open file (usually locked by an antivirus or an indexer)
+    "LNK1104",
+    // cannot open file for writing
+    "LNK1168",
+    // cannot open program database
+    "C1041",
+    // the process cannot access the file because it is being used
+    "being used by another process",
+    "Text file busy",
+    "Resource temporarily unavailable",
+];
+
+/// Whether a failed compilation is due to a transient error.
+pub(crate) fn is_transient_output(output: &Output) -> bool {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    TRANSIENT_DIAGNOSTICS
+        .iter()
+        .any(|diagnostic| stdout.contains(diagnostic) || stderr.contains(diagnostic))
+}
+
+/// Whether a program failed to start because of a transient error.
+pub(crate) fn is_transient_error(error: &io::Error) -> bool {
+    match error.raw_os_error() {
+        // `ERROR_ACCESS_DENIED`, `ERROR_SHARING_VIOLATION` and
+        // `ERROR_LOCK_VIOLATION`.
+        Some(5) | Some(32) | Some(33) if cfg!(windows) => true,
+        // `ETXTBSY`.
+        Some(26) if cfg!(unix) => true,
+        _ => matches!(error.kind(), io::ErrorKind::WouldBlock),
+    }
+}
+
+/// Wait before the given attempt, with a linear back-off.
+pub(crate) fn wait(attempt: usize) {
+    thread::sleep(Duration::from_millis(200 * attempt as u64));
+}
@@ -0,0 +1,563 @@
+//! Runners execute the compiled programs, either directly or through
+//! another program.
+
+use std::{
+    collections::HashMap,
+    ffi::{OsStr, OsString},
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The directory of an Android device where executables can be run.
+const ADB_REMOTE_DIR: &str = "/data/local/tmp";
+
+/// How a compiled program is executed.
+#[derive(Debug, Clone)]
+pub enum Runner {
+    /// Run the executable directly on the host.
+    Native,
+
+    /// Run a WebAssembly module with the Wasmer CLI.
+    Wasmer {
+        /// The `wasmer` program.
+        program: PathBuf,
+
+        /// The WASI capabilities granted to the module.
+        wasi: Wasi,
+    },
+
+    /// Copy the executable to a remote machine with `scp`, and run it
+    /// there with `ssh`, e.g. on a machine that cannot build it.
+    Ssh {
+        /// The remote host, e.g. `user@host`, as understood by `ssh`.
+        host: String,
+
+        /// The remote directory receiving the executable.
+        remote_dir: String,
+
+        /// Additional files to copy along the executable, e.g. shared
+        /// libraries.
+        files: Vec<PathBuf>,
+    },
+
+    /// Run a Windows executable with Wine, e.g. when cross-compiling
+    /// to `x86_64-pc-windows-gnu` from Linux.
+    Wine {
+        /// The `wine` program.
+        program: PathBuf,
+    },
+
+    /// Run a foreign-architecture executable with QEMU user-mode
+    /// emulation, e.g. `qemu-aarch64 -L <sysroot>`.
+    Qemu {
+        /// The QEMU program, e.g. `qemu-aarch64`.
+        program: PathBuf,
+
+        /// The sysroot to load the dynamic libraries from, given to
+        /// QEMU with `-L`.
+        sysroot: Option<PathBuf>,
+    },
+
+    /// Push the executable to an Android device or emulator with
+    /// `adb`, in `/data/local/tmp`, and run it there.
+    Adb {
+        /// The `adb` program.
+        program: PathBuf,
+
+        /// The serial number of the device, when several are attached.
+        serial: Option<String>,
+    },
+
+    /// Run an executable built for the iOS simulator with
+    /// `xcrun simctl spawn`.
+    Simctl {
+        /// The simulated device, e.g. its UDID, or `booted` for the
+        /// device currently booted.
+        device: String,
+    },
+
+    /// Run the executable in a container, e.g. to validate it against
+    /// an older glibc or a musl userland. The directory containing the
+    /// executable is bind-mounted read-only at `/inline-c`.
+    Docker {
+        /// The container engine, e.g. `docker` or `podman`.
+        program: PathBuf,
+
+        /// The image to run, e.g. `debian:buster`.
+        image: String,
+
+        /// Additional host directories, mounted to container
+        /// directories.
+        mounts: Vec<(PathBuf, String)>,
+    },
+}
+
+/// The WASI capabilities granted to a WebAssembly module. Nothing is
+/// granted by default, so that runs are hermetic.
+#[derive(Debug, Clone, Default)]
+pub struct Wasi {
+    /// Host directories, mapped to guest directories.
+    pub dirs: Vec<(PathBuf, String)>,
+
+    /// Environment variables.
+    pub envs: Vec<(String, String)>,
+}
+
+impl Runner {
+    /// Parse a runner from the `RUNNER` variable:
+    ///
+    /// * `native`,
+    /// * `wasmer` or `wasmer:<path to wasmer>`,
+    /// * `ssh://<host>/<remote directory>`, e.g.
+    ///   `ssh://ci@arm64-box/C:/tmp`,
+    /// * `docker://<image>` or `podman://<image>`, e.g.
+    ///   `docker://alpine:3.12`,
+    /// * `wine` or `wine:<path to wine>`,
+    /// * `qemu:<qemu program>` or `qemu:<qemu program>:<sysroot>`, e.g.
+    ///   `qemu:qemu-aarch64:/usr/aarch64-linux-gnu`,
+    /// * `adb` or `adb://<device serial>`, e.g. `adb://emulator-5554`,
+    /// * `simctl` or `simctl://<device>`, e.g. `simctl://iPhone 12`.
+    pub fn parse(runner: &str) -> Result<Self, String> {
+        let runner = runner.trim();
+
+        if runner == "native" {
+            Ok(Self::Native)
+        } else if runner == "wasmer" || runner.starts_with("wasmer:") {
+            Ok(Self::Wasmer {
+                program: PathBuf::from(runner.strip_prefix("wasmer:").unwrap_or("wasmer")),
+                wasi: Wasi::default(),
+            })
+        } else if runner == "wine" || runner.starts_with("wine:") {
+            Ok(Self::Wine {
+                program: PathBuf::from(runner.strip_prefix("wine:").unwrap_or("wine")),
+            })
+        } else if let Some(qemu) = runner.strip_prefix("qemu:") {
+            let (program, sysroot) = match qemu.split_once(':') {
+                Some((program, sysroot)) => (program, Some(PathBuf::from(sysroot))),
+                None => (qemu, None),
+            };
+
+            Ok(Self::Qemu {
+                program: PathBuf::from(program),
+                sysroot,
+            })
+        } else if runner == "adb" || runner.starts_with("adb://") {
+            Ok(Self::Adb {
+                program: PathBuf::from("adb"),
+                serial: runner
+                    .strip_prefix("adb://")
+                    .filter(|serial| !serial.is_empty())
+                    .map(ToString::to_string),
+            })
+        } else if runner == "simctl" || runner.starts_with("simctl://") {
+            Ok(Self::Simctl {
+                device: runner
+                    .strip_prefix("simctl://")
+                    .filter(|device| !device.is_empty())
+                    .unwrap_or("booted")
+                    .to_string(),
+            })
+        } else if let Some(location) = runner.strip_prefix("ssh://") {
+            let (host, remote_dir) = location
+                .split_once('/')
+                .ok_or_else(|| format!("missing remote directory in runner `{}`", runner))?;
+
+            Ok(Self::Ssh {
+                host: host.to_string(),
+                remote_dir: remote_dir.to_string(),
+                files: Vec::new(),
+            })
+        } else if let Some((program, image)) = runner
+            .strip_prefix("docker://")
+            .map(|image| ("docker", image))
+            .or_else(|| {
+                runner
+                    .strip_prefix("podman://")
+                    .map(|image| ("podman", image))
+            })
+        {
+            Ok(Self::Docker {
+                program: PathBuf::from(program),
+                image: image.to_string(),
+                mounts: Vec::new(),
+            })
+        } else {
+            Err(format!("unknown runner `{}`", runner))
+        }
+    }
+
+    /// Prepare the execution of a freshly compiled executable, e.g.
+    /// copy it to a remote machine.
+    pub(crate) fn prepare(&self, executable: &Path) -> io::Result<()> {
+        match self {
+            Self::Native
+            | Self::Wasmer { .. }
+            | Self::Wine { .. }
+            | Self::Qemu { .. }
+            | Self::Simctl { .. }
+            | Self::Docker { .. } => Ok(()),
+
+            Self::Ssh {
+                host,
+                remote_dir,
+                files,
+            } => {
+                let mut command = Command::new("scp");
+                command
+                    .arg("-q")
+                    .arg(executable)
+                    .args(files)
+                    .arg(format!("{}:{}/", host, remote_dir));
+
+                check(command)
+            }
+
+            Self::Adb { .. } => {
+                let mut command = self.adb();
+                command
+                    .arg("push")
+                    .arg(executable)
+                    .arg(format!("{}/", ADB_REMOTE_DIR));
+
+                check(command)?;
+
+                let mut command = self.adb();
+                command.arg("shell").arg(format!(
+                    "chmod 755 {}",
+                    quote_sh(&remote_path(ADB_REMOTE_DIR, executable))
+                ));
+
+                check(command)
+            }
+        }
+    }
+
+    /// Remove what [`Runner::prepare`] has created.
+    pub(crate) fn cleanup(&self, executable: &Path) {
+        if let Self::Ssh {
+            host, remote_dir, ..
+        } = self
+        {
+            let remote_executable = remote_path(remote_dir, executable);
+            let remove = if is_windows_executable(executable) {
+                format!("del /q {}", quote_cmd(&remote_executable))
+            } else {
+                format!("rm -f {}", quote_sh(&remote_executable))
+            };
+
+            let _ = Command::new("ssh").arg(host).arg(remove).output();
+        } else if let Self::Adb { .. } = self {
+            let _ = self
+                .adb()
+                .arg("shell")
+                .arg(format!(
+                    "rm -f {}",
+                    quote_sh(&remote_path(ADB_REMOTE_DIR, executable))
+                ))
+                .output();
+        }
+    }
+
+    /// The `adb` command addressing the device of [`Runner::Adb`].
+    fn adb(&self) -> Command {
+        let Self::Adb { program, serial } = self else {
+            unreachable!("not an adb runner");
+        };
+
+        let mut command = Command::new(program);
+
+        if let Some(serial) = serial {
+            command.arg("-s").arg(serial);
+        }
+
+        command
+    }
+
+    /// The command running `executable` with `args`. `envs` are
+    /// forwarded to the program when it does not inherit the
+    /// environment of the runner process, e.g. on a remote machine.
+    pub(crate) fn command(
+        &self,
+        executable: &Path,
+        args: &[OsString],
+        envs: &HashMap<String, String>,
+    ) -> Command {
+        match self {
+            Self::Native => {
+                let mut command = Command::new(executable);
+                command.args(args);
+
+                command
+            }
+
+            Self::Wasmer { program, wasi } => {
+                let mut command = Command::new(program);
+                command.arg("run");
+
+                for (host, guest) in &wasi.dirs {
+                    let mut mapping = OsString::from(guest);
+                    mapping.push(":");
+                    mapping.push(host);
+
+                    command.arg("--mapdir").arg(mapping);
+                }
+
+                for (key, value) in &wasi.envs {
+                    command.arg("--env").arg(format!("{}={}", key, value));
+                }
+
+                command.arg(executable);
+
+                if !args.is_empty() {
+                    command.arg("--").args(args);
+                }
+
+                command
+            }
+
+            Self::Wine { program } => {
+                let mut command = Command::new(program);
+                command.arg(executable).args(args);
+
+                // Silence Wine's own diagnostics, which would pollute the
+                // standard error of the program.
+                if !envs.contains_key("WINEDEBUG") {
+                    command.env("WINEDEBUG", "-all");
+                }
+
+                command
+            }
+
+            Self::Qemu { program, sysroot } => {
+                let mut command = Command::new(program);
+
+                if let Some(sysroot) = sysroot {
+                    command.arg("-L").arg(sysroot);
+                }
+
+                command.arg(executable).args(args);
+
+                command
+            }
+
+            Self::Ssh {
+                host, remote_dir, ..
+            } => {
+                let remote_executable = remote_path(remote_dir, executable);
+                let args = args.iter().map(|argument| argument.to_string_lossy());
+
+                // Windows hosts run OpenSSH with `cmd.exe` as the shell.
+                let remote_command = if is_windows_executable(executable) {
+                    format!(
+                        "cd /d {} && {}{}",
+                        quote_cmd(remote_dir),
+                        envs.iter()
+                            .map(|(key, value)| format!("set \"{}={}\" && ", key, value))
+                            .collect::<String>(),
+                        std::iter::once(quote_cmd(&remote_executable))
+                            .chain(args.map(|argument| quote_cmd(&argument)))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    )
+                } else {
+                    format!(
+                        "cd {} && env {}",
+                        quote_sh(remote_dir),
+                        envs.iter()
+                            .map(|(key, value)| quote_sh(&format!("{}={}", key, value)))
+                            .chain(std::iter::once(quote_sh(&remote_executable)))
+                            .chain(args.map(|argument| quote_sh(&argument)))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    )
+                };
+
+                let mut command = Command::new("ssh");
+                command.arg(host).arg(remote_command);
+
+                command
+            }
+
+            Self::Simctl { device } => {
+                let mut command = Command::new("xcrun");
+                command
+                    .args(["simctl", "spawn", device.as_str()])
+                    .arg(executable)
+                    .args(args);
+
+                // The simulator only passes the variables prefixed with
+                // `SIMCTL_CHILD_` to the program, without the prefix.
+                for (key, value) in envs {
+                    command.env(format!("SIMCTL_CHILD_{}", key), value);
+                }
+
+                command
+            }
+
+            Self::Adb { .. } => {
+                let args = args.iter().map(|argument| argument.to_string_lossy());
+
+                // `adb shell` forwards the exit code of the remote
+                // command since Android 7.
+                let remote_command = format!(
+                    "cd {} && env {}",
+                    ADB_REMOTE_DIR,
+                    envs.iter()
+                        .map(|(key, value)| quote_sh(&format!("{}={}", key, value)))
+                        .chain(std::iter::once(quote_sh(&remote_path(
+                            ADB_REMOTE_DIR,
+                            executable
+                        ))))
+                        .chain(args.map(|argument| quote_sh(&argument)))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                );
+
+                let mut command = self.adb();
+                command.arg("shell").arg(remote_command);
+
+                command
+            }
+
+            Self::Docker {
+                program,
+                image,
+                mounts,
+            } => {
+                let mut command = Command::new(program);
+                command.args(["run", "--rm", "--interactive"]);
+
+                if let Some(directory) = executable.parent() {
+                    command
+                        .arg("--mount")
+                        .arg(bind_mount(directory, "/inline-c", true));
+                }
+
+                for (host, container) in mounts {
+                    command
+                        .arg("--mount")
+                        .arg(bind_mount(host, container, false));
+                }
+
+                for (key, value) in envs {
+                    command.arg("--env").arg(format!("{}={}", key, value));
+                }
+
+                let mut executable_in_container = OsString::from("/inline-c/");
+                executable_in_container.push(executable.file_name().unwrap_or_default());
+
+                command
+                    .args(["--workdir", "/inline-c", image.as_str()])
+                    .arg(executable_in_container)
+                    .args(args);
+
+                command
+            }
+        }
+    }
+}
+
+/// The `--mount` argument of a container engine binding the `host`
+/// directory to the `container` one. Unlike `--volume`, whose fields
+/// are separated by colons, it keeps the Windows paths, e.g. `C:\…`,
+/// whole. Its fields are separated by commas, and quoted when they
+/// contain a comma or a quote.
+fn bind_mount(host: &Path, container: &str, read_only: bool) -> OsString {
+    let mut mount = OsString::from("type=bind,");
+    push_field(&mut mount, "source", host.as_os_str());
+    mount.push(",");
+    push_field(&mut mount, "target", container.as_ref());
+
+    if read_only {
+        mount.push(",readonly");
+    }
+
+    mount
+}
+
+fn push_field(mount: &mut OsString, key: &str, value: &OsStr) {
+    let lossy = value.to_string_lossy();
+
+    if lossy.contains([',', '"']) {
+        mount.push(format!("\"{}={}\"", key, lossy.replace('"', "\"\"")));
+    } else {
+        mount.push(key);
+        mount.push("=");
+        mount.push(value);
+    }
+}
+
+/// The QEMU user-mode emulator for the architecture of `target`, if
+/// it differs from the architecture of `host`.
+pub(crate) fn qemu_for(target: &str, host: &str) -> Option<PathBuf> {
+    let architecture = |triple: &str| triple.split('-').next().unwrap_or_default().to_string();
+    let target_architecture = architecture(target);
+
+    if target_architecture == architecture(host) || !target.contains("linux") {
+        return None;
+    }
+
+    let qemu_architecture = match target_architecture.as_str() {
+        "i586" | "i686" => "i386",
+        "armv7" | "thumbv7neon" => "arm",
+        "powerpc64le" => "ppc64le",
+        "powerpc64" => "ppc64",
+        "powerpc" => "ppc",
+        architecture if architecture.starts_with("riscv64") => "riscv64",
+        architecture if architecture.starts_with("riscv32") => "riscv32",
+        architecture if architecture.starts_with("arm") => "arm",
+        architecture => architecture,
+    };
+
+    Some(PathBuf::from(format!("qemu-{}", qemu_architecture)))
+}
+
+/// Whether `target` is an iOS simulator target, e.g.
+/// `aarch64-apple-ios-sim` or `x86_64-apple-ios`.
+pub(crate) fn is_ios_simulator(target: &str) -> bool {
+    target.contains("-apple-ios")
+        && (target.ends_with("-sim") || target.starts_with("x86_64") || target.starts_with("i386"))
+}
+
+/// Run `command`, and fail with its standard error if it fails.
+fn check(mut command: Command) -> io::Result<()> {
+    let output = command.output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "`{:?}` has failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr)
+        )))
+    }
+}
+
+fn remote_path(remote_dir: &str, executable: &Path) -> String {
+    format!(
+        "{}/{}",
+        remote_dir.trim_end_matches('/'),
+        executable
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default()
+    )
+}
+
+fn is_windows_executable(executable: &Path) -> bool {
+    executable
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+}
+
+/// Quote an argument for a POSIX shell.
+fn quote_sh(argument: &str) -> String {
+    format!("'{}'", argument.replace('\'', "'\\''"))
+}
+
+/// Quote an argument for `cmd.exe`.
+fn quote_cmd(argument: &str) -> String {
+    format!("\"{}\"", argument.replace('"', "\"\""))
+}
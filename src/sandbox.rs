@@ -0,0 +1,142 @@
+//! The confinement of the programs to their working directory, see
+//! `Assert::sandbox`, so that a bug in an experimental snippet cannot
+//! damage the files of the machine running the tests.
+//!
+//! A program run natively or with QEMU is run with `bwrap`, from
+//! Bubblewrap, on Linux: the root is mounted read-only, `/tmp` is a
+//! new empty directory, and only the working directory is writable. On
+//! macOS, it is run with `sandbox-exec`, with a profile denying the
+//! writes outside of the working directory and of `/dev`. The programs
+//! run in a container or by Wasmer are already confined. The programs
+//! cannot be confined on the other hosts and runners.
+
+use crate::{
+    network::{self, wrap},
+    runner::Runner,
+};
+use std::{
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Check that the programs run by `runner` can be confined.
+pub(crate) fn check(runner: &Runner) -> io::Result<()> {
+    let supported = match runner {
+        Runner::Wasmer { .. } | Runner::Docker { .. } => true,
+        Runner::Native | Runner::Qemu { .. } => {
+            cfg!(any(target_os = "linux", target_os = "macos"))
+        }
+        Runner::Ssh { .. } | Runner::Wine { .. } | Runner::Adb { .. } | Runner::Simctl { .. } => {
+            false
+        }
+    };
+
+    if supported {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!(
+                "the programs run by {:?} cannot be sandboxed on this host",
+                runner
+            ),
+        ))
+    }
+}
+
+/// Change `command`, run by `runner`, so that the program can only
+/// write in `directory`, its working directory, and can read
+/// `readable`, e.g. the directories of the executable and of its shared
+/// libraries, which would be hidden under `/tmp` otherwise. The
+/// network is denied too if `deny_network`, since the sandboxes do not
+/// nest on macOS.
+pub(crate) fn confine(
+    command: &mut Command,
+    runner: &Runner,
+    directory: &Path,
+    readable: &[PathBuf],
+    deny_network: bool,
+) {
+    match runner {
+        Runner::Native | Runner::Qemu { .. } => {
+            if cfg!(target_os = "linux") {
+                wrap(
+                    command,
+                    "bwrap",
+                    bwrap_args(directory, readable, deny_network),
+                );
+            } else if cfg!(target_os = "macos") {
+                wrap(
+                    command,
+                    "sandbox-exec",
+                    ["-p".to_string(), profile(directory, deny_network)],
+                );
+            }
+        }
+
+        _ => {
+            if deny_network {
+                network::deny(command, runner);
+            }
+        }
+    }
+}
+
+fn bwrap_args(directory: &Path, readable: &[PathBuf], deny_network: bool) -> Vec<OsString> {
+    let mut args = [
+        "--ro-bind",
+        "/",
+        "/",
+        "--dev",
+        "/dev",
+        "--proc",
+        "/proc",
+        "--tmpfs",
+        "/tmp",
+        "--die-with-parent",
+    ]
+    .iter()
+    .map(OsString::from)
+    .collect::<Vec<_>>();
+
+    // The later mounts are over the earlier ones.
+    for path in readable {
+        args.extend(["--ro-bind".into(), path.into(), path.into()]);
+    }
+
+    args.extend(["--bind".into(), directory.into(), directory.into()]);
+    args.extend(["--chdir".into(), directory.into()]);
+
+    if deny_network {
+        args.push("--unshare-net".into());
+    }
+
+    args.push("--".into());
+
+    args
+}
+
+fn profile(directory: &Path, deny_network: bool) -> String {
+    // The profiles match the resolved paths, e.g. `/private/var/…` for
+    // `/var/…`.
+    let directory = directory
+        .canonicalize()
+        .unwrap_or_else(|_| directory.to_path_buf());
+    let quoted = directory
+        .to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    let mut profile = format!(
+        "(version 1) (allow default) (deny file-write*) \
+         (allow file-write* (subpath \"{}\") (subpath \"/dev\"))",
+        quoted
+    );
+
+    if deny_network {
+        profile.push_str(" (deny network*)");
+    }
+
+    profile
+}
@@ -0,0 +1,81 @@
+//! The priority and the CPU affinity of the programs, see
+//! `Assert::priority` and `Assert::cpu_affinity`, e.g. to reduce the
+//! noise of a benchmark, or to reproduce a race condition that only
+//! occurs on a single core.
+//!
+//! A program run natively, with Wine, with QEMU or by Wasmer is run
+//! with `nice` and `taskset` on Linux, and with `nice` on the other
+//! Unix hosts, which cannot pin a program to some CPUs. On Windows, it
+//! is created with the priority class closest to its niceness, and
+//! cannot be pinned either. The programs run on other machines, or in
+//! a container, cannot be scheduled.
+
+use crate::{network::wrap, runner::Runner};
+use std::{io, process::Command};
+
+/// Check that the programs run by `runner` can be given `priority` and
+/// `affinity`.
+pub(crate) fn check(runner: &Runner, priority: Option<i32>, affinity: &[usize]) -> io::Result<()> {
+    let local = matches!(
+        runner,
+        Runner::Native | Runner::Wasmer { .. } | Runner::Wine { .. } | Runner::Qemu { .. }
+    );
+
+    if (priority.is_some() || !affinity.is_empty()) && !local {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("the programs run by {:?} cannot be scheduled", runner),
+        ));
+    }
+
+    if !affinity.is_empty() && !cfg!(target_os = "linux") {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the programs cannot be pinned to some CPUs on this host",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Change `command` so that the program runs with the niceness
+/// `priority`, from -20, the highest priority, to 19, the lowest, and
+/// on the CPUs of `affinity` only, if [`check`] allows it.
+pub(crate) fn apply(command: &mut Command, priority: Option<i32>, affinity: &[usize]) {
+    if cfg!(target_os = "linux") && !affinity.is_empty() {
+        let cpus = affinity
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        wrap(command, "taskset", ["--cpu-list".to_string(), cpus]);
+    }
+
+    let Some(priority) = priority else {
+        return;
+    };
+
+    if cfg!(unix) {
+        wrap(command, "nice", ["-n".to_string(), priority.to_string()]);
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        const IDLE_PRIORITY_CLASS: u32 = 0x40;
+        const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x4000;
+        const NORMAL_PRIORITY_CLASS: u32 = 0x20;
+        const ABOVE_NORMAL_PRIORITY_CLASS: u32 = 0x8000;
+        const HIGH_PRIORITY_CLASS: u32 = 0x80;
+
+        command.creation_flags(match priority {
+            15.. => IDLE_PRIORITY_CLASS,
+            1..=14 => BELOW_NORMAL_PRIORITY_CLASS,
+            0 => NORMAL_PRIORITY_CLASS,
+            -14..=-1 => ABOVE_NORMAL_PRIORITY_CLASS,
+            _ => HIGH_PRIORITY_CLASS,
+        });
+    }
+}
@@ -0,0 +1,98 @@
+//! The programs shared by the tests of a test binary, see
+//! [`once_compiled!`](crate::once_compiled), e.g. a helper program run
+//! by several tests.
+//!
+//! A shared program is compiled by the first test using it, while the
+//! other tests using it wait, and its artifacts are removed when the
+//! test binary exits. Its runs are serialized, since an assertion
+//! cannot be used by several threads at once.
+
+use crate::assert::{Assert, Run};
+use lazy_static::lazy_static;
+use std::{
+    collections::HashMap,
+    os::raw::c_int,
+    path::PathBuf,
+    sync::{Mutex, Once, PoisonError},
+};
+
+lazy_static! {
+    static ref PROGRAMS: Mutex<HashMap<String, &'static Mutex<Option<Assert>>>> =
+        Mutex::new(HashMap::new());
+}
+
+extern "C" {
+    fn atexit(callback: extern "C" fn()) -> c_int;
+}
+
+/// A program shared by the tests, returned by [`once_compiled`].
+#[derive(Clone, Copy)]
+pub struct Shared {
+    program: &'static Mutex<Option<Assert>>,
+}
+
+/// The program `name`, built by `build` and compiled the first time it
+/// is requested, or `None` if `build` has skipped it. It panics if the
+/// program could not be compiled.
+pub fn once_compiled<F>(name: &str, build: F) -> Option<Shared>
+where
+    F: FnOnce() -> Option<Assert>,
+{
+    static CLEANUP: Once = Once::new();
+
+    CLEANUP.call_once(|| unsafe {
+        atexit(remove_programs);
+    });
+
+    let program = *PROGRAMS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(name.to_string())
+        .or_insert_with(|| Box::leak(Box::new(Mutex::new(None))));
+
+    // A failed compilation poisons the program, whose next user
+    // compiles it again, and fails the same way.
+    let mut assert = program.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if assert.is_none() {
+        let mut built = build()?;
+        built.executable();
+        *assert = Some(built);
+    }
+
+    Some(Shared { program })
+}
+
+impl Shared {
+    /// The path of the executable.
+    pub fn executable(&self) -> PathBuf {
+        self.program
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_mut()
+            .expect("The shared program has been removed")
+            .executable()
+            .to_path_buf()
+    }
+
+    /// Prepare a run of the program, see
+    /// [`Compiled::run`](crate::Compiled::run).
+    pub fn run(&self) -> Run<'static> {
+        Run::shared(self.program)
+    }
+}
+
+/// Remove the artifacts of the shared programs, when the process exits.
+extern "C" fn remove_programs() {
+    let Ok(programs) = PROGRAMS.try_lock() else {
+        return;
+    };
+
+    for program in programs.values() {
+        // A program still used by another thread is left behind, to be
+        // removed with the directory of the run.
+        if let Ok(mut assert) = program.try_lock() {
+            assert.take();
+        }
+    }
+}
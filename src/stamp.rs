@@ -0,0 +1,171 @@
+//! The stamps of the executables, see `Assert::stamp`: the metadata of
+//! the snippet an executable has been compiled from, embedded into it,
+//! so that a stray executable, e.g. attached to a crash report or left
+//! in a build directory, can be traced back to its test with [`read`].
+//!
+//! The stamp is a C string, made of [`MARKER`], of a `format=1` line,
+//! of `key=value` lines and of [`END`], defined in the `.inline_c_rs`
+//! section, `__DATA,__inline_c_rs` on macOS, or `.icrs` with MSVC,
+//! whose section names are limited to 8 characters. It is found by its
+//! marker, whatever the format of the executable, and the occurrences
+//! of the marker which are not followed by a whole stamp, e.g. the
+//! constant itself in the executables linking this crate, are skipped.
+
+use std::{fs, io, path::Path};
+
+/// The first line of a stamp.
+pub const MARKER: &str = "INLINE_C_RS_STAMP\n";
+
+/// The last line of a stamp.
+pub const END: &str = "INLINE_C_RS_STAMP_END\n";
+
+/// The version of the format of the stamps.
+const FORMAT: &str = "1";
+
+/// The metadata of a snippet, embedded into its executable.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stamp {
+    /// The hash of the source of the snippet, before its templates are
+    /// rendered.
+    pub hash: String,
+
+    /// The name of the snippet, given by the `NAME` variable.
+    pub name: Option<String>,
+
+    /// The test binary and the test compiling the snippet, e.g.
+    /// `integration` and `tests::linked_list`.
+    pub binary: Option<String>,
+    pub test: Option<String>,
+
+    /// The compiler, its version, if it could be found, and its flags.
+    pub compiler: String,
+    pub version: Option<String>,
+    pub flags: Vec<String>,
+}
+
+impl Stamp {
+    /// The C source defining the stamp, appended to the source of the
+    /// program.
+    pub(crate) fn source(&self) -> String {
+        let mut lines = vec![
+            ("format", FORMAT),
+            ("hash", self.hash.as_str()),
+            ("compiler", self.compiler.as_str()),
+        ];
+        lines.extend(self.name.as_deref().map(|name| ("name", name)));
+        lines.extend(self.binary.as_deref().map(|binary| ("binary", binary)));
+        lines.extend(self.test.as_deref().map(|test| ("test", test)));
+        lines.extend(self.version.as_deref().map(|version| ("version", version)));
+        lines.extend(self.flags.iter().map(|flag| ("flag", flag.as_str())));
+
+        // One literal per line, since MSVC limits their length.
+        let literals = std::iter::once(c_literal(MARKER))
+            .chain(
+                lines
+                    .into_iter()
+                    .map(|(key, value)| c_literal(&format!("{}={}\n", key, value))),
+            )
+            .chain(std::iter::once(c_literal(END)))
+            .map(|literal| format!("    {}\n", literal))
+            .collect::<String>();
+
+        format!(
+            r#"
+#if defined(_MSC_VER)
+#pragma section(".icrs", read)
+#define INLINE_C_RS_STAMP_SECTION __declspec(allocate(".icrs"))
+#if defined(_M_IX86)
+#pragma comment(linker, "/include:_inline_c_rs_stamp")
+#else
+#pragma comment(linker, "/include:inline_c_rs_stamp")
+#endif
+#elif defined(__APPLE__)
+#define INLINE_C_RS_STAMP_SECTION __attribute__((used, section("__DATA,__inline_c_rs")))
+#else
+#define INLINE_C_RS_STAMP_SECTION __attribute__((used, section(".inline_c_rs")))
+#endif
+
+#ifdef __cplusplus
+extern "C"
+#endif
+INLINE_C_RS_STAMP_SECTION const char inline_c_rs_stamp[] =
+{};
+"#,
+            literals.trim_end()
+        )
+    }
+
+    /// Parse the lines of a stamp, between its marker and its end, if
+    /// they are `key=value` lines starting with the known format.
+    fn parse(lines: &str) -> Option<Self> {
+        let mut stamp = Self::default();
+        let mut lines = lines.lines();
+
+        if lines.next()? != format!("format={}", FORMAT) {
+            return None;
+        }
+
+        for line in lines {
+            let (key, value) = line.split_once('=')?;
+            let value = value.to_string();
+
+            match key {
+                "hash" => stamp.hash = value,
+                "name" => stamp.name = Some(value),
+                "binary" => stamp.binary = Some(value),
+                "test" => stamp.test = Some(value),
+                "compiler" => stamp.compiler = value,
+                "version" => stamp.version = Some(value),
+                "flag" => stamp.flags.push(value),
+                // The keys of the later versions.
+                _ => (),
+            }
+        }
+
+        Some(stamp)
+    }
+}
+
+/// The stamp of `executable`, if it has been stamped.
+pub fn read<P>(executable: P) -> io::Result<Option<Stamp>>
+where
+    P: AsRef<Path>,
+{
+    let bytes = fs::read(executable)?;
+    let marker = MARKER.as_bytes();
+
+    Ok(bytes
+        .windows(marker.len())
+        .enumerate()
+        .filter(|(_, window)| *window == marker)
+        .find_map(|(start, _)| {
+            let stamp = &bytes[start + marker.len()..];
+            let end = stamp.iter().position(|byte| *byte == 0)?;
+            let lines = std::str::from_utf8(&stamp[..end]).ok()?;
+
+            Stamp::parse(lines.strip_suffix(END)?)
+        }))
+}
+
+/// The C string literal of `value`, whose bytes other than the
+/// printable ASCII ones and the line feeds are escaped in octal. `?` is
+/// escaped too, so that no trigraph is formed.
+fn c_literal(value: &str) -> String {
+    let mut literal = String::from('"');
+
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' | b'?' => {
+                literal.push('\\');
+                literal.push(char::from(byte));
+            }
+            b'\n' => literal.push_str("\\n"),
+            b' '..=b'~' => literal.push(char::from(byte)),
+            _ => literal.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+
+    literal.push('"');
+
+    literal
+}
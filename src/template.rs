@@ -0,0 +1,34 @@
+//! Templating of the snippets.
+//!
+//! A snippet can contain `{{name}}` placeholders, replaced by the value
+//! of the `name` variable before the snippet is written, e.g. to
+//! instantiate one skeleton with different type or function names. The
+//! whitespace the macros insert between the braces is allowed, and the
+//! placeholders without a value are kept as they are, since `{{x}}` is
+//! also a valid C initializer.
+
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+
+/// Replace the placeholders of `source` by the value of their
+/// `variables`.
+pub(crate) fn render(source: &str, variables: &HashMap<String, String>) -> String {
+    lazy_static! {
+        static ref PLACEHOLDER: Regex =
+            Regex::new(r"\{\s*\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\s*\}").unwrap();
+    }
+
+    if variables.is_empty() {
+        return source.to_string();
+    }
+
+    PLACEHOLDER
+        .replace_all(source, |captures: &Captures| {
+            variables
+                .get(&captures[1])
+                .cloned()
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .into_owned()
+}
@@ -0,0 +1,84 @@
+//! Inspection of the WebAssembly modules compiled from snippets.
+
+use std::error::Error;
+use wasmparser::{Parser, Payload, Validator, WasmFeatures};
+
+/// Features that a module may require, by their proposal name.
+const FEATURES: &[(&str, WasmFeatures)] = &[
+    ("mutable-global", WasmFeatures::MUTABLE_GLOBAL),
+    (
+        "saturating-float-to-int",
+        WasmFeatures::SATURATING_FLOAT_TO_INT,
+    ),
+    ("sign-extension", WasmFeatures::SIGN_EXTENSION),
+    ("reference-types", WasmFeatures::REFERENCE_TYPES),
+    ("multi-value", WasmFeatures::MULTI_VALUE),
+    ("bulk-memory", WasmFeatures::BULK_MEMORY),
+    ("simd", WasmFeatures::SIMD),
+    ("relaxed-simd", WasmFeatures::RELAXED_SIMD),
+    ("threads", WasmFeatures::THREADS),
+    ("tail-call", WasmFeatures::TAIL_CALL),
+    ("multi-memory", WasmFeatures::MULTI_MEMORY),
+    ("exceptions", WasmFeatures::EXCEPTIONS),
+    ("extended-const", WasmFeatures::EXTENDED_CONST),
+    ("memory64", WasmFeatures::MEMORY64),
+];
+
+/// The shape of a WebAssembly module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmModule {
+    /// The names of the exports.
+    pub exports: Vec<String>,
+
+    /// The imports, as `(module, name)` pairs.
+    pub imports: Vec<(String, String)>,
+
+    /// The features the module cannot be validated without, e.g.
+    /// `bulk-memory`.
+    pub required_features: Vec<&'static str>,
+}
+
+impl WasmModule {
+    /// Validate and inspect a WebAssembly module.
+    pub fn parse(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Validator::new().validate_all(bytes)?;
+
+        let mut exports = Vec::new();
+        let mut imports = Vec::new();
+
+        for payload in Parser::new(0).parse_all(bytes) {
+            match payload? {
+                Payload::ExportSection(reader) => {
+                    for export in reader {
+                        exports.push(export?.name.to_string());
+                    }
+                }
+
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        let import = import?;
+                        imports.push((import.module.to_string(), import.name.to_string()));
+                    }
+                }
+
+                _ => (),
+            }
+        }
+
+        let required_features = FEATURES
+            .iter()
+            .filter(|(_, feature)| {
+                Validator::new_with_features(WasmFeatures::default() - *feature)
+                    .validate_all(bytes)
+                    .is_err()
+            })
+            .map(|(name, _)| *name)
+            .collect();
+
+        Ok(Self {
+            exports,
+            imports,
+            required_features,
+        })
+    }
+}
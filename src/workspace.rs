@@ -0,0 +1,193 @@
+//! Linking against the Rust libraries of the workspace under test.
+//!
+//! [`Library::resolve`] builds a crate of the workspace with `cargo
+//! build`, and finds its `staticlib`, or else its `cdylib`, in the
+//! artifact messages of Cargo, so that the tests do not hard-code the
+//! paths of the target directory. The headers are looked up in the
+//! `include` directory of the crate, or in its root.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::{
+    env,
+    error::Error,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The system libraries the Rust standard library depends on, which
+/// must be linked with a `staticlib`, see `rustc --print
+/// native-static-libs`.
+const LINUX_NATIVE_LIBRARIES: &[&str] = &["-lgcc_s", "-lutil", "-lrt", "-lpthread", "-lm", "-ldl"];
+const APPLE_NATIVE_LIBRARIES: &[&str] = &["-liconv", "-lSystem", "-lc", "-lm"];
+const MSVC_NATIVE_LIBRARIES: &[&str] = &[
+    "kernel32.lib",
+    "advapi32.lib",
+    "ntdll.lib",
+    "userenv.lib",
+    "ws2_32.lib",
+    "bcrypt.lib",
+    "dbghelp.lib",
+];
+
+/// A library built from a crate of the workspace.
+#[derive(Debug, Clone)]
+pub(crate) struct Library {
+    /// The directory of the headers.
+    include: PathBuf,
+
+    /// The static library, or the import library of the dynamic one on
+    /// Windows.
+    path: PathBuf,
+
+    /// Whether the library is a `cdylib`.
+    dynamic: bool,
+}
+
+impl Library {
+    /// Build the library of the crate `name`, with the profile of the
+    /// tests.
+    pub(crate) fn resolve(name: &str) -> Result<Self, Box<dyn Error>> {
+        lazy_static! {
+            static ref KIND: Regex = Regex::new(r#""kind":\[([^\]]*)\]"#).unwrap();
+            static ref NAME: Regex = Regex::new(r#""name":"([^"]*)""#).unwrap();
+            static ref MANIFEST_PATH: Regex =
+                Regex::new(r#""manifest_path":"((?:[^"\\]|\\.)*)""#).unwrap();
+            static ref FILENAMES: Regex = Regex::new(r#""filenames":\[([^\]]*)\]"#).unwrap();
+            static ref STRING: Regex = Regex::new(r#""((?:[^"\\]|\\.)*)""#).unwrap();
+        }
+
+        let mut command = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()));
+        command.args(["build", "--message-format=json", "--package", name]);
+
+        if !cfg!(debug_assertions) {
+            command.arg("--release");
+        }
+
+        let output = command.output()?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "failed to build the crate `{}`:\n{}",
+                name,
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .into());
+        }
+
+        let unescape = |string: &str| string.replace("\\\\", "\\");
+        let target_name = name.replace('-', "_");
+        let stdout = String::from_utf8(output.stdout)?;
+
+        let artifact = stdout
+            .lines()
+            .filter(|line| line.contains(r#""reason":"compiler-artifact""#))
+            .find(|line| {
+                NAME.captures(line)
+                    .is_some_and(|name| name[1] == target_name)
+            })
+            .ok_or_else(|| format!("the crate `{}` is not in the workspace", name))?;
+
+        let kinds = KIND
+            .captures(artifact)
+            .map_or("", |kinds| kinds.get(1).unwrap().as_str());
+        let filenames = FILENAMES
+            .captures(artifact)
+            .map(|filenames| {
+                STRING
+                    .captures_iter(filenames.get(1).unwrap().as_str())
+                    .map(|filename| PathBuf::from(unescape(&filename[1])))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let is_static = |path: &&PathBuf| {
+            let file_name = path.to_string_lossy();
+
+            (file_name.ends_with(".a") || file_name.ends_with(".lib"))
+                && !file_name.ends_with(".dll.lib")
+        };
+        let is_dynamic = |path: &&PathBuf| {
+            let file_name = path.to_string_lossy();
+
+            [".so", ".dylib", ".dll.lib", ".dll.a"]
+                .iter()
+                .any(|extension| file_name.ends_with(extension))
+        };
+
+        let (path, dynamic) = if kinds.contains(r#""staticlib""#) {
+            (filenames.iter().find(is_static), false)
+        } else if kinds.contains(r#""cdylib""#) {
+            (filenames.iter().find(is_dynamic), true)
+        } else {
+            (None, false)
+        };
+        let path = path
+            .ok_or_else(|| format!("the crate `{}` has no `staticlib` or `cdylib`", name))?
+            .clone();
+
+        let root = MANIFEST_PATH
+            .captures(artifact)
+            .map(|manifest_path| PathBuf::from(unescape(&manifest_path[1])))
+            .and_then(|manifest_path| manifest_path.parent().map(Path::to_path_buf))
+            .ok_or_else(|| format!("the crate `{}` has no manifest", name))?;
+        let include = if root.join("include").is_dir() {
+            root.join("include")
+        } else {
+            root
+        };
+
+        Ok(Self {
+            include,
+            path,
+            dynamic,
+        })
+    }
+
+    /// The flags to compile with the library, and to link it.
+    pub(crate) fn flags(&self, msvc: bool, target: &str) -> Vec<OsString> {
+        let mut include = OsString::from("-I");
+        include.push(&self.include);
+
+        let mut flags = vec![include, self.path.clone().into_os_string()];
+
+        if !self.dynamic {
+            let native_libraries = if msvc {
+                MSVC_NATIVE_LIBRARIES
+            } else if target.contains("apple") {
+                APPLE_NATIVE_LIBRARIES
+            } else if target.contains("linux") {
+                LINUX_NATIVE_LIBRARIES
+            } else {
+                &[]
+            };
+
+            flags.extend(native_libraries.iter().map(OsString::from));
+        }
+
+        flags
+    }
+
+    /// The directory of the dynamic library, where the program must
+    /// find it when it runs.
+    pub(crate) fn runtime_directory(&self) -> Option<&Path> {
+        if self.dynamic {
+            self.path.parent()
+        } else {
+            None
+        }
+    }
+}
+
+/// The variable listing the directories of the dynamic libraries on
+/// `target`.
+pub(crate) fn library_path_variable(target: &str) -> &'static str {
+    if target.contains("windows") {
+        "PATH"
+    } else if target.contains("apple") {
+        "DYLD_LIBRARY_PATH"
+    } else {
+        "LD_LIBRARY_PATH"
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+use wasmer_inline_c::assert_asm;
+
+#[test]
+fn test_assert_asm() {
+    (assert_asm!(
+        r#"
+        .globl inline_c_main
+        inline_c_main:
+            movl $7, %eax
+            ret
+
+        .section .note.GNU-stack,"",@progbits
+        "#
+    ))
+    .failure()
+    .code(7);
+}
+
+#[test]
+fn test_assert_asm_entry_and_driver() {
+    (assert_asm!(
+        r#"
+        #inline_c_rs ENTRY: "answer"
+        .globl answer
+        answer:
+            movl $42, %eax
+            ret
+
+        .section .note.GNU-stack,"",@progbits
+        "#
+    ))
+    .failure()
+    .code(42);
+
+    (assert_asm!(
+        driver = "#include <stdio.h>\nint answer(void);\nint main(void) { printf(\"%d\\n\", answer()); return 0; }\n",
+        r#"
+        .globl answer
+        answer:
+            movl $42, %eax
+            ret
+
+        .section .note.GNU-stack,"",@progbits
+        "#
+    ))
+    .success()
+    .stdout("42\n");
+}
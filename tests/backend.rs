@@ -0,0 +1,211 @@
+//! The backend is picked from the family of the compiler given by the
+//! `CC` and `CXX` variables. Each compiler is checked by its predefined
+//! macros, and skipped when it is not installed.
+
+use std::{env, process::Command};
+use wasmer_inline_c::{assert_c, assert_cxx};
+
+fn installed(program: &str, version: &[&str]) -> bool {
+    let installed = Command::new(program).args(version).output().is_ok();
+
+    if !installed {
+        eprintln!("Skipped: `{}` is not installed", program);
+    }
+
+    installed
+}
+
+#[test]
+fn test_gcc() {
+    if !installed("gcc", &["--version"]) {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "gcc"
+
+        int main() {
+        #if defined(__GNUC__) && !defined(__clang__)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_gxx() {
+    if !installed("g++", &["--version"]) {
+        return;
+    }
+
+    (assert_cxx! {
+        #inline_c_rs CXX: "g++"
+
+        int main() {
+        #if defined(__GNUC__) && !defined(__clang__)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_clang() {
+    if !installed("clang", &["--version"]) {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "clang"
+
+        int main() {
+        #ifdef __clang__
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_clangxx() {
+    if !installed("clang++", &["--version"]) {
+        return;
+    }
+
+    (assert_cxx! {
+        #inline_c_rs CXX: "clang++"
+
+        int main() {
+        #ifdef __clang__
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_msvc() {
+    // MSVC links the snippets against the `.dll` of the `LDFLAGS`.
+    if !installed("cl", &[]) || env::var_os("INLINE_C_RS_LDFLAGS").is_none() {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "cl"
+
+        int main() {
+        #if defined(_MSC_VER) && !defined(__clang__)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_intel() {
+    if !installed("icx", &["--version"]) {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "icx"
+
+        int main() {
+        #ifdef __INTEL_LLVM_COMPILER
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_nvidia() {
+    if !installed("nvc", &["--version"]) {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "nvc"
+
+        int main() {
+        #ifdef __NVCOMPILER
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_tiny() {
+    if !installed("tcc", &["-v"]) {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "tcc"
+
+        int main() {
+        #ifdef __TINYC__
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_tcc_variable() {
+    // C++ is still compiled by the C++ compiler.
+    (assert_cxx! {
+        #inline_c_rs TCC: "1"
+
+        int main() {
+        #ifdef __TINYC__
+            return 1;
+        #else
+            return 0;
+        #endif
+        }
+    })
+    .success();
+
+    if env::var_os("CI").is_some() || !installed("tcc", &["-v"]) {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs TCC: "1"
+
+        int main() {
+        #ifdef __TINYC__
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
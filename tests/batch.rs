@@ -0,0 +1,32 @@
+use std::env;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_batch() {
+    env::set_var("INLINE_C_RS_BATCH", "1");
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("first\n");
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout("first\n");
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("second\n");
+
+            return 2;
+        }
+    })
+    .failure()
+    .code(2)
+    .stdout("second\n");
+}
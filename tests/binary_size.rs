@@ -0,0 +1,28 @@
+use std::{fs, panic};
+use wasmer_inline_c::{run, Assert, Language};
+
+fn minimal() -> Assert {
+    run(Language::C, "int main() {\n    return 0;\n}\n").unwrap()
+}
+
+#[test]
+fn test_max_binary_size() {
+    let size = fs::metadata(minimal().executable()).unwrap().len();
+
+    minimal().max_binary_size(size).success();
+    assert!(panic::catch_unwind(|| {
+        minimal().max_binary_size(size - 1);
+    })
+    .is_err());
+}
+
+#[test]
+fn test_max_stripped_binary_size() {
+    let size = fs::metadata(minimal().executable()).unwrap().len();
+
+    minimal().max_stripped_binary_size(size).success();
+    assert!(panic::catch_unwind(|| {
+        minimal().max_stripped_binary_size(1);
+    })
+    .is_err());
+}
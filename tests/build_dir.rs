@@ -0,0 +1,68 @@
+use std::{path::Path, process};
+use wasmer_inline_c::{assert_c, run, Language};
+
+#[test]
+fn test_build_directory() {
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+    assert.success();
+
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .parent()
+        .unwrap()
+        .join("inline-c")
+        .join("build_dir")
+        .join(format!("run-{}", process::id()));
+    let artifacts = assert.artifacts().to_vec();
+
+    assert!(!artifacts.is_empty());
+    assert!(
+        artifacts
+            .iter()
+            .all(|artifact| artifact.parent() == Some(&directory)),
+        "{:?} are not in {}",
+        artifacts,
+        directory.display()
+    );
+
+    drop(assert);
+
+    assert!(artifacts.iter().all(|artifact| !artifact.exists()));
+}
+
+#[test]
+fn test_artifact_dir() {
+    let directory = tempfile::tempdir().unwrap();
+
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+    assert.artifact_dir(directory.path()).success();
+
+    assert!(!assert.artifacts().is_empty());
+    assert!(assert
+        .artifacts()
+        .iter()
+        .all(|artifact| artifact.parent() == Some(directory.path())));
+
+    let mut assert = run(
+        Language::C,
+        &format!(
+            "#inline_c_rs ARTIFACT_DIR: \"{}\"\n\nint main() {{\n    return 0;\n}}\n",
+            directory.path().join("variable").display()
+        ),
+    )
+    .unwrap();
+    assert.success();
+
+    assert!(!assert.artifacts().is_empty());
+    assert!(assert
+        .artifacts()
+        .iter()
+        .all(|artifact| artifact.parent() == Some(&directory.path().join("variable"))));
+}
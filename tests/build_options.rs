@@ -0,0 +1,83 @@
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_freestanding() {
+    // Neither linked nor run, so that it needs no `main`.
+    (assert_c! {
+        #include <stdint.h>
+
+        uint32_t add(uint32_t a, uint32_t b) {
+            return a + b;
+        }
+    })
+    .freestanding()
+    .success();
+
+    (assert_c! {
+        int add(int a, int b) {
+            return a + c;
+        }
+    })
+    .freestanding()
+    .failure();
+}
+
+#[test]
+fn test_openmp() {
+    (assert_c! {
+        #include <omp.h>
+        #include <stdio.h>
+
+        int main() {
+            int sum = 0;
+
+            #pragma omp parallel for reduction(+:sum)
+            for (int i = 1; i <= 100; ++i) {
+                sum += i;
+            }
+
+            printf("%d %d\n", _OPENMP > 0, sum);
+
+            return omp_get_max_threads() > 0 ? 0 : 1;
+        }
+    })
+    .openmp(true)
+    .success()
+    .stdout("1 5050\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_threads_and_math() {
+    (assert_c! {
+        #include <math.h>
+        #include <pthread.h>
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        static void *root(void *value) {
+            *(double *) value = sqrt(*(double *) value);
+
+            return NULL;
+        }
+
+        int main(int argc, char **argv) {
+            pthread_t thread;
+            double value = atof(argv[argc - 1]);
+
+            if (pthread_create(&thread, NULL, root, &value) != 0) {
+                return 1;
+            }
+
+            pthread_join(thread, NULL);
+            printf("%g\n", value);
+
+            return 0;
+        }
+    })
+    .threads(true)
+    .math(true)
+    .arg("2.25")
+    .success()
+    .stdout("1.5\n");
+}
@@ -0,0 +1,53 @@
+use std::{fs, path::Path};
+use wasmer_inline_c::{run, Language};
+
+fn hello(cache: &Path, greeting: &str) -> bool {
+    let mut assert = run(
+        Language::C,
+        &format!(
+            "#include <stdio.h>\n\nint main() {{\n    printf(\"{}\\n\");\n\n    return 0;\n}}\n",
+            greeting
+        ),
+    )
+    .unwrap();
+    assert.cache(cache);
+    assert.success().stdout(format!("{}\n", greeting));
+
+    assert.cache_hit()
+}
+
+#[test]
+fn test_cache() {
+    let cache = tempfile::tempdir().unwrap();
+
+    assert!(!hello(cache.path(), "Hello"));
+    assert!(hello(cache.path(), "Hello"));
+    assert!(!hello(cache.path(), "Goodbye"));
+}
+
+#[test]
+fn test_cache_of_the_headers() {
+    let cache = tempfile::tempdir().unwrap();
+    let include = tempfile::tempdir().unwrap();
+    let program = format!(
+        "#inline_c_rs CFLAGS: \"-I{}\"\n\n#include \"value.h\"\n\nint main() {{\n    return VALUE;\n}}\n",
+        include.path().display()
+    );
+    let run_with_value = |value: i32| {
+        fs::write(
+            include.path().join("value.h"),
+            format!("#define VALUE {}\n", value),
+        )
+        .unwrap();
+
+        let mut assert = run(Language::C, &program).unwrap();
+        assert.cache(cache.path());
+        assert.failure().code(value);
+
+        assert.cache_hit()
+    };
+
+    assert!(!run_with_value(1));
+    assert!(run_with_value(1));
+    assert!(!run_with_value(2));
+}
@@ -0,0 +1,132 @@
+//! `cargo` is replaced by a script compiling nothing and reporting a
+//! temporary target directory, holding a hand-written manifest.
+
+#![cfg(all(feature = "cli", unix))]
+
+use std::{
+    fs,
+    io::{prelude::*, BufReader},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::{Command, Output, Stdio},
+    time::{Duration, SystemTime},
+};
+
+const CARGO_INLINE_C: &str = env!("CARGO_BIN_EXE_cargo-inline-c");
+
+const HELLO: &str = "#include <stdio.h>\n\
+                     int main() { printf(\"Hello\\n\"); return 3; }\n";
+
+const WORLD: &str = "#include <stdio.h>\n\
+                     int main() { printf(\"World\\n\"); return 3; }\n";
+
+/// A package in `directory`, whose fake `cargo` records `hello.c` in
+/// the manifest of the `demo` package.
+fn package(directory: &Path) {
+    let target = directory.join("target");
+    let manifest = target.join("inline-c").join("manifest").join("demo");
+    fs::create_dir_all(&manifest).unwrap();
+    fs::write(manifest.join("hello.c"), HELLO).unwrap();
+
+    fs::create_dir(directory.join("src")).unwrap();
+    fs::write(directory.join("src").join("lib.rs"), "").unwrap();
+
+    let cargo = directory.join("cargo");
+    fs::write(
+        &cargo,
+        format!(
+            "#!/bin/sh\n\
+             if [ \"$1\" = metadata ]; then\n\
+             \techo '{{\"target_directory\":\"{}\"}}'\n\
+             fi\n",
+            target.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+fn cargo_inline_c(directory: &Path) -> Command {
+    let mut command = Command::new(CARGO_INLINE_C);
+    command
+        .arg("inline-c")
+        .current_dir(directory)
+        .env("CARGO", directory.join("cargo"));
+
+    command
+}
+
+fn output(directory: &Path, arguments: &[&str]) -> Output {
+    cargo_inline_c(directory).args(arguments).output().unwrap()
+}
+
+#[test]
+fn test_list_show_run() {
+    let directory = tempfile::tempdir().unwrap();
+    package(directory.path());
+
+    let list = output(directory.path(), &["list"]);
+    assert!(list.status.success());
+    assert_eq!(String::from_utf8_lossy(&list.stdout), "demo/hello\n");
+
+    let show = output(directory.path(), &["show", "demo/hello"]);
+    assert!(show.status.success());
+    assert_eq!(String::from_utf8_lossy(&show.stdout), HELLO);
+
+    let run = output(directory.path(), &["run", "hello", "--verbose"]);
+    assert_eq!(run.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&run.stdout), "Hello\n");
+    assert!(String::from_utf8_lossy(&run.stderr).starts_with("=== demo/hello (c)\n"));
+
+    let unknown = output(directory.path(), &["run", "goodbye"]);
+    assert_eq!(unknown.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&unknown.stderr).contains("no snippet named `goodbye`"));
+
+    let usage = output(directory.path(), &[]);
+    assert_eq!(usage.status.code(), Some(2));
+}
+
+#[test]
+fn test_watch() {
+    let directory = tempfile::tempdir().unwrap();
+    package(directory.path());
+
+    let mut child = cargo_inline_c(directory.path())
+        .args(["watch", "hello"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+    let mut stderr = BufReader::new(child.stderr.take().unwrap()).lines();
+    assert_eq!(stdout.next().unwrap().unwrap(), "Hello");
+
+    // The sources must change after the watcher read their stamps.
+    stderr
+        .find(|line| line.as_ref().unwrap().starts_with("=== watching "))
+        .unwrap()
+        .unwrap();
+
+    fs::write(
+        directory
+            .path()
+            .join("target/inline-c/manifest/demo/hello.c"),
+        WORLD,
+    )
+    .unwrap();
+    fs::File::options()
+        .write(true)
+        .open(directory.path().join("src").join("lib.rs"))
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(60))
+        .unwrap();
+
+    let diff = [stdout.next(), stdout.next()];
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_eq!(diff[0].as_ref().unwrap().as_ref().unwrap(), "+ World");
+    assert_eq!(diff[1].as_ref().unwrap().as_ref().unwrap(), "- Hello");
+}
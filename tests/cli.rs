@@ -0,0 +1,59 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::prelude::*,
+    process::{Command, Stdio},
+};
+
+const INLINE_C: &str = env!("CARGO_BIN_EXE_inline-c");
+
+#[test]
+fn test_stdin() {
+    let mut child = Command::new(INLINE_C)
+        .arg("--cxx")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            b"#include <iostream>\nint main() { std::cout << \"Hello\" << std::endl; return 3; }\n",
+        )
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(output.stdout, b"Hello\n");
+}
+
+#[test]
+fn test_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let file = directory.path().join("hello.c");
+    std::fs::write(
+        &file,
+        "#include <stdio.h>\nint main() { printf(\"Hello\\n\"); return 0; }\n",
+    )
+    .unwrap();
+
+    let output = Command::new(INLINE_C).arg(&file).output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"Hello\n");
+}
+
+#[test]
+fn test_unexpected_argument() {
+    let output = Command::new(INLINE_C)
+        .args(["a.c", "b.c"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unexpected argument `b.c`"));
+}
@@ -0,0 +1,48 @@
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_same_behavior() {
+    (assert_c! {
+        #inline_c_rs COMPARE_OPTIMIZATIONS: "1"
+
+        #include <stdio.h>
+
+        int main() {
+            printf("Hello, World!\n");
+
+            return 0;
+        }
+    })
+    .success();
+}
+
+#[cfg(not(target_env = "msvc"))]
+#[test]
+fn test_different_behavior() {
+    let different = std::panic::catch_unwind(|| {
+        (assert_c! {
+            #include <stdio.h>
+
+            int main() {
+            #ifdef __OPTIMIZE__
+                printf("optimized\n");
+            #else
+                printf("unoptimized\n");
+            #endif
+
+                return 0;
+            }
+        })
+        .compare_optimizations(true)
+        .success();
+    })
+    .unwrap_err();
+    let message = different.downcast_ref::<String>().unwrap();
+
+    assert!(
+        message.starts_with("The program behaves differently with `-O0` and `-O2`:"),
+        "{}",
+        message
+    );
+    assert!(message.contains("unoptimized"), "{}", message);
+}
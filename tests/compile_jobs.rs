@@ -0,0 +1,61 @@
+//! The compiler is replaced by a script recording the compilations of
+//! the snippets overlapping each other, before calling `gcc`.
+
+#![cfg(target_os = "linux")]
+
+use std::{env, fs, os::unix::fs::PermissionsExt, thread};
+use wasmer_inline_c::{run, Language};
+
+/// Compile 4 snippets at once, at most `jobs` of them at the same time.
+fn compile(jobs: Option<usize>, tag: &str) {
+    thread::scope(|scope| {
+        for i in 0..4 {
+            scope.spawn(move || {
+                let mut assert = run(
+                    Language::C,
+                    &format!("int main() {{\n    return 0; /* {} {} */\n}}\n", tag, i),
+                )
+                .unwrap();
+
+                if let Some(jobs) = jobs {
+                    assert.compile_jobs(jobs);
+                }
+
+                assert.success();
+            });
+        }
+    });
+}
+
+#[test]
+fn test_compile_jobs() {
+    let directory = tempfile::tempdir().unwrap();
+    let overlaps = directory.path().join("overlaps");
+    let cc = directory.path().join("gcc");
+    fs::write(
+        &cc,
+        format!(
+            "#!/bin/sh\n\
+             case \"$*\" in\n\
+             *inline-c-rs*)\n\
+             \tmkdir {directory}/compiling 2>/dev/null || echo overlap >> {overlaps}\n\
+             \tsleep 0.5\n\
+             \trmdir {directory}/compiling 2>/dev/null;;\n\
+             esac\n\
+             exec gcc \"$@\"\n",
+            directory = directory.path().display(),
+            overlaps = overlaps.display(),
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&cc, fs::Permissions::from_mode(0o755)).unwrap();
+    env::set_var("INLINE_C_RS_CC", &cc);
+
+    compile(None, "unlimited");
+    assert!(overlaps.exists());
+
+    fs::remove_file(&overlaps).unwrap();
+
+    compile(Some(1), "limited");
+    assert!(!overlaps.exists());
+}
@@ -0,0 +1,54 @@
+use std::fs;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_compile_once_run_many() {
+    let mut assert = assert_c! {
+        #inline_c_rs GREETING: "Hello"
+
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main(int argc, char **argv) {
+            char name[32] = "";
+            scanf("%31s", name);
+
+            printf("%s %s", getenv("GREETING"), name);
+
+            for (int i = 1; i < argc; ++i) {
+                printf(" %s", argv[i]);
+            }
+
+            printf("\n");
+
+            return argc - 1;
+        }
+    };
+    assert.arg("--first");
+
+    let compiled = assert.compile();
+    let executable = compiled.executable().to_path_buf();
+    let modified = fs::metadata(&executable).unwrap().modified().unwrap();
+
+    compiled
+        .run()
+        .stdin("World")
+        .failure()
+        .code(1)
+        .stdout("Hello World --first\n");
+
+    compiled
+        .run()
+        .args(["--second", "--third"])
+        .env("GREETING", "Goodbye")
+        .stdin("Moon")
+        .failure()
+        .code(3)
+        .stdout("Goodbye Moon --first --second --third\n");
+
+    assert_eq!(compiled.executable(), executable);
+    assert_eq!(
+        fs::metadata(&executable).unwrap().modified().unwrap(),
+        modified
+    );
+}
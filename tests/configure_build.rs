@@ -0,0 +1,50 @@
+use std::fs;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_configure_build_flag_if_supported() {
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+        #ifdef CONFIGURED
+            printf("configured\n");
+        #endif
+
+            return 0;
+        }
+    })
+    .configure_build(|build| {
+        build
+            .flag_if_supported("-DCONFIGURED")
+            .flag_if_supported("-fthis-flag-does-not-exist");
+    })
+    .success()
+    .stdout("configured\n");
+}
+
+#[test]
+fn test_configure_build_repeated_flags() {
+    let first = tempfile::tempdir().unwrap();
+    let second = tempfile::tempdir().unwrap();
+    fs::write(first.path().join("first.h"), "#define FIRST 1\n").unwrap();
+    fs::write(second.path().join("second.h"), "#define SECOND 2\n").unwrap();
+
+    (assert_c! {
+        #include "first.h"
+        #include "second.h"
+
+        int main() {
+            return FIRST + SECOND;
+        }
+    })
+    .configure_build(move |build| {
+        build
+            .flag("-I")
+            .flag(first.path().to_str().unwrap())
+            .flag("-I")
+            .flag(second.path().to_str().unwrap());
+    })
+    .failure()
+    .code(3);
+}
@@ -0,0 +1,39 @@
+#![cfg(target_env = "msvc")]
+
+use std::panic;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_crt_leak_check() {
+    (assert_c! {
+        #include <stdlib.h>
+
+        int main() {
+            free(malloc(16));
+
+            return 0;
+        }
+    })
+    .crt_leak_check(true)
+    .success();
+
+    let leak = panic::catch_unwind(|| {
+        (assert_c! {
+            #include <stdlib.h>
+
+            int main() {
+                return malloc(16) == NULL;
+            }
+        })
+        .crt_leak_check(true)
+        .assert();
+    })
+    .unwrap_err();
+    let message = leak.downcast_ref::<String>().unwrap();
+
+    assert!(
+        message.starts_with("The CRT debug heap has detected memory leaks"),
+        "{}",
+        message
+    );
+}
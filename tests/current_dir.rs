@@ -0,0 +1,29 @@
+use std::fs;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_current_dir() {
+    let directory = tempfile::tempdir().unwrap();
+    fs::write(directory.path().join("input.txt"), "Hello\n").unwrap();
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            char line[16];
+            FILE *input = fopen("input.txt", "r");
+
+            if (input == NULL || fgets(line, sizeof(line), input) == NULL) {
+                return 1;
+            }
+
+            printf("%s", line);
+            fclose(input);
+
+            return 0;
+        }
+    })
+    .current_dir(directory.path())
+    .success()
+    .stdout("Hello\n");
+}
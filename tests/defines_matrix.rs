@@ -0,0 +1,72 @@
+use std::env;
+use wasmer_inline_c::assert_c;
+
+#[test]
+#[should_panic(expected = "1 of 4 configurations have failed")]
+fn test_defines_matrix_labels_the_failures() {
+    (assert_c! {
+        int main() {
+        #if defined(FIRST) && defined(SECOND)
+            return 1;
+        #else
+            return 0;
+        #endif
+        }
+    })
+    .defines_matrix(&["FIRST", "SECOND"], |assert| {
+        assert.success();
+    });
+}
+
+#[test]
+fn test_defines_matrix_ignores_the_host_cppflags() {
+    env::set_var("CPPFLAGS", "-DFROM_THE_HOST");
+
+    (assert_c! {
+        int main() {
+        #ifdef FROM_THE_HOST
+            return 1;
+        #else
+            return 0;
+        #endif
+        }
+    })
+    .defines_matrix(&["NDEBUG"], |assert| {
+        assert.success();
+    });
+}
+
+#[test]
+fn test_defines_matrix_keeps_the_options() {
+    (assert_c! {
+        int main() {
+        #if defined(_REENTRANT) || defined(NDEBUG)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .threads(true)
+    .defines_matrix(&["NDEBUG"], |assert| {
+        assert.success();
+    });
+}
+
+#[test]
+#[should_panic(expected = "too many configurations")]
+fn test_defines_matrix_rejects_large_matrices() {
+    let defines = (0..64)
+        .map(|index| format!("D{}", index))
+        .collect::<Vec<_>>();
+    let defines = defines.iter().map(String::as_str).collect::<Vec<_>>();
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .defines_matrix(&defines, |assert| {
+        assert.success();
+    });
+}
@@ -0,0 +1,37 @@
+//! The compiler is replaced by a script answering the probes of `cc`,
+//! and printing its version, like Clang.
+
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::PermissionsExt};
+use wasmer_inline_c::{
+    run::{detect_compiler, Family},
+    Language,
+};
+
+#[test]
+fn test_detect_compiler() {
+    let directory = tempfile::tempdir().unwrap();
+    let clang = directory.path().join("clang");
+    fs::write(
+        &clang,
+        "#!/bin/sh\n\
+         for arg; do\n\
+         \tcase \"$arg\" in\n\
+         \t--version) echo 'clang version 17.0.6 (https://github.com/llvm/llvm-project)'; exit 0;;\n\
+         \t'-?') exit 1;;\n\
+         \tesac\n\
+         done\n\
+         echo '#pragma message \"clang\"'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&clang, fs::Permissions::from_mode(0o755)).unwrap();
+    env::set_var("INLINE_C_RS_CC", &clang);
+
+    let compiler = detect_compiler(Language::C).unwrap();
+
+    assert_eq!(compiler.family, Family::Clang);
+    assert_eq!(compiler.path, clang);
+    assert_eq!(compiler.version.as_deref(), Some("17.0.6"));
+    assert!(compiler.target.starts_with(env::consts::ARCH));
+}
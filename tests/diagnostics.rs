@@ -0,0 +1,138 @@
+use std::fs;
+use wasmer_inline_c::{
+    assert_c,
+    diagnostic::{self, Diagnostic, Severity},
+    predicates::*,
+    run, Language,
+};
+
+#[test]
+fn test_parse() {
+    let diagnostics = diagnostic::parse(
+        "main.c: In function 'main':\n\
+         main.c:4:9: warning: unused variable 'x' [-Wunused-variable]\n\
+         \x20   4 |     int x;\n\
+         main.c(12,5): warning C4996: 'strcpy': This function may be unsafe.\n\
+         cl : Command line warning D9002 : ignoring unknown option '-Wall'\n",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic {
+                file: Some("main.c".to_string()),
+                line: Some(4),
+                column: Some(9),
+                severity: Severity::Warning,
+                code: Some("-Wunused-variable".to_string()),
+                message: "unused variable 'x'".to_string(),
+            },
+            Diagnostic {
+                file: Some("main.c".to_string()),
+                line: Some(12),
+                column: Some(5),
+                severity: Severity::Warning,
+                code: Some("C4996".to_string()),
+                message: "'strcpy': This function may be unsafe.".to_string(),
+            },
+            Diagnostic {
+                file: None,
+                line: None,
+                column: None,
+                severity: Severity::Warning,
+                code: Some("D9002".to_string()),
+                message: "ignoring unknown option '-Wall'".to_string(),
+            },
+        ]
+    );
+
+    assert!(diagnostic_code("C4996").eval(&diagnostics));
+    assert!(!diagnostic_code("C4013").eval(&diagnostics));
+    assert!(!no_warnings().eval(&diagnostics));
+    assert!(no_warnings().eval(&[]));
+}
+
+#[test]
+fn test_compile_diagnostics() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .diagnostics(no_warnings())
+    .success();
+
+    let mut assert = assert_c! {
+        #inline_c_rs CFLAGS: "-Wno-error"
+
+        int main() {
+            int unused;
+
+            return 0;
+        }
+    };
+
+    assert
+        .diagnostics(diagnostic_code("-Wunused-variable"))
+        .success();
+
+    let diagnostics = assert.compile_diagnostics();
+
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0].line.is_some());
+}
+
+#[test]
+fn test_deny_warnings() {
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-Wno-error"
+        #inline_c_rs DENY_WARNINGS: "implicit-function-declaration"
+
+        int main() {
+            int unused;
+
+            return 0;
+        }
+    })
+    .success();
+}
+
+#[test]
+#[should_panic(expected = "The compiler has reported denied warnings")]
+fn test_deny_warnings_failure() {
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-Wno-error"
+
+        int main() {
+            int unused;
+
+            return 0;
+        }
+    })
+    .deny_warnings(&["-Wunused-variable"])
+    .success();
+}
+
+#[test]
+fn test_system_include() {
+    let include = tempfile::tempdir().unwrap();
+    fs::write(
+        include.path().join("noisy.h"),
+        "static int unused_in_the_header(void) { return 0; }\n",
+    )
+    .unwrap();
+    let program = format!(
+        "#inline_c_rs CFLAGS: \"-I{}\"\n\n#include \"noisy.h\"\n\nint main() {{\n    return 0;\n}}\n",
+        include.path().display()
+    );
+
+    let mut assert = run(Language::C, &program).unwrap();
+    assert.diagnostics(diagnostic_code("-Werror=unused-function"));
+
+    run(Language::C, &program)
+        .unwrap()
+        .system_include(include.path())
+        .diagnostics(no_warnings())
+        .success();
+}
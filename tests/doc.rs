@@ -0,0 +1,84 @@
+use std::fs;
+use wasmer_inline_c::{doc, Language};
+
+const MARKDOWN: &str = r#"# Example
+
+```c
+#include <stdio.h>
+
+int main() {
+    printf("Hello, World!\n");
+
+    return 0;
+}
+```
+
+```cpp,ignore
+int main() {
+    return 1;
+}
+```
+
+```rust
+fn main() {}
+```
+"#;
+
+const RUST: &str = r#"
+/// Returns 0.
+///
+/// ```c
+/// int main() {
+///     return 0;
+/// }
+/// ```
+pub fn zero() {}
+
+// ```c
+// int main() { return 1; }
+// ```
+"#;
+
+#[test]
+fn test_extract_markdown() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("README.md");
+    fs::write(&path, MARKDOWN).unwrap();
+
+    let blocks = doc::extract(&path).unwrap();
+
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].line, 3);
+    assert_eq!(blocks[0].language, Language::C);
+    assert!(!blocks[0].ignore);
+    assert!(blocks[0].source.starts_with("#include <stdio.h>\n"));
+    assert_eq!(blocks[1].language, Language::Cxx);
+    assert!(blocks[1].ignore);
+
+    doc::assert_code_blocks(&[&path]);
+}
+
+#[test]
+fn test_extract_doc_comments() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("lib.rs");
+    fs::write(&path, RUST).unwrap();
+
+    let blocks = doc::extract(&path).unwrap();
+
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].line, 4);
+    assert_eq!(blocks[0].source, "int main() {\n    return 0;\n}\n");
+
+    doc::assert_code_blocks(&[&path]);
+}
+
+#[test]
+#[should_panic(expected = "README.md:1` has failed")]
+fn test_failing_code_block() {
+    let directory = tempfile::tempdir().unwrap();
+    let path = directory.path().join("README.md");
+    fs::write(&path, "```c\nint main() {\n    return 1;\n}\n```\n").unwrap();
+
+    doc::assert_code_blocks(&[&path]);
+}
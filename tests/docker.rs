@@ -0,0 +1,59 @@
+use predicates::prelude::*;
+use std::{path::PathBuf, process::Command};
+use wasmer_inline_c::{assert_c, runner::Runner};
+
+/// `echo` stands for the container engine, and prints the arguments it
+/// is given.
+#[cfg(unix)]
+#[test]
+fn test_docker_mounts() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::Docker {
+        program: PathBuf::from("echo"),
+        image: "alpine:3.12".to_string(),
+        mounts: vec![(PathBuf::from("/tmp/fixtures,v2"), "/fixtures".to_string())],
+    })
+    .success()
+    .stdout(
+        predicate::str::starts_with("run --rm --interactive --mount type=bind,source=/")
+            .and(predicate::str::contains(
+                ",target=/inline-c,readonly --mount",
+            ))
+            .and(predicate::str::contains(
+                r#"--mount type=bind,"source=/tmp/fixtures,v2",target=/fixtures --"#,
+            ))
+            .and(predicate::str::contains(
+                "--workdir /inline-c alpine:3.12 /inline-c/",
+            )),
+    );
+}
+
+#[test]
+fn test_docker() {
+    if !Command::new("docker")
+        .arg("info")
+        .output()
+        .is_ok_and(|output| output.status.success())
+    {
+        eprintln!("Skipped: no Docker daemon");
+
+        return;
+    }
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("Hello from the container\n");
+
+            return 0;
+        }
+    })
+    .runner(Runner::parse("docker://debian:stable-slim").unwrap())
+    .success()
+    .stdout("Hello from the container\n");
+}
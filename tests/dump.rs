@@ -0,0 +1,63 @@
+use std::{
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+};
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_success_or_dump() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .success_or_dump();
+
+    let mut assert = assert_c! {
+        #inline_c_rs GREETING: "hello"
+        #include <stdio.h>
+
+        int main() {
+            printf("before the failure\n");
+            fprintf(stderr, "the failure\n");
+
+            return 7;
+        }
+    };
+
+    let dump = panic::catch_unwind(AssertUnwindSafe(|| {
+        assert.success_or_dump();
+    }))
+    .unwrap_err()
+    .downcast::<String>()
+    .unwrap();
+
+    assert!(dump.starts_with("The program has failed.\n\n=== source `"));
+    assert!(dump.contains("   1 | "));
+    assert!(dump.contains("\n=== compiler command\n"));
+    assert!(dump.contains("\nGREETING=hello\n"));
+    assert!(dump.contains("\n=== exit status\nexit status: 7\n"));
+    assert!(dump.contains("\n=== stdout\nbefore the failure\n"));
+    assert!(dump.contains("\n=== stderr\nthe failure\n"));
+
+    let artifacts = dump
+        .split("\n=== artifacts (kept)\n")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+
+    assert!(!artifacts.is_empty());
+    assert!(artifacts
+        .iter()
+        .all(|artifact| assert.artifacts().contains(artifact)));
+
+    drop(assert);
+
+    for artifact in artifacts {
+        assert!(artifact.exists());
+        fs::remove_file(artifact).unwrap();
+    }
+}
@@ -0,0 +1,50 @@
+use wasmer_inline_c::{assert_c, run, Language};
+
+#[test]
+fn test_entry() {
+    (assert_c! {
+        #inline_c_rs ENTRY: "example_main"
+
+        #include <stdio.h>
+
+        int example_main(int argc, char **argv) {
+            printf("%d %s %d\n", argc, argv[1], __LINE__);
+
+            return 5;
+        }
+    })
+    .arg("--verbose")
+    .failure()
+    .code(5)
+    .stdout("2 --verbose 5\n");
+
+    // The renaming does not shift the lines of the snippet.
+    (assert_c! {
+        #inline_c_rs GREETING: "Hello"
+
+        #include <stdio.h>
+
+        int main() {
+            printf("%d\n", __LINE__);
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout("5\n");
+}
+
+#[test]
+fn test_invalid_entry() {
+    let Err(error) = run(
+        Language::C,
+        "#inline_c_rs ENTRY: \"example main\"\n\nint main() {\n    return 0;\n}\n",
+    ) else {
+        panic!("the `ENTRY` variable is invalid");
+    };
+
+    assert_eq!(
+        error.to_string(),
+        "invalid `ENTRY` variable `example main`, expected the name of a function"
+    );
+}
@@ -0,0 +1,39 @@
+use std::{env, fs};
+use wasmer_inline_c::{assert_c, run, Language};
+
+#[test]
+fn test_env_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let file = directory.path().join("inline-c.env");
+    fs::write(
+        &file,
+        "# Machine-specific paths.\nCFLAGS=-DFROM_FILE=1\n\nexport INLINE_C_RS_GREETING=\"hello there\"\nFAREWELL=bye\n",
+    )
+    .unwrap();
+    env::set_var("INLINE_C_RS_ENV_FILE", &file);
+    env::set_var("INLINE_C_RS_FAREWELL", "goodbye");
+
+    (assert_c! {
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main() {
+            printf("%d %s, %s\n", FROM_FILE, getenv("GREETING"), getenv("FAREWELL"));
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout("1 hello there, goodbye\n");
+
+    env::set_var("INLINE_C_RS_ENV_FILE", directory.path().join("missing.env"));
+    let error = run(Language::C, "int main() {\n    return 0;\n}\n")
+        .err()
+        .unwrap();
+
+    assert!(
+        error.to_string().starts_with("failed to read `"),
+        "{}",
+        error
+    );
+}
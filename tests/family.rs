@@ -0,0 +1,51 @@
+//! `nvc` stands for a wrapper of GCC recording its arguments, and
+//! spelling the NVIDIA HPC flags for GCC.
+
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::PermissionsExt};
+use wasmer_inline_c::{
+    assert_c,
+    run::{detect_compiler, Family},
+    Language,
+};
+
+#[test]
+fn test_nvidia_flags() {
+    let directory = tempfile::tempdir().unwrap();
+    let nvc = directory.path().join("nvc");
+    let log = directory.path().join("args");
+    fs::write(
+        &nvc,
+        format!(
+            "#!/bin/sh\necho \"$@\" >> {}\nfor arg; do\n    shift\n    [ \"$arg\" = -mp ] && arg=-fopenmp\n    set -- \"$@\" \"$arg\"\ndone\nexec gcc \"$@\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&nvc, fs::Permissions::from_mode(0o755)).unwrap();
+    env::set_var("INLINE_C_RS_CC", &nvc);
+
+    assert_eq!(detect_compiler(Language::C).unwrap().family, Family::Nvidia);
+
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-Wall -Wextra"
+
+        int main() {
+            return 0;
+        }
+    })
+    .openmp(true)
+    .success();
+
+    let log = fs::read_to_string(&log).unwrap();
+    let compilation = log
+        .lines()
+        .find(|line| line.contains("/inline-c-rs-"))
+        .unwrap();
+
+    assert!(compilation.ends_with(" -mp"), "{}", compilation);
+    assert!(compilation.contains(" -Wall "), "{}", compilation);
+    assert!(!compilation.contains("-Wextra"), "{}", compilation);
+    assert!(!compilation.contains("-fopenmp"), "{}", compilation);
+}
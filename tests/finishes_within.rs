@@ -0,0 +1,57 @@
+use std::time::Duration;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_finishes_within() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .finishes_within(Duration::from_secs(10))
+    .success();
+
+    (assert_c! {
+        #inline_c_rs FINISHES_WITHIN: "10"
+
+        int main() {
+            return 0;
+        }
+    })
+    .success();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_finishes_too_late() {
+    let late = std::panic::catch_unwind(|| {
+        (assert_c! {
+            #include <stdio.h>
+            #include <unistd.h>
+
+            int main() {
+                usleep(200000);
+                printf("done\n");
+
+                return 0;
+            }
+        })
+        .finishes_within(Duration::from_millis(50))
+        .assert();
+    })
+    .unwrap_err();
+    let message = late.downcast_ref::<String>().unwrap();
+
+    // The program has not been killed.
+    assert!(
+        message.starts_with("The program has run for"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("more than the 50ms it must finish within"),
+        "{}",
+        message
+    );
+    assert!(message.contains("done"), "{}", message);
+}
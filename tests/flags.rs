@@ -0,0 +1,45 @@
+use std::env;
+use wasmer_inline_c::{assert_c, assert_cxx};
+
+#[test]
+fn test_flags_are_routed_by_language() {
+    env::set_var("INLINE_C_RS_CPPFLAGS", "-DBOTH");
+    env::set_var("INLINE_C_RS_CFLAGS", "-DC_ONLY");
+    env::set_var("INLINE_C_RS_CXXFLAGS", "-DCXX_ONLY");
+
+    (assert_c! {
+        int main() {
+        #if defined(BOTH) && defined(C_ONLY) && !defined(CXX_ONLY)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+
+    (assert_cxx! {
+        int main() {
+        #if defined(BOTH) && defined(CXX_ONLY) && !defined(C_ONLY)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success();
+}
+
+#[test]
+fn test_flags_directives() {
+    (assert_c! {
+        #inline_c_rs CPPFLAGS: "-DVALUE=2"
+        #inline_c_rs CFLAGS: "-DOTHER=3"
+
+        int main() {
+            return VALUE + OTHER;
+        }
+    })
+    .failure()
+    .code(5);
+}
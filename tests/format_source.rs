@@ -0,0 +1,78 @@
+//! `clang-format` is replaced by a script recording its style, and
+//! writing the source back after a comment.
+
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::PermissionsExt};
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_format_source() {
+    let bin = tempfile::tempdir().unwrap();
+    let clang_format = bin.path().join("clang-format");
+    fs::write(
+        &clang_format,
+        "#!/bin/sh\n\
+         echo \"/* formatted with $1 */\"\n\
+         cat\n",
+    )
+    .unwrap();
+    fs::set_permissions(&clang_format, fs::Permissions::from_mode(0o755)).unwrap();
+    env::set_var(
+        "PATH",
+        env::join_paths(
+            [bin.path().to_path_buf()]
+                .into_iter()
+                .chain(env::split_paths(&env::var_os("PATH").unwrap())),
+        )
+        .unwrap(),
+    );
+
+    let artifacts = tempfile::tempdir().unwrap();
+    let style = artifacts.path().join(".clang-format");
+
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+    assert
+        .artifact_dir(artifacts.path())
+        .clang_format_file(&style)
+        .success();
+
+    let source = assert
+        .artifacts()
+        .iter()
+        .find(|artifact| {
+            artifact
+                .extension()
+                .is_some_and(|extension| extension == "c")
+        })
+        .map(|source| fs::read_to_string(source).unwrap())
+        .unwrap();
+
+    assert!(
+        source.starts_with(&format!(
+            "/* formatted with --style=file:{} */\n",
+            style.display()
+        )),
+        "{}",
+        source
+    );
+
+    // The lines are the ones of the formatted source, after the comment.
+    (assert_c! {
+        #inline_c_rs FORMAT_SOURCE: "1"
+
+        #include <stdio.h>
+
+        int main() {
+            printf("%d\n", __LINE__);
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout("6\n");
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use wasmer_inline_c::{
+    assert_c,
+    framework::{self, Framework, Outcome, TestCase},
+};
+
+fn test_case(name: &str, outcome: Outcome, message: Option<&str>) -> TestCase {
+    TestCase {
+        name: name.to_string(),
+        outcome,
+        message: message.map(ToString::to_string),
+    }
+}
+
+#[test]
+fn test_parse_unity() {
+    assert_eq!(
+        framework::parse_unity(
+            "test.c:12:test_parse:PASS\ntest.c:20:test_empty:FAIL: Expected 1 Was 2\ntest.c:30:test_later:IGNORE\n\n-----------------------\n3 Tests 1 Failures 1 Ignored\n"
+        ),
+        [
+            test_case("test_parse", Outcome::Passed, None),
+            test_case("test_empty", Outcome::Failed, Some("Expected 1 Was 2")),
+            test_case("test_later", Outcome::Ignored, None),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_gtest() {
+    assert_eq!(
+        framework::parse_gtest(
+            "[ RUN      ] Parser.Full\n[       OK ] Parser.Full (0 ms)\n[ RUN      ] Parser.Empty\nparser.cc:7: Failure\nExpected: 1\n[  FAILED  ] Parser.Empty (1 ms)\n"
+        ),
+        [
+            test_case("Parser.Full", Outcome::Passed, None),
+            test_case(
+                "Parser.Empty",
+                Outcome::Failed,
+                Some("parser.cc:7: Failure\nExpected: 1")
+            ),
+        ]
+    );
+}
+
+/// A stand-in for Unity, from a directory with its `unity.h` and
+/// `unity.c`.
+#[test]
+fn test_unity() {
+    let unity = tempfile::tempdir().unwrap();
+    fs::write(
+        unity.path().join("unity.h"),
+        "void unity_report(int line, const char *name, int passed);\n",
+    )
+    .unwrap();
+    fs::write(
+        unity.path().join("unity.c"),
+        "#include <stdio.h>\n#include \"unity.h\"\n\nvoid unity_report(int line, const char *name, int passed) {\n    printf(\"test.c:%d:%s:%s\\n\", line, name, passed ? \"PASS\" : \"FAIL\");\n}\n",
+    )
+    .unwrap();
+
+    let test_cases = (assert_c! {
+        #include "unity.h"
+
+        int main() {
+            unity_report(3, "test_first", 1);
+            unity_report(4, "test_second", 0);
+
+            return 0;
+        }
+    })
+    .framework(Framework::Unity(unity.path().to_path_buf()))
+    .test_cases();
+
+    assert_eq!(
+        test_cases,
+        [
+            test_case("test_first", Outcome::Passed, None),
+            test_case("test_second", Outcome::Failed, None),
+        ]
+    );
+}
+
+#[test]
+fn test_parse_tap() {
+    let test_cases = framework::parse_tap(
+        "1..4\nok 1 - parse\nnot ok 2 - empty input # TODO later\nok 3 # SKIP no network\n",
+    );
+
+    assert_eq!(test_cases.len(), 4);
+    assert_eq!(test_cases[0].outcome, Outcome::Passed);
+    assert_eq!(test_cases[1].outcome, Outcome::Ignored);
+    assert_eq!(test_cases[2].outcome, Outcome::Ignored);
+    assert_eq!(test_cases[3].outcome, Outcome::Failed);
+}
+
+#[test]
+fn test_tap() {
+    let test_cases = (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("1..2\n");
+            printf("ok 1 - first\n");
+            printf("ok 2 - second\n");
+
+            return 0;
+        }
+    })
+    .tap();
+
+    assert_eq!(test_cases.len(), 2);
+    assert_eq!(test_cases[1].name, "2 - second");
+}
+
+#[test]
+#[should_panic(expected = "1 of 2 TAP tests have failed")]
+fn test_tap_failure() {
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("1..2\n");
+            printf("ok 1 - first\n");
+            printf("not ok 2 - second\n");
+
+            return 0;
+        }
+    })
+    .tap();
+}
@@ -0,0 +1,69 @@
+//! libFuzzer comes with Clang, so the tests are skipped without it.
+
+use std::{panic, process::Command};
+use wasmer_inline_c::{assert_c, fuzz::Fuzz};
+
+fn clang() -> bool {
+    let installed = Command::new("clang").arg("--version").output().is_ok();
+
+    if !installed {
+        eprintln!("Skipped: `clang` is not installed");
+    }
+
+    installed
+}
+
+#[test]
+fn test_fuzz() {
+    if !clang() {
+        return;
+    }
+
+    (assert_c! {
+        #inline_c_rs CC: "clang"
+
+        #include <stddef.h>
+        #include <stdint.h>
+
+        int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size) {
+            return data == NULL && size > 0;
+        }
+    })
+    .fuzz(Fuzz {
+        runs: Some(100),
+        ..Fuzz::default()
+    })
+    .success();
+}
+
+#[test]
+fn test_fuzz_crash() {
+    if !clang() {
+        return;
+    }
+
+    let crash = panic::catch_unwind(|| {
+        (assert_c! {
+            #inline_c_rs CC: "clang"
+
+            #include <stddef.h>
+            #include <stdint.h>
+            #include <stdlib.h>
+
+            int LLVMFuzzerTestOneInput(const uint8_t *data, size_t size) {
+                if (size > 0 && data[0] == 'x') {
+                    abort();
+                }
+
+                return 0;
+            }
+        })
+        .fuzz(Fuzz {
+            runs: Some(1_000_000),
+            ..Fuzz::default()
+        })
+        .success();
+    });
+
+    assert!(crash.is_err());
+}
@@ -0,0 +1,69 @@
+use std::{env, fs, panic, path::Path};
+use wasmer_inline_c::assert_c;
+
+fn hello(golden: &Path) {
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("Hello, World!\n");
+
+            return 0;
+        }
+    })
+    .stdout_matches_file(golden)
+    .success();
+}
+
+fn missing_declaration(expected: &Path) {
+    (assert_c! {
+        int main() {
+            return undeclared;
+        }
+    })
+    .compile_fail_matches_file(expected);
+}
+
+#[test]
+fn test_matches_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let golden = directory.path().join("golden").join("hello.stdout");
+
+    assert!(panic::catch_unwind(|| hello(&golden)).is_err());
+
+    let expected = directory
+        .path()
+        .join("compile-fail")
+        .join("undeclared.stderr");
+
+    env::set_var("INLINE_C_RS_UPDATE_GOLDEN", "1");
+    hello(&golden);
+    missing_declaration(&expected);
+    env::remove_var("INLINE_C_RS_UPDATE_GOLDEN");
+
+    let diagnostics = fs::read_to_string(&expected).unwrap();
+    assert!(diagnostics.contains("$SOURCE:"), "{}", diagnostics);
+    assert!(diagnostics.contains("undeclared"), "{}", diagnostics);
+    assert!(!diagnostics.contains(&*directory.path().to_string_lossy()));
+    missing_declaration(&expected);
+
+    fs::write(&expected, "$SOURCE: error: something else\n").unwrap();
+    assert!(panic::catch_unwind(|| missing_declaration(&expected)).is_err());
+
+    assert_eq!(fs::read_to_string(&golden).unwrap(), "Hello, World!\n");
+    hello(&golden);
+
+    fs::write(&golden, "Goodbye\n").unwrap();
+    assert!(panic::catch_unwind(|| hello(&golden)).is_err());
+}
+
+#[test]
+#[should_panic(expected = "The program has compiled, but it was expected to fail")]
+fn test_compile_fail_matches_file_of_a_valid_program() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .compile_fail_matches_file("tests/compile-fail/none.stderr");
+}
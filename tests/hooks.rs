@@ -0,0 +1,36 @@
+use std::sync::{Arc, Mutex};
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_hooks() {
+    let compiled = Arc::new(Mutex::new(None));
+    let finished = Arc::new(Mutex::new(None));
+
+    let mut assert = assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("%d\n", VALUE);
+
+            return 0;
+        }
+    };
+
+    assert
+        .on_source(|source| source.insert_str(0, "#define VALUE 42\n"))
+        .on_compiled({
+            let compiled = compiled.clone();
+
+            move |executable| *compiled.lock().unwrap() = Some(executable.is_file())
+        })
+        .on_finished({
+            let finished = finished.clone();
+
+            move |result| *finished.lock().unwrap() = Some(result.stdout.clone())
+        });
+
+    assert.success().stdout("42\n");
+
+    assert_eq!(*compiled.lock().unwrap(), Some(true));
+    assert_eq!(*finished.lock().unwrap(), Some(b"42\n".to_vec()));
+}
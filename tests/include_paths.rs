@@ -0,0 +1,53 @@
+use std::fs;
+use wasmer_inline_c::{run, Language};
+
+/// An include directory longer than `MAX_PATH`, whose headers MSVC can
+/// only open with an extended-length path.
+#[test]
+fn test_long_include_directory() {
+    let root = tempfile::tempdir().unwrap();
+    let mut directory = root.path().to_path_buf();
+
+    while directory.as_os_str().len() < 300 {
+        directory.push("a-rather-long-directory-name");
+    }
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("value.h"), "#define VALUE 42\n").unwrap();
+
+    let program = format!(
+        "#inline_c_rs CFLAGS: \"-I{}\"\n\n#include \"value.h\"\n\nint main() {{\n    return VALUE - 42;\n}}\n",
+        directory.display()
+    );
+
+    run(Language::C, &program).unwrap().success();
+}
+
+#[test]
+fn test_missing_directories() {
+    let root = tempfile::tempdir().unwrap();
+    let missing = root.path().join("incldue");
+    let program = format!(
+        "#inline_c_rs CFLAGS: \"-I{}\"\n\nint main() {{\n    return 0;\n}}\n",
+        missing.display()
+    );
+
+    let Err(error) = run(Language::C, &program) else {
+        panic!("`{}` does not exist", missing.display());
+    };
+
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "the include directory `{}`, given by the `CFLAGS` variable, of an `#inline_c_rs` directive or of the `.env` file, does not exist; set the `CHECK_PATHS` variable to `0` to skip this check",
+            missing.display()
+        )
+    );
+
+    run(
+        Language::C,
+        &format!("#inline_c_rs CHECK_PATHS: \"0\"\n{}", program),
+    )
+    .unwrap()
+    .success();
+}
@@ -0,0 +1,79 @@
+use std::{fs, path::Path};
+use wasmer_inline_c::{
+    includes::{self, MAX_DEPTH},
+    run, Language,
+};
+
+#[test]
+fn test_resolve() {
+    let directory = tempfile::tempdir().unwrap();
+    let include = directory.path().join("include");
+    fs::create_dir_all(include.join("nested")).unwrap();
+    fs::write(include.join("a.h"), "#include \"nested/b.h\"\n").unwrap();
+    // `b.h` includes `c.h` next to it, and `a.h` again.
+    fs::write(
+        include.join("nested").join("b.h"),
+        "#include \"c.h\"\n#include \"a.h\"\n#include <stdio.h>\n",
+    )
+    .unwrap();
+    fs::write(include.join("nested").join("c.h"), "").unwrap();
+    let include = include.canonicalize().unwrap();
+
+    let headers = includes::resolve(
+        "#include \"a.h\"\n#include \"missing.h\"\n",
+        std::slice::from_ref(&include),
+    )
+    .unwrap();
+
+    assert_eq!(
+        headers
+            .iter()
+            .map(|header| header.strip_prefix(&include).unwrap().to_path_buf())
+            .collect::<Vec<_>>(),
+        [
+            "a.h".into(),
+            Path::new("nested").join("b.h"),
+            Path::new("nested").join("c.h"),
+        ]
+    );
+}
+
+#[test]
+fn test_depth_limit() {
+    let directory = tempfile::tempdir().unwrap();
+
+    for depth in 0..=MAX_DEPTH {
+        fs::write(
+            directory.path().join(format!("{}.h", depth)),
+            format!("#include \"{}.h\"\n", depth + 1),
+        )
+        .unwrap();
+    }
+
+    let error =
+        includes::resolve("#include \"0.h\"\n", &[directory.path().to_path_buf()]).unwrap_err();
+
+    assert!(error.to_string().contains("0.h"), "{}", error);
+}
+
+#[test]
+fn test_dependencies() {
+    let directory = tempfile::tempdir().unwrap();
+    fs::write(directory.path().join("value.h"), "#define VALUE 0\n").unwrap();
+
+    let mut assert = run(
+        Language::C,
+        &format!(
+            "#inline_c_rs CFLAGS: \"-I{}\"\n\n#include \"value.h\"\n\nint main() {{\n    return VALUE;\n}}\n",
+            directory.path().display()
+        ),
+    )
+    .unwrap();
+
+    assert!(assert.dependencies().is_empty());
+    assert.success();
+    assert_eq!(
+        assert.dependencies(),
+        [directory.path().join("value.h").canonicalize().unwrap()]
+    );
+}
@@ -0,0 +1,44 @@
+//! The files of a snippet whose variables are invalid are removed.
+
+use std::{env, fs, path::Path, process};
+use wasmer_inline_c::{run, Language};
+
+/// Build a snippet, whose variables are invalid, and return the error.
+fn error() -> String {
+    match run(Language::C, "int main() {\n    return 0;\n}\n") {
+        Ok(_) => panic!("the variables are valid"),
+        Err(error) => error.to_string(),
+    }
+}
+
+#[test]
+fn test_invalid_variables() {
+    let directory = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .parent()
+        .unwrap()
+        .join("inline-c")
+        .join("invalid_variables")
+        .join(format!("run-{}", process::id()));
+    let files = || fs::read_dir(&directory).map_or(0, |entries| entries.count());
+
+    env::set_var("INLINE_C_RS_RUNNER", "teleport:somewhere");
+    assert!(error().contains("teleport"));
+    assert_eq!(files(), 0);
+    env::remove_var("INLINE_C_RS_RUNNER");
+
+    for (variable, value) in [
+        ("NETWORK", "sometimes"),
+        ("PRIORITY", "high"),
+        ("CPU_AFFINITY", "first"),
+        ("RETRIES", "many"),
+        ("FINISHES_WITHIN", "soon"),
+    ] {
+        let variable = format!("INLINE_C_RS_{}", variable);
+
+        env::set_var(&variable, value);
+        error();
+        env::remove_var(&variable);
+
+        assert_eq!(files(), 0, "the files are left by `{}`", variable);
+    }
+}
@@ -0,0 +1,44 @@
+//! Objective-C needs the Objective-C front-end of GCC, or Clang, so its
+//! test is skipped without it.
+
+use std::process::{Command, Stdio};
+use wasmer_inline_c::{run, Language};
+
+#[test]
+fn test_languages() {
+    for &language in Language::ALL {
+        assert_eq!(
+            Language::from_extension(language.extension()),
+            Some(language)
+        );
+        assert_eq!(language.to_string(), language.extension());
+    }
+
+    assert_eq!(Language::from_extension("cc"), Some(Language::Cxx));
+    assert_eq!(Language::from_extension("asm"), Some(Language::Assembler));
+    assert_eq!(Language::from_extension("rs"), None);
+}
+
+#[test]
+fn test_objective_c() {
+    let supported = Command::new("cc")
+        .args(["-x", "objective-c", "-fsyntax-only", "-"])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+
+    if !supported {
+        eprintln!("Skipped: `cc` does not compile Objective-C");
+
+        return;
+    }
+
+    run(
+        Language::ObjectiveC,
+        "#inline_c_rs OBJCFLAGS: \"-DVALUE=3\"\n\nint main() {\n    return VALUE;\n}\n",
+    )
+    .unwrap()
+    .failure()
+    .code(3);
+}
@@ -0,0 +1,39 @@
+use std::fs;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_lazy_compilation() {
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+
+    assert!(assert.compile_time().is_none());
+    assert.success();
+    assert!(assert.compile_time().is_some());
+}
+
+#[test]
+fn test_genuine_failures_are_not_retried() {
+    let directory = tempfile::tempdir().unwrap();
+    let runs = directory.path().join("runs");
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main(int argc, char **argv) {
+            FILE *runs = fopen(argv[1], "a");
+            fputs("run\n", runs);
+            fclose(runs);
+
+            return argc;
+        }
+    })
+    .retries(3)
+    .arg(&runs)
+    .failure()
+    .code(2);
+
+    assert_eq!(fs::read_to_string(&runs).unwrap(), "run\n");
+}
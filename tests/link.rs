@@ -0,0 +1,173 @@
+#![cfg(target_os = "linux")]
+
+use predicates::prelude::*;
+use std::{fs, path::Path, process::Command};
+use wasmer_inline_c::{run, Language};
+
+const PROGRAM: &str = "int a(void);\n\nint main() {\n    return a();\n}\n";
+const ANSWER: &str = "int answer(void);\n\nint main() {\n    return answer();\n}\n";
+
+fn cc(directory: &Path, args: &[&str]) {
+    assert!(Command::new("cc")
+        .current_dir(directory)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+/// Build the static library `lib<name>.a` in `directory`, with an
+/// object per source.
+fn archive(directory: &Path, name: &str, sources: &[(&str, &str)]) {
+    let mut objects = Vec::new();
+
+    for (file_name, source) in sources {
+        fs::write(directory.join(file_name), source).unwrap();
+        let object = format!("{}.o", file_name);
+        cc(directory, &["-c", "-fPIC", file_name, "-o", &object]);
+        objects.push(object);
+    }
+
+    assert!(Command::new("ar")
+        .current_dir(directory)
+        .arg("rcs")
+        .arg(format!("lib{}.a", name))
+        .args(&objects)
+        .status()
+        .unwrap()
+        .success());
+}
+
+#[test]
+fn test_link_group() {
+    let directory = tempfile::tempdir().unwrap();
+    archive(
+        directory.path(),
+        "cycle_a",
+        &[
+            ("a.c", "int b(void);\nint a(void) { return b() + 1; }\n"),
+            ("a_base.c", "int a_base(void) { return 1; }\n"),
+        ],
+    );
+    archive(
+        directory.path(),
+        "cycle_b",
+        &[(
+            "b.c",
+            "int a_base(void);\nint b(void) { return a_base() + 1; }\n",
+        )],
+    );
+
+    let program = || run(Language::C, PROGRAM).unwrap();
+
+    program()
+        .link_search_path(directory.path())
+        .link_library("cycle_a")
+        .link_library("cycle_b")
+        .failure()
+        .stderr(predicate::str::contains("undefined reference to `a_base'"));
+
+    program()
+        .link_search_path(directory.path())
+        .link_library("cycle_a")
+        .link_library("cycle_b")
+        .link_group(true)
+        .failure()
+        .code(3);
+
+    run(
+        Language::C,
+        &format!(
+            "#inline_c_rs LINK_SEARCH_PATHS: \"{}\"\n\
+             #inline_c_rs LINK_LIBRARIES: \"cycle_a,cycle_b\"\n\
+             #inline_c_rs LINK_GROUP: \"1\"\n\n{}",
+            directory.path().display(),
+            PROGRAM
+        ),
+    )
+    .unwrap()
+    .failure()
+    .code(3);
+}
+
+/// The static and shared libraries `answer`, returning 42 and 43.
+fn answers(directory: &Path) {
+    archive(
+        directory,
+        "answer",
+        &[("static.c", "int answer(void) { return 42; }\n")],
+    );
+    fs::write(
+        directory.join("shared.c"),
+        "int answer(void) { return 43; }\n",
+    )
+    .unwrap();
+    cc(
+        directory,
+        &["-shared", "-fPIC", "shared.c", "-o", "libanswer.so"],
+    );
+}
+
+#[test]
+fn test_link_static_and_dylib() {
+    let directory = tempfile::tempdir().unwrap();
+    answers(directory.path());
+
+    let program = || {
+        let mut assert = run(Language::C, ANSWER).unwrap();
+        assert.link_search_path(directory.path());
+
+        assert
+    };
+
+    program().link_library("answer").failure().code(43);
+    program().link_static("answer").failure().code(42);
+    program().link_dylib("answer").failure().code(43);
+
+    run(
+        Language::C,
+        &format!(
+            "#inline_c_rs LINK_SEARCH_PATHS: \"{}\"\n\
+             #inline_c_rs LINK_LIBRARIES: \"static=answer\"\n\n{}",
+            directory.path().display(),
+            ANSWER
+        ),
+    )
+    .unwrap()
+    .failure()
+    .code(42);
+}
+
+#[test]
+fn test_rpath() {
+    let directory = tempfile::tempdir().unwrap();
+    answers(directory.path());
+
+    let program = "#include <stdio.h>\n#include <stdlib.h>\n\nint answer(void);\n\nint main() {\n    const char *path = getenv(\"LD_LIBRARY_PATH\");\n    printf(\"%s\\n\", path ? path : \"\");\n\n    return answer();\n}\n";
+    let directory_name = directory.path().to_string_lossy().into_owned();
+
+    run(Language::C, program)
+        .unwrap()
+        .link_search_path(directory.path())
+        .link_dylib("answer")
+        .failure()
+        .code(43)
+        .stdout(predicate::str::contains(&*directory_name));
+
+    let mut assert = run(Language::C, program).unwrap();
+    assert
+        .link_search_path(directory.path())
+        .link_dylib("answer")
+        .rpath(true)
+        .failure()
+        .code(43)
+        .stdout(predicate::str::contains(&*directory_name).not());
+
+    let dynamic_section = Command::new("readelf")
+        .arg("-d")
+        .arg(assert.executable())
+        .output()
+        .unwrap();
+
+    assert!(String::from_utf8_lossy(&dynamic_section.stdout).contains("[$ORIGIN/"));
+}
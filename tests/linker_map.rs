@@ -0,0 +1,32 @@
+use std::fs;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_linker_map() {
+    let mut assert = assert_c! {
+        int inline_c_rs_mapped(void) {
+            return 0;
+        }
+
+        int main() {
+            return inline_c_rs_mapped();
+        }
+    };
+    assert.linker_map(true);
+
+    let map = fs::read_to_string(assert.map_file()).unwrap();
+
+    assert!(map.contains("inline_c_rs_mapped"), "{}", map);
+    assert.success();
+}
+
+#[test]
+#[should_panic(expected = "The linker map has not been enabled")]
+fn test_linker_map_not_enabled() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .map_file();
+}
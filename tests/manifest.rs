@@ -0,0 +1,92 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use wasmer_inline_c::assert_c;
+
+/// Two snippets expanded on the same line.
+#[rustfmt::skip]
+macro_rules! two_snippets {
+    () => {
+        ((assert_c! { int main() { int first_snippet = 0; return first_snippet; } }), (assert_c! { int main() { int second_snippet = 0; return second_snippet; } }))
+    };
+}
+
+/// The snippets of this test in the manifest.
+fn snippets() -> Vec<String> {
+    let directory = option_env!("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("target"))
+        .join("inline-c")
+        .join("manifest")
+        .join(env!("CARGO_PKG_NAME"));
+
+    fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("tests_manifest_rs_")
+        })
+        .map(|path| fs::read_to_string(path).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_manifest_keeps_the_snippets_of_the_same_line() {
+    let (mut first, mut second) = two_snippets!();
+    first.success();
+    second.success();
+
+    let snippets = snippets();
+
+    for name in ["first_snippet", "second_snippet"] {
+        assert!(
+            snippets.iter().any(|snippet| snippet.contains(name)),
+            "`{}` is missing from the manifest",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_source_is_kept_as_written() {
+    (assert_c! {
+        #include <stdio.h>
+        #define TWICE(x) ((x) * 2)
+
+        int main() {
+            const char *url = "http://example.org/as_written";
+            int shifted = 1;
+            shifted <<= TWICE(1);
+
+            printf("%s %d %d\n", url, shifted, __LINE__);
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout("http://example.org/as_written 4 9\n");
+
+    let snippet = snippets()
+        .into_iter()
+        .find(|snippet| snippet.contains("as_written"))
+        .unwrap();
+
+    assert!(
+        snippet.contains(
+            "#include <stdio.h>\n        \
+             #define TWICE(x) ((x) * 2)\n\n        \
+             int main() {\n"
+        ),
+        "{}",
+        snippet
+    );
+    assert!(
+        snippet.contains("            shifted <<= TWICE(1);\n"),
+        "{}",
+        snippet
+    );
+}
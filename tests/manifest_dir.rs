@@ -0,0 +1,38 @@
+//! The relative paths of the variables are resolved against the crate,
+//! even when the tests run in another directory.
+
+#![cfg(unix)]
+
+use std::{env, path::Path};
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_relative_paths() {
+    let directory = tempfile::tempdir().unwrap();
+    env::set_current_dir(directory.path()).unwrap();
+
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-I tests/fixtures"
+        #inline_c_rs CWD: "tests/fixtures"
+
+        #include <stdio.h>
+        #include <unistd.h>
+        #include "answer.h"
+
+        int main() {
+            char directory[4096];
+            printf("%s\n", getcwd(directory, sizeof(directory)));
+
+            return ANSWER;
+        }
+    })
+    .failure()
+    .code(42)
+    .stdout(format!(
+        "{}\n",
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .display()
+    ));
+}
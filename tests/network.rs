@@ -0,0 +1,52 @@
+use wasmer_inline_c::network::Network;
+
+#[test]
+fn test_parse() {
+    assert_eq!("deny".parse(), Ok(Network::Denied));
+    assert_eq!(" Required ".parse(), Ok(Network::Required));
+    assert_eq!("allow".parse(), Ok(Network::Inherit));
+    assert_eq!(
+        "sometimes".parse::<Network>(),
+        Err("unknown network access `sometimes`".to_string())
+    );
+}
+
+/// Connect to the loopback interface, which is down in a new network
+/// namespace.
+#[cfg(target_os = "linux")]
+const CONNECT: &str = r#"
+#include <arpa/inet.h>
+#include <netinet/in.h>
+#include <sys/socket.h>
+
+int main() {
+    struct sockaddr_in address = { 0 };
+    address.sin_family = AF_INET;
+    address.sin_port = htons(9);
+    address.sin_addr.s_addr = htonl(INADDR_LOOPBACK);
+
+    int fd = socket(AF_INET, SOCK_DGRAM, 0);
+
+    return connect(fd, (struct sockaddr *) &address, sizeof(address)) == 0 ? 0 : 1;
+}
+"#;
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_network_denied() {
+    use wasmer_inline_c::{run, Language};
+
+    run(Language::C, CONNECT).unwrap().success();
+    run(Language::C, CONNECT)
+        .unwrap()
+        .network(Network::Denied)
+        .failure()
+        .code(1);
+    run(
+        Language::C,
+        &format!("#inline_c_rs NETWORK: \"deny\"\n{}", CONNECT),
+    )
+    .unwrap()
+    .failure()
+    .code(1);
+}
@@ -0,0 +1,55 @@
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_duplicated_include_directories() {
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-I. -I."
+
+        int main() {
+            return 0;
+        }
+    })
+    .success();
+}
+
+#[cfg(not(target_env = "msvc"))]
+#[test]
+#[should_panic(expected = r#"the flags ["/EHsc", "/nologo"] are not understood by GCC or Clang"#)]
+fn test_flags_of_msvc() {
+    (assert_c! {
+        #inline_c_rs CFLAGS: "/EHsc -DVALUE=1 /nologo"
+
+        int main() {
+            return 0;
+        }
+    })
+    .success();
+}
+
+#[cfg(target_env = "msvc")]
+#[test]
+#[should_panic(expected = r#"the flags ["-pthread"] are not understood by MSVC"#)]
+fn test_flags_of_gcc() {
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-pthread"
+
+        int main() {
+            return 0;
+        }
+    })
+    .success();
+}
+
+/// The GNU-style flags are spelled for MSVC when it compiles the
+/// program.
+#[test]
+fn test_gnu_style_flags() {
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-O2 -DVALUE=3 -UUNDEFINED -std=c11 -I."
+
+        int main() {
+            return VALUE - 3;
+        }
+    })
+    .success();
+}
@@ -0,0 +1,48 @@
+use std::env;
+use wasmer_inline_c::once_compiled;
+
+#[test]
+fn test_shared_program_is_compiled_once() {
+    let first = once_compiled!("echo", {
+        #include <stdio.h>
+
+        int main(int argc, char **argv) {
+            for (int i = 1; i < argc; ++i) {
+                printf("%s\n", argv[i]);
+            }
+
+            return 0;
+        }
+    });
+    let second = once_compiled!("echo", {
+        int main() {
+            return 1;
+        }
+    });
+
+    assert_eq!(first.executable(), second.executable());
+
+    first.run().arg("first").success().stdout("first\n");
+    second.run().args(["a", "b"]).success().stdout("a\nb\n");
+}
+
+fn skipped() -> Result<(), &'static str> {
+    once_compiled!("skipped", {
+        #inline_c_rs SKIP_IF_ENV: "INLINE_C_RS_TEST_ONCE_COMPILED_SKIP"
+
+        int main() {
+            return 1;
+        }
+    })
+    .run()
+    .success();
+
+    Err("the test has not been skipped")
+}
+
+#[test]
+fn test_skipped_program_skips_the_test() {
+    env::set_var("INLINE_C_RS_TEST_ONCE_COMPILED_SKIP", "1");
+
+    skipped().unwrap();
+}
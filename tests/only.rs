@@ -0,0 +1,43 @@
+//! The `INLINE_C_RS_ONLY` variable selects the named snippets to run.
+
+use std::env;
+use wasmer_inline_c::assert_c;
+
+fn not_selected() -> Result<(), &'static str> {
+    (assert_c!(name = "engine_delete_null", {
+        int main() {
+            return 1;
+        }
+    }))
+    .success();
+
+    Err("the snippet has not been filtered out")
+}
+
+#[test]
+fn test_only() {
+    env::set_var("INLINE_C_RS_ONLY", "engine_new, engine_new_null");
+
+    not_selected().unwrap();
+
+    let mut assert = assert_c!(name = "engine_new_null", {
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main() {
+            printf("%s\n", getenv("NAME"));
+
+            return 0;
+        }
+    });
+    assert.success().stdout("engine_new_null\n");
+
+    let artifacts = assert.artifacts();
+
+    assert!(!artifacts.is_empty());
+    assert!(artifacts.iter().all(|artifact| artifact
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .starts_with("inline-c-rs-engine_new_null-")));
+}
@@ -0,0 +1,192 @@
+use wasmer_inline_c::{
+    assert_c,
+    output::{decode, Encoding, Stream},
+};
+
+#[test]
+fn test_output() {
+    let output = (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("out");
+            fprintf(stderr, "err");
+
+            return 7;
+        }
+    })
+    .output()
+    .unwrap();
+
+    assert_eq!(output.exit_code, Some(7));
+    assert!(!output.success());
+    assert_eq!(output.stdout, b"out");
+    assert_eq!(output.stderr, b"err");
+    assert!(output.chunks.is_empty());
+}
+
+#[test]
+fn test_strip_ansi() {
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("\x1b[1;31mred\x1b[0m\n");
+            fprintf(stderr, "\x1b[32mgreen\x1b[0m\n");
+
+            return 0;
+        }
+    })
+    .strip_ansi()
+    .success()
+    .stdout("red\n")
+    .stderr("green\n");
+}
+
+#[test]
+fn test_tee() {
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("first\nsecond");
+            fprintf(stderr, "error\n");
+
+            return 0;
+        }
+    })
+    .tee()
+    .success()
+    .stdout("first\nsecond")
+    .stderr("error\n");
+}
+
+#[test]
+fn test_deterministic() {
+    (assert_c! {
+        #inline_c_rs TZ: "Europe/Paris"
+
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main() {
+            printf("%s %s %s %s\n", getenv("LC_ALL"), getenv("TZ"), getenv("SOURCE_DATE_EPOCH"), getenv("SEED"));
+
+            return 0;
+        }
+    })
+    .deterministic()
+    .success()
+    .stdout("C Europe/Paris 0 0\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_interleaved() {
+    let mut assert = assert_c! {
+        #include <stdio.h>
+        #include <unistd.h>
+
+        int main() {
+            setvbuf(stdout, NULL, _IONBF, 0);
+
+            printf("first\n");
+            usleep(100000);
+            fprintf(stderr, "error\n");
+            usleep(100000);
+            printf("result\n");
+
+            return 0;
+        }
+    };
+
+    assert
+        .printed_before((Stream::Stdout, "first"), (Stream::Stderr, "error"))
+        .printed_before((Stream::Stderr, "error"), (Stream::Stdout, "result"));
+
+    let result = assert.output().unwrap();
+    let streams = result
+        .chunks
+        .iter()
+        .map(|chunk| chunk.stream)
+        .collect::<Vec<_>>();
+
+    assert_eq!(streams, [Stream::Stdout, Stream::Stderr, Stream::Stdout]);
+    assert!(result.chunks[0].elapsed < result.chunks[2].elapsed);
+    assert_eq!(result.chunks[2].bytes, b"result\n");
+}
+
+#[cfg(unix)]
+#[test]
+#[should_panic(expected = "Expected \"result\" in Stdout before \"error\" in Stderr")]
+fn test_interleaved_failure() {
+    (assert_c! {
+        #include <stdio.h>
+        #include <unistd.h>
+
+        int main() {
+            fprintf(stderr, "error\n");
+            usleep(100000);
+            printf("result\n");
+
+            return 0;
+        }
+    })
+    .printed_before((Stream::Stdout, "result"), (Stream::Stderr, "error"));
+}
+
+#[test]
+fn test_output_encoding() {
+    assert_eq!("auto".parse(), Ok(Encoding::Auto));
+    assert_eq!("UTF-16LE".parse(), Ok(Encoding::Utf16Le));
+    assert_eq!("cp437".parse(), Ok(Encoding::CodePage(437)));
+    assert!("ebcdic".parse::<Encoding>().is_err());
+
+    let utf16le = [0xff, 0xfe, b'h', 0, 0xe9, 0, b'\n', 0];
+    assert_eq!(decode(&utf16le, Encoding::Auto), "h\u{e9}\n".as_bytes());
+    assert_eq!(
+        decode(&utf16le[2..], Encoding::Auto),
+        "h\u{e9}\n".as_bytes()
+    );
+    assert_eq!(decode(&utf16le, Encoding::Utf16Le), "h\u{e9}\n".as_bytes());
+    assert_eq!(
+        decode(&[0xfe, 0xff, 0, b'h', 0, 0xe9], Encoding::Auto),
+        "h\u{e9}".as_bytes()
+    );
+    assert_eq!(
+        decode(&[b'h', 0xe9], Encoding::CodePage(28591)),
+        "h\u{e9}".as_bytes()
+    );
+    assert_eq!(
+        decode("h\u{e9}".as_bytes(), Encoding::Auto),
+        "h\u{e9}".as_bytes()
+    );
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            unsigned char utf16le[] = {0xff, 0xfe, 'h', 0, 0xe9, 0, '\n', 0};
+            fwrite(utf16le, 1, sizeof(utf16le), stdout);
+
+            return 0;
+        }
+    })
+    .output_encoding(Encoding::Utf16Le)
+    .success()
+    .stdout("h\u{e9}\n");
+
+    (assert_c! {
+        #inline_c_rs OUTPUT_ENCODING: "cp28591"
+        #include <stdio.h>
+
+        int main() {
+            unsigned char latin1[] = {'h', 0xe9, '\n'};
+            fwrite(latin1, 1, sizeof(latin1), stdout);
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout("h\u{e9}\n");
+}
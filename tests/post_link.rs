@@ -0,0 +1,55 @@
+#![cfg(target_os = "linux")]
+
+use std::{fs, process::Command};
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_post_link() {
+    let mut strip = Command::new("strip");
+    strip.arg("{executable}");
+
+    let mut objcopy = Command::new("objcopy");
+    objcopy.args([
+        "--dump-section",
+        ".comment={executable}.comment",
+        "{executable}",
+    ]);
+
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+    assert
+        .post_link(strip, Vec::<String>::new())
+        .post_link(objcopy, ["{executable}.comment"])
+        .success();
+
+    let comment = assert
+        .artifacts()
+        .iter()
+        .find(|artifact| artifact.extension().is_some_and(|e| e == "comment"))
+        .cloned()
+        .unwrap();
+
+    assert!(!fs::read(&comment).unwrap().is_empty());
+
+    drop(assert);
+
+    assert!(!comment.exists());
+}
+
+#[test]
+#[should_panic(expected = "the signature is invalid")]
+fn test_post_link_failure() {
+    let mut sign = Command::new("sh");
+    sign.args(["-c", "echo the signature is invalid >&2; exit 1"]);
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .post_link(sign, Vec::<String>::new())
+    .success();
+}
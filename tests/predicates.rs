@@ -0,0 +1,104 @@
+use wasmer_inline_c::{assert_c, diff, predicates::*};
+
+#[test]
+fn test_numbers_close() {
+    assert!(numbers_close("result: 3.1416", 1e-4).eval("result: 3.14159"));
+    assert!(!numbers_close("result: 3.1416", 1e-6).eval("result: 3.14159"));
+    assert!(!numbers_close("result: 3.1416", 1e-4).eval("total: 3.1416"));
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("%f %e\n", 1.0 / 3.0, 2.0 / 3.0);
+
+            return 0;
+        }
+    })
+    .success()
+    .stdout(numbers_close("0.3333 0.6667\n", 1e-3));
+}
+
+#[test]
+fn test_unordered_lines_eq() {
+    assert!(unordered_lines_eq("a\nb\nb\n").eval("b\na\nb\n"));
+    assert!(!unordered_lines_eq("a\nb\nb\n").eval("a\na\nb\n"));
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("second\nfirst\n");
+
+            return 0;
+        }
+    })
+    .stdout_lines_unordered("first\nsecond\n")
+    .success();
+}
+
+#[test]
+fn test_bytes_and_sha256() {
+    assert!(bytes_eq(&b"\x00\xff"[..]).eval(b"\x00\xff"));
+    assert!(!bytes_eq(&b"\x00\xff"[..]).eval(b"\x00\xfe"));
+
+    let mut assert = assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            unsigned char bytes[] = { 0, 1, 255, 10 };
+            fwrite(bytes, 1, sizeof(bytes), stdout);
+
+            return 0;
+        }
+    };
+
+    assert.stdout_bytes_eq(b"\x00\x01\xff\n").success();
+    assert
+        .stdout_sha256("A95803D91F2A2EB57D42E2A799BB0D42E2ACC37C22AA9D433A4173E5DB794F28")
+        .success();
+}
+
+#[test]
+fn test_eq_diff() {
+    assert_eq!(
+        diff::lines("a\nb\nc\n", "a\nx\nc\n"),
+        "  a\n+ x\n- b\n  c\n"
+    );
+
+    assert!(eq_diff("a\nb\n").eval("a\nb\n"));
+    assert!(!eq_diff("a\nb\n").eval("a\r\nb\r\n"));
+
+    let predicate = eq_diff("a\nb\n");
+    let case = predicate.find_case(false, "a\r\nb\r\n").unwrap();
+    assert_eq!(
+        case.products().next().unwrap().value().to_string(),
+        "the lines are equal, but not their endings"
+    );
+
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("Hello\nWorld\n");
+
+            return 0;
+        }
+    })
+    .stdout_eq("Hello\nWorld\n");
+}
+
+#[test]
+#[should_panic(expected = "- Goodbye")]
+fn test_eq_diff_failure() {
+    (assert_c! {
+        #include <stdio.h>
+
+        int main() {
+            printf("Hello\nWorld\n");
+
+            return 0;
+        }
+    })
+    .stdout_eq("Hello\nGoodbye\n");
+}
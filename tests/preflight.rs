@@ -0,0 +1,34 @@
+use std::env;
+use wasmer_inline_c::preflight;
+
+#[test]
+fn test_preflight() {
+    let checks = preflight();
+
+    assert!(checks.is_ok(), "{}", checks);
+
+    for name in [
+        "variables",
+        "C compiler",
+        "C program",
+        "C++ compiler",
+        "C++ program",
+    ] {
+        assert!(
+            checks.checks.iter().any(|check| check.name == name),
+            "no `{}` check in:\n{}",
+            name,
+            checks
+        );
+    }
+
+    // A compiler failing to print its version is reported, rather than
+    // found.
+    env::set_var("INLINE_C_RS_CC", "false");
+
+    let checks = preflight();
+    let failures = checks.failures().collect::<Vec<_>>();
+
+    assert_eq!(failures.len(), 1, "{}", checks);
+    assert_eq!(failures[0].name, "C compiler");
+}
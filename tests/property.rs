@@ -0,0 +1,68 @@
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+use std::panic;
+use wasmer_inline_c::{assert_c, property::Case};
+
+#[test]
+fn test_property() {
+    (assert_c! {
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main(int argc, char **argv) {
+            printf("%ld\n", 2 * strtol(argv[argc - 1], NULL, 10));
+
+            return 0;
+        }
+    })
+    .property(
+        (0..1000u32).prop_map(|n| Case::args([n.to_string()])),
+        |case, result| {
+            let n = case.args[0].to_str().unwrap().parse::<u32>().unwrap();
+
+            if result.success() && result.stdout == format!("{}\n", 2 * n).as_bytes() {
+                Ok(())
+            } else {
+                Err(format!("{:?}", result))
+            }
+        },
+    );
+}
+
+#[test]
+fn test_shrunk_case() {
+    let failure = panic::catch_unwind(|| {
+        (assert_c! {
+            #include <stdio.h>
+
+            int main() {
+                int c, length = 0;
+
+                while ((c = getchar()) != EOF) {
+                    length++;
+                }
+
+                return length >= 10;
+            }
+        })
+        .property(
+            proptest::collection::vec(any::<u8>(), 0..64).prop_map(Case::stdin),
+            |_, result| {
+                if result.success() {
+                    Ok(())
+                } else {
+                    Err("too long".to_string())
+                }
+            },
+        );
+    })
+    .unwrap_err();
+    let message = failure.downcast_ref::<String>().unwrap();
+
+    assert!(
+        message.starts_with("The property does not hold"),
+        "{}",
+        message
+    );
+}
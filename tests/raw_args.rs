@@ -0,0 +1,52 @@
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_raw_compiler_arg() {
+    (assert_c! {
+        int main() {
+            return VALUE;
+        }
+    })
+    .raw_compiler_arg("-DVALUE=5")
+    .failure()
+    .code(5);
+}
+
+// The GNU linker only takes the symbols of an archive which are
+// undefined by the objects before it.
+#[cfg(target_os = "linux")]
+#[test]
+fn test_raw_linker_arg() {
+    use std::{fs, process::Command};
+
+    let directory = tempfile::tempdir().unwrap();
+    fs::write(
+        directory.path().join("answer.c"),
+        "int answer(void) { return 42; }\n",
+    )
+    .unwrap();
+
+    assert!(Command::new("cc")
+        .current_dir(directory.path())
+        .args(["-c", "answer.c", "-o", "answer.o"])
+        .status()
+        .unwrap()
+        .success());
+    assert!(Command::new("ar")
+        .current_dir(directory.path())
+        .args(["rcs", "libanswer.a", "answer.o"])
+        .status()
+        .unwrap()
+        .success());
+
+    (assert_c! {
+        int answer(void);
+
+        int main() {
+            return answer();
+        }
+    })
+    .raw_linker_arg(directory.path().join("libanswer.a"))
+    .failure()
+    .code(42);
+}
@@ -0,0 +1,59 @@
+use std::{fs, panic};
+use wasmer_inline_c::{assert_c, run, Language};
+
+const HELLO: &str = "#include <stdio.h>\n\n\
+                     int main() {\n    \
+                     printf(\"Hello, World!\\n\");\n\n    \
+                     return 3;\n\
+                     }\n";
+
+#[test]
+fn test_record_replay() {
+    let directory = tempfile::tempdir().unwrap();
+    let bundle = directory.path().join("bundle");
+
+    run(Language::C, HELLO)
+        .unwrap()
+        .record(&bundle)
+        .failure()
+        .code(3);
+
+    for name in ["source", "compiler", "compilation.status", "command"] {
+        assert!(bundle.join(name).exists(), "no `{}` in the bundle", name);
+    }
+
+    assert_eq!(
+        fs::read_to_string(bundle.join("status")).unwrap(),
+        "exit 3\n"
+    );
+    assert_eq!(
+        fs::read_to_string(bundle.join("stdout")).unwrap(),
+        "Hello, World!\n"
+    );
+
+    // The replayed output is the one of the bundle, not of a new run.
+    fs::write(bundle.join("stdout"), "Hello, CI!\n").unwrap();
+
+    run(Language::C, HELLO)
+        .unwrap()
+        .replay(&bundle)
+        .failure()
+        .code(3)
+        .stdout("Hello, CI!\n");
+
+    let other = panic::catch_unwind(|| {
+        (assert_c! {
+            int main() {
+                return 0;
+            }
+        })
+        .replay(&bundle)
+        .success();
+    })
+    .unwrap_err();
+
+    assert!(other
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("has been recorded from another source"));
+}
@@ -0,0 +1,112 @@
+use std::{env, fs, panic};
+use wasmer_inline_c::{assert_c, run, Language};
+
+#[test]
+fn test_report() {
+    let directory = tempfile::tempdir().unwrap();
+    let report = directory.path().join("report.jsonl");
+    let junit = directory.path().join("junit.xml");
+    env::set_var("INLINE_C_RS_REPORT", &report);
+    env::set_var("INLINE_C_RS_JUNIT", &junit);
+
+    (assert_c! {
+        #inline_c_rs NAME: "passing"
+
+        int main() {
+            return 0;
+        }
+    })
+    .success();
+
+    let failing = panic::catch_unwind(|| {
+        (assert_c! {
+            #inline_c_rs NAME: "failing"
+
+            int main() {
+                return 1;
+            }
+        })
+        .success();
+    });
+    assert!(failing.is_err());
+
+    let not_compiling = panic::catch_unwind(|| {
+        (assert_c! {
+            #inline_c_rs NAME: "not_compiling"
+
+            int main() {
+                return undeclared;
+            }
+        })
+        .success();
+    });
+    assert!(not_compiling.is_err());
+
+    let mut not_run = assert_c! {
+        #inline_c_rs NAME: "not_run"
+
+        int main() {
+            return 0;
+        }
+    };
+    assert!(not_run.skip_if(|| true).is_none());
+    drop(not_run);
+
+    let cache = directory.path().join("cache");
+    let cached = format!(
+        "#inline_c_rs NAME: \"cached\"\n#inline_c_rs CACHE: \"{}\"\n\nint main() {{\n    return 0;\n}}\n",
+        cache.display()
+    );
+
+    for _ in 0..2 {
+        run(Language::C, &cached).unwrap().success();
+    }
+
+    let records = fs::read_to_string(&report).unwrap();
+    let record = |name: &str| {
+        records
+            .lines()
+            .find(|record| record.contains(&format!("\"name\":\"{}\"", name)))
+            .unwrap_or_else(|| panic!("no record of `{}` in:\n{}", name, records))
+            .to_string()
+    };
+
+    assert!(record("passing").contains("\"compiled\":true"));
+    assert!(record("passing").contains("\"exit_code\":0"));
+    assert!(record("passing").contains("\"passed\":true,\"not_run\":false"));
+    assert!(record("failing").contains("\"exit_code\":1"));
+    assert!(record("failing").contains("\"passed\":false,\"not_run\":false"));
+    assert!(record("not_run").contains("\"compiled\":false"));
+    assert!(record("not_run").contains("\"passed\":false,\"not_run\":true"));
+
+    let compile_time_ms = record("passing")
+        .split("\"compile_time_ms\":")
+        .nth(1)
+        .and_then(|rest| rest.split(',').next())
+        .map(|value| value.parse::<u128>())
+        .unwrap();
+    assert!(compile_time_ms.is_ok());
+    assert!(record("passing").contains("\"cache_hit\":false"));
+
+    let cached = records
+        .lines()
+        .filter(|record| record.contains("\"name\":\"cached\""))
+        .collect::<Vec<_>>();
+
+    assert_eq!(cached.len(), 2);
+    assert!(cached[0].contains("\"cache_hit\":false"));
+    assert!(cached[1].contains("\"cache_hit\":true"));
+
+    let junit = fs::read_to_string(&junit).unwrap();
+
+    assert!(
+        junit.contains("tests=\"6\" errors=\"1\" failures=\"1\" skipped=\"1\""),
+        "{}",
+        junit
+    );
+    assert!(junit.contains("<skipped message=\"The snippet has not been run\"/>"));
+    assert!(junit.contains("<error type=\"compile\" message=\"The snippet failed to compile\"/>"));
+    assert!(
+        junit.contains("<failure type=\"run\" message=\"The assertion failed (exit code: 1)\"/>")
+    );
+}
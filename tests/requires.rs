@@ -0,0 +1,30 @@
+use wasmer_inline_c::{assert_c, Assert};
+
+fn unsatisfied() -> Result<(), &'static str> {
+    (assert_c! {
+        #inline_c_rs REQUIRES: "nvidia>=1000"
+
+        int main() {
+            return 1;
+        }
+    })
+    .success();
+
+    Err("the assertion has not been skipped")
+}
+
+/// A snippet without a `REQUIRES` or `SKIP_IF_ENV` directive is not
+/// guarded, and can be returned.
+fn unguarded() -> Assert {
+    assert_c! {
+        int main() {
+            return 0;
+        }
+    }
+}
+
+#[test]
+fn test_requires() {
+    unsatisfied().unwrap();
+    unguarded().success();
+}
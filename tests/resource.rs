@@ -0,0 +1,64 @@
+#![cfg(windows)]
+
+use std::fs;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_resource() {
+    let directory = tempfile::tempdir().unwrap();
+    let script = directory.path().join("strings.rc");
+    fs::write(
+        &script,
+        "STRINGTABLE\nBEGIN\n    42 \"Hello, World!\"\nEND\n",
+    )
+    .unwrap();
+
+    (assert_c! {
+        #include <stdio.h>
+        #include <windows.h>
+
+        int main() {
+            char string[32];
+
+            if (LoadStringA(GetModuleHandleA(NULL), 42, string, sizeof(string)) == 0) {
+                return 1;
+            }
+
+            printf("%s\n", string);
+
+            return 0;
+        }
+    })
+    .resource(&script)
+    .success()
+    .stdout("Hello, World!\n");
+}
+
+#[test]
+fn test_manifest() {
+    let directory = tempfile::tempdir().unwrap();
+    let manifest = directory.path().join("app.manifest");
+    fs::write(
+        &manifest,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<assembly xmlns="urn:schemas-microsoft-com:asm.v1" manifestVersion="1.0">
+  <application xmlns="urn:schemas-microsoft-com:asm.v3">
+    <windowsSettings>
+      <activeCodePage xmlns="http://schemas.microsoft.com/SMI/2019/WindowsSettings">UTF-8</activeCodePage>
+    </windowsSettings>
+  </application>
+</assembly>
+"#,
+    )
+    .unwrap();
+
+    (assert_c! {
+        #include <windows.h>
+
+        int main() {
+            return GetACP() == CP_UTF8 ? 0 : 1;
+        }
+    })
+    .manifest(&manifest)
+    .success();
+}
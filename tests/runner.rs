@@ -0,0 +1,115 @@
+//! The runners are given `echo` as their program, so that they print
+//! the arguments they would run the program with.
+
+#![cfg(unix)]
+
+use predicates::prelude::*;
+use wasmer_inline_c::{assert_c, runner::Runner};
+
+#[test]
+fn test_wasmer() {
+    assert!(matches!(
+        Runner::parse("wasmer").unwrap(),
+        Runner::Wasmer { program, .. } if program.to_str() == Some("wasmer")
+    ));
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::parse("wasmer:echo").unwrap())
+    .success()
+    .stdout(predicate::str::is_match(r"^run /\S+\n$").unwrap());
+}
+
+#[test]
+fn test_wasi() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::parse("wasmer:echo").unwrap())
+    .wasi_dir("/tmp/fixtures", "/fixtures")
+    .wasi_env("GREETING", "hello")
+    .args(["--verbose", "input"])
+    .success()
+    .stdout(
+        predicate::str::starts_with("run --mapdir /fixtures:/tmp/fixtures --env GREETING=hello /")
+            .and(predicate::str::ends_with(" -- --verbose input\n")),
+    );
+}
+
+#[test]
+#[should_panic(expected = "WASI can only be configured for WebAssembly targets")]
+fn test_wasi_outside_of_webassembly() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .wasi_env("GREETING", "hello");
+}
+
+#[test]
+fn test_wine() {
+    assert!(matches!(
+        Runner::parse("wine").unwrap(),
+        Runner::Wine { program } if program.to_str() == Some("wine")
+    ));
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::parse("wine:echo").unwrap())
+    .arg("--verbose")
+    .success()
+    .stdout(predicate::str::is_match(r"^/\S+ --verbose\n$").unwrap());
+}
+
+#[test]
+fn test_qemu() {
+    assert!(matches!(
+        Runner::parse("qemu:qemu-aarch64").unwrap(),
+        Runner::Qemu { program, sysroot: None } if program.to_str() == Some("qemu-aarch64")
+    ));
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::parse("qemu:echo:/usr/aarch64-linux-gnu").unwrap())
+    .arg("--verbose")
+    .success()
+    .stdout(predicate::str::is_match(r"^-L /usr/aarch64-linux-gnu /\S+ --verbose\n$").unwrap());
+}
+
+#[test]
+fn test_adb() {
+    assert!(matches!(
+        Runner::parse("adb://emulator-5554").unwrap(),
+        Runner::Adb { serial: Some(serial), .. } if serial == "emulator-5554"
+    ));
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::Adb {
+        program: "echo".into(),
+        serial: Some("emulator-5554".to_string()),
+    })
+    .arg("--verbose")
+    .success()
+    .stdout(
+        predicate::str::is_match(
+            r"^-s emulator-5554 shell cd /data/local/tmp && env .*'/data/local/tmp/inline-c-rs-\S+' '--verbose'\n$",
+        )
+        .unwrap(),
+    );
+}
@@ -0,0 +1,57 @@
+use std::{fs, path::Path, process::Command};
+use wasmer_inline_c::{assert_c, run, runner::Runner, Language};
+
+#[test]
+#[should_panic(expected = "cannot be sandboxed on this host")]
+fn test_unsupported_runner() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::Adb {
+        program: "echo".into(),
+        serial: None,
+    })
+    .sandbox(true)
+    .success();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_sandbox() {
+    if !Command::new("bwrap")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success())
+    {
+        eprintln!("Skipped: no Bubblewrap");
+
+        return;
+    }
+
+    let directory = tempfile::tempdir().unwrap();
+    let outside = Path::new(env!("CARGO_TARGET_TMPDIR")).join("outside-of-the-sandbox");
+    let _ = fs::remove_file(&outside);
+
+    run(
+        Language::C,
+        &format!(
+            "#include <stdio.h>\n\n\
+             int main() {{\n    \
+             FILE *inside = fopen(\"inside\", \"w\");\n    \
+             FILE *outside = fopen(\"{}\", \"w\");\n\n    \
+             return (inside != NULL) + 2 * (outside != NULL);\n\
+             }}\n",
+            outside.display()
+        ),
+    )
+    .unwrap()
+    .current_dir(directory.path())
+    .sandbox(true)
+    .failure()
+    .code(1);
+
+    assert!(directory.path().join("inside").exists());
+    assert!(!outside.exists());
+}
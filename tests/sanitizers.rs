@@ -0,0 +1,140 @@
+#![cfg(target_os = "linux")]
+
+use std::{fs, panic};
+use wasmer_inline_c::assert_c;
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[test]
+fn test_leak_sanitizer() {
+    let leak = panic::catch_unwind(|| {
+        (assert_c! {
+            #include <stdlib.h>
+
+            int main() {
+                return malloc(16) == NULL;
+            }
+        })
+        .leak_sanitizer(true)
+        .assert();
+    })
+    .unwrap_err();
+    let message = panic_message(leak);
+
+    assert!(
+        message.starts_with("LeakSanitizer has detected memory leaks"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_lsan_suppressions() {
+    let directory = tempfile::tempdir().unwrap();
+    let suppressions = directory.path().join("lsan.supp");
+    fs::write(&suppressions, "leak:third_party_init\n").unwrap();
+
+    (assert_c! {
+        #include <stdlib.h>
+
+        __attribute__((noinline)) void *third_party_init(void) {
+            return malloc(16);
+        }
+
+        int main() {
+            return third_party_init() == NULL;
+        }
+    })
+    .leak_sanitizer(true)
+    .lsan_suppressions(&suppressions)
+    .success();
+}
+
+#[test]
+fn test_thread_sanitizer() {
+    let race = panic::catch_unwind(|| {
+        (assert_c! {
+            #include <pthread.h>
+
+            int counter = 0;
+
+            void *increment(void *argument) {
+                counter += 1;
+
+                return argument;
+            }
+
+            int main() {
+                pthread_t threads[2];
+
+                for (int i = 0; i < 2; ++i) {
+                    pthread_create(&threads[i], NULL, increment, NULL);
+                }
+
+                for (int i = 0; i < 2; ++i) {
+                    pthread_join(threads[i], NULL);
+                }
+
+                return 0;
+            }
+        })
+        .thread_sanitizer(true)
+        .assert();
+    })
+    .unwrap_err();
+    let message = panic_message(race);
+
+    assert!(
+        message.starts_with(
+            "ThreadSanitizer has detected data races:\nWARNING: ThreadSanitizer: data race"
+        ),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("SUMMARY: ThreadSanitizer: data race"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn test_tsan_suppressions() {
+    let directory = tempfile::tempdir().unwrap();
+    let suppressions = directory.path().join("tsan.supp");
+    fs::write(&suppressions, "race:legacy_increment\n").unwrap();
+
+    (assert_c! {
+        #include <pthread.h>
+
+        int counter = 0;
+
+        __attribute__((noinline)) void *legacy_increment(void *argument) {
+            counter += 1;
+
+            return argument;
+        }
+
+        int main() {
+            pthread_t threads[2];
+
+            for (int i = 0; i < 2; ++i) {
+                pthread_create(&threads[i], NULL, legacy_increment, NULL);
+            }
+
+            for (int i = 0; i < 2; ++i) {
+                pthread_join(threads[i], NULL);
+            }
+
+            return 0;
+        }
+    })
+    .thread_sanitizer(true)
+    .tsan_suppressions(&suppressions)
+    .success();
+}
@@ -0,0 +1,60 @@
+use wasmer_inline_c::{assert_c, runner::Runner};
+
+#[test]
+#[should_panic(expected = "cannot be scheduled")]
+fn test_unsupported_runner() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::Adb {
+        program: "echo".into(),
+        serial: None,
+    })
+    .priority(5)
+    .success();
+}
+
+/// Print the niceness and the number of CPUs the program can run on.
+#[cfg(target_os = "linux")]
+const SCHEDULING: &str = r#"
+#define _GNU_SOURCE
+#include <sched.h>
+#include <stdio.h>
+#include <sys/resource.h>
+
+int main() {
+    cpu_set_t cpus;
+    sched_getaffinity(0, sizeof(cpus), &cpus);
+
+    printf("%d %d\n", getpriority(PRIO_PROCESS, 0), CPU_COUNT(&cpus));
+
+    return 0;
+}
+"#;
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_priority_and_cpu_affinity() {
+    use wasmer_inline_c::{run, Language};
+
+    let output = run(Language::C, SCHEDULING).unwrap().output().unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let niceness = output.split(' ').next().unwrap().parse::<i32>().unwrap();
+
+    run(Language::C, SCHEDULING)
+        .unwrap()
+        .priority(5)
+        .cpu_affinity(&[0])
+        .success()
+        .stdout(format!("{} 1\n", (niceness + 5).min(19)));
+
+    run(
+        Language::C,
+        &format!("#inline_c_rs CPU_AFFINITY: \"0\"\n{}", SCHEDULING),
+    )
+    .unwrap()
+    .success()
+    .stdout(format!("{} 1\n", niceness));
+}
@@ -0,0 +1,28 @@
+use std::env;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_scoped_variables() {
+    env::set_var("INLINE_C_RS_CFLAGS", "-DSCOPE=1");
+    env::set_var("INLINE_C_RS_SCOPED_VARIABLES__CFLAGS", "-DSCOPE=2");
+    env::set_var("INLINE_C_RS_INTEGRATION__CFLAGS", "-DSCOPE=3");
+
+    (assert_c! {
+        int main() {
+            return SCOPE;
+        }
+    })
+    .failure()
+    .code(2);
+
+    // The directives still take precedence.
+    (assert_c! {
+        #inline_c_rs CFLAGS: "-DSCOPE=4"
+
+        int main() {
+            return SCOPE;
+        }
+    })
+    .failure()
+    .code(4);
+}
@@ -0,0 +1,49 @@
+//! `xcrun` is replaced by a script printing its arguments and the
+//! variables forwarded to the simulated program.
+
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::PermissionsExt};
+use wasmer_inline_c::{assert_c, runner::Runner};
+
+#[test]
+fn test_simctl() {
+    assert!(matches!(
+        Runner::parse("simctl").unwrap(),
+        Runner::Simctl { device } if device == "booted"
+    ));
+
+    let bin = tempfile::tempdir().unwrap();
+    let xcrun = bin.path().join("xcrun");
+    fs::write(
+        &xcrun,
+        "#!/bin/sh\necho \"$@\"\necho \"$SIMCTL_CHILD_GREETING\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&xcrun, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let path = env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.path().to_path_buf()];
+    paths.extend(env::split_paths(&path));
+    env::set_var("PATH", env::join_paths(paths).unwrap());
+
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+    assert.runner(Runner::parse("simctl://iPhone 12").unwrap());
+
+    let compiled = assert.compile();
+    let executable = compiled.executable().display().to_string();
+
+    compiled
+        .run()
+        .arg("--verbose")
+        .env("GREETING", "hello")
+        .success()
+        .stdout(format!(
+            "simctl spawn iPhone 12 {} --verbose\nhello\n",
+            executable
+        ));
+}
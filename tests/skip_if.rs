@@ -0,0 +1,73 @@
+use std::env;
+use wasmer_inline_c::{
+    assert_c,
+    run::{skip_reason, Options},
+    Language,
+};
+
+#[test]
+fn test_skip_if() {
+    let mut assert = assert_c! {
+        int main() {
+            return 1;
+        }
+    };
+
+    assert!(assert
+        .skip_if(|| true)
+        .map(|assert| assert.success())
+        .is_none());
+    assert!(assert
+        .skip_if(|| false)
+        .map(|assert| assert.failure())
+        .is_some());
+}
+
+fn skipped() -> Result<(), &'static str> {
+    (assert_c! {
+        #inline_c_rs SKIP_IF_ENV: "INLINE_C_RS_TEST_UNSET, INLINE_C_RS_TEST_SKIP_IF"
+
+        int main() {
+            return 1;
+        }
+    })
+    .success();
+
+    Err("the assertion has not been skipped")
+}
+
+#[test]
+fn test_skip_if_env_directive() {
+    env::set_var("INLINE_C_RS_TEST_SKIP_IF", "yes");
+
+    skipped().unwrap();
+}
+
+#[test]
+fn test_skip_if_env_reports_the_first_variable() {
+    env::set_var("INLINE_C_RS_TEST_FIRST", "1");
+    env::set_var("INLINE_C_RS_TEST_SECOND", "1");
+
+    for (names, first) in [
+        (
+            "INLINE_C_RS_TEST_FIRST, INLINE_C_RS_TEST_SECOND",
+            "INLINE_C_RS_TEST_FIRST",
+        ),
+        (
+            "INLINE_C_RS_TEST_SECOND, INLINE_C_RS_TEST_FIRST",
+            "INLINE_C_RS_TEST_SECOND",
+        ),
+    ] {
+        let program = format!(
+            "#inline_c_rs SKIP_IF_ENV: \"{}\"\nint main() {{ return 0; }}\n",
+            names
+        );
+        let reason = skip_reason(Language::C, &program, &Options::default()).unwrap();
+
+        assert!(
+            reason.starts_with(&format!("the environment variable `{}` is set", first)),
+            "{}",
+            reason
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! `scp` and `ssh` are replaced by scripts printing their arguments.
+
+#![cfg(unix)]
+
+use predicates::prelude::*;
+use std::{env, fs, os::unix::fs::PermissionsExt, path::Path};
+use wasmer_inline_c::{assert_c, runner::Runner};
+
+fn fake(directory: &Path, program: &str, script: &str) {
+    let path = directory.join(program);
+    fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn test_ssh() {
+    assert!(Runner::parse("ssh://ci@arm64-box").is_err());
+
+    let bin = tempfile::tempdir().unwrap();
+    let copied = bin.path().join("copied");
+    fake(
+        bin.path(),
+        "scp",
+        &format!("echo \"$@\" >> {}", copied.display()),
+    );
+    fake(bin.path(), "ssh", "printf '%s\\n' \"$@\"");
+
+    let path = env::var_os("PATH").unwrap_or_default();
+    let mut paths = vec![bin.path().to_path_buf()];
+    paths.extend(env::split_paths(&path));
+    env::set_var("PATH", env::join_paths(paths).unwrap());
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .runner(Runner::parse("ssh://ci@arm64-box/tmp/inline c").unwrap())
+    .arg("it's")
+    .success()
+    .stdout(
+        predicate::str::starts_with("ci@arm64-box\ncd 'tmp/inline c' && env ")
+            .and(predicate::str::contains(" 'tmp/inline c/inline-c-rs-"))
+            .and(predicate::str::ends_with(" 'it'\\''s'\n")),
+    );
+
+    let copied = fs::read_to_string(copied).unwrap();
+
+    assert!(copied.starts_with("-q /"), "{}", copied);
+    assert!(
+        copied.ends_with(" ci@arm64-box:tmp/inline c/\n"),
+        "{}",
+        copied
+    );
+}
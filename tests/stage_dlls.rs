@@ -0,0 +1,105 @@
+//! `answer.dll` imports `base.dll`, and both are kept away from their
+//! import libraries, like in the `bin` and `lib` directories of an
+//! installed library.
+
+#![cfg(windows)]
+
+use std::{fs, path::Path};
+use wasmer_inline_c::{run, Language};
+
+fn compiler() -> cc::Tool {
+    let target = target_lexicon::HOST.to_string();
+
+    cc::Build::new()
+        .cargo_metadata(false)
+        .target(&target)
+        .host(&target)
+        .opt_level(0)
+        .get_compiler()
+}
+
+/// Build a DLL with its import library.
+fn compile(compiler: &cc::Tool, directory: &Path, args: &[&str]) {
+    let mut command = compiler.to_command();
+
+    if compiler.is_like_msvc() {
+        command.args(["/nologo", "/LD"]);
+    } else {
+        command.arg("-shared");
+    }
+
+    assert!(command
+        .current_dir(directory)
+        .args(args)
+        .status()
+        .unwrap()
+        .success());
+}
+
+#[test]
+fn test_stage_dlls() {
+    let directory = tempfile::tempdir().unwrap();
+    let lib = directory.path();
+    let bin = directory.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+
+    fs::write(
+        lib.join("base.c"),
+        "__declspec(dllexport) int base(void) { return 40; }\n",
+    )
+    .unwrap();
+    fs::write(
+        lib.join("answer.c"),
+        "__declspec(dllimport) int base(void);\n\
+         __declspec(dllexport) int answer(void) { return base() + 2; }\n",
+    )
+    .unwrap();
+
+    let compiler = compiler();
+
+    if compiler.is_like_msvc() {
+        compile(&compiler, lib, &["base.c", "/Febase.dll"]);
+        compile(&compiler, lib, &["answer.c", "base.lib", "/Feanswer.dll"]);
+    } else {
+        compile(
+            &compiler,
+            lib,
+            &["base.c", "-o", "base.dll", "-Wl,--out-implib,libbase.dll.a"],
+        );
+        compile(
+            &compiler,
+            lib,
+            &[
+                "answer.c",
+                "-o",
+                "answer.dll",
+                "-L.",
+                "-lbase",
+                "-Wl,--out-implib,libanswer.dll.a",
+            ],
+        );
+    }
+
+    for dll in ["base.dll", "answer.dll"] {
+        fs::rename(lib.join(dll), bin.join(dll)).unwrap();
+    }
+
+    let mut assert = run(
+        Language::C,
+        "int answer(void);\n\nint main() {\n    return answer();\n}\n",
+    )
+    .unwrap();
+    assert
+        .link_search_path(lib)
+        .link_library("answer")
+        .dll_search_path(&bin)
+        .stage_dlls(true)
+        .failure()
+        .code(42);
+
+    let executable_directory = assert.executable().parent().unwrap().to_path_buf();
+
+    for dll in ["base.dll", "answer.dll"] {
+        assert!(assert.artifacts().contains(&executable_directory.join(dll)));
+    }
+}
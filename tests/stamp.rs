@@ -0,0 +1,35 @@
+use std::env;
+use wasmer_inline_c::{assert_c, stamp};
+
+#[test]
+fn test_stamp() {
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+    assert.stamp(true);
+
+    let stamp = stamp::read(assert.executable()).unwrap().unwrap();
+
+    assert!(!stamp.hash.is_empty());
+    assert!(!stamp.compiler.is_empty());
+    assert.success();
+}
+
+#[test]
+fn test_unstamped() {
+    let mut assert = assert_c! {
+        int main() {
+            return 0;
+        }
+    };
+
+    assert_eq!(stamp::read(assert.executable()).unwrap(), None);
+}
+
+#[test]
+fn test_marker_of_the_crate() {
+    // This test binary links the crate, and thus contains the marker.
+    assert_eq!(stamp::read(env::current_exe().unwrap()).unwrap(), None);
+}
@@ -0,0 +1,47 @@
+use std::{fs, panic};
+use wasmer_inline_c::{assert_c, run, Language};
+
+#[test]
+fn test_stress() {
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .stress(20, 4);
+
+    let runs = tempfile::tempdir().unwrap();
+    let runs = runs.path().join("runs");
+
+    // Fail from the fifth run.
+    let failure = panic::catch_unwind(|| {
+        run(
+            Language::C,
+            &format!(
+                "#include <stdio.h>\n\n\
+                 int main() {{\n    \
+                 FILE *runs = fopen(\"{}\", \"a\");\n    \
+                 fputc('.', runs);\n    \
+                 long run = ftell(runs);\n    \
+                 fclose(runs);\n\n    \
+                 printf(\"run %ld\\n\", run);\n\n    \
+                 return run >= 5;\n\
+                 }}\n",
+                runs.display()
+            ),
+        )
+        .unwrap()
+        .stress(10, 1);
+    })
+    .unwrap_err();
+
+    let failure = failure.downcast_ref::<String>().unwrap();
+
+    assert!(
+        failure.starts_with("The run 5 of 10 has failed with"),
+        "{}",
+        failure
+    );
+    assert!(failure.contains("=== stdout\nrun 5\n"), "{}", failure);
+    assert_eq!(fs::read_to_string(&runs).unwrap(), ".....");
+}
@@ -0,0 +1,55 @@
+use wasmer_inline_c::{assert_c_str, assert_cxx_str};
+
+#[test]
+fn test_assert_c_str() {
+    // The line splice and the spacing of the output are kept byte for
+    // byte.
+    (assert_c_str!(
+        r#"
+#inline_c_rs GREETING: "Hello"
+
+#include <stdio.h>
+#include <stdlib.h>
+
+#define GREET(name) \
+    printf("%s,   %s!\n", getenv("GREETING"), name)
+
+int main() {
+    GREET("World");
+
+    return 0;
+}
+"#
+    ))
+    .success()
+    .stdout("Hello,   World!\n");
+}
+
+#[test]
+fn test_assert_cxx_str() {
+    (assert_cxx_str!(
+        r#"
+#include <iostream>
+
+int main() {
+    std::cout << "Hello, World!" << std::endl;
+
+    return 0;
+}
+"#
+    ))
+    .success()
+    .stdout("Hello, World!\n");
+}
+
+#[test]
+fn test_escapes() {
+    // The escapes are unescaped by Rust, before compiling the program,
+    // and the line continuation skips the indentation of the next line.
+    (assert_c_str!(
+        "#include <stdio.h>\n\
+         int main() { printf(\"\x41 \u{00e9}\\n\"); return 0; }\n"
+    ))
+    .success()
+    .stdout("A é\n");
+}
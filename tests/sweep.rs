@@ -0,0 +1,27 @@
+//! The first snippet of a run removes the build directories of the dead
+//! runs, so this file must hold a single test.
+
+use std::{fs, path::Path};
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_sweep_of_the_dead_runs() {
+    let parent = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .parent()
+        .unwrap()
+        .join("inline-c")
+        .join("sweep");
+    // No process has this PID, which is above the limit of the systems.
+    let dead_run = parent.join("run-4000000000");
+    fs::create_dir_all(&dead_run).unwrap();
+    fs::write(dead_run.join("inline-c-rs-leaked.c"), "").unwrap();
+
+    (assert_c! {
+        int main() {
+            return 0;
+        }
+    })
+    .success();
+
+    assert!(!dead_run.exists());
+}
@@ -0,0 +1,140 @@
+//! The programs with a syntax error are checked in a crate, written in
+//! a temporary directory, whose compilation must fail.
+
+use std::{env, fs, path::Path, process::Command};
+use wasmer_inline_c::assert_c_str;
+
+/// The errors reported by `cargo check` for a crate whose library is
+/// `library`, using the macros.
+fn check(library: &str) -> String {
+    let directory = tempfile::tempdir().unwrap();
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+
+    fs::write(
+        directory.path().join("Cargo.toml"),
+        format!(
+            "[package]\n\
+             name = \"syntax\"\n\
+             version = \"0.0.0\"\n\
+             edition = \"2021\"\n\n\
+             [dependencies]\n\
+             wasmer-inline-c-macro = {{ path = {:?} }}\n",
+            manifest_dir.join("macros")
+        ),
+    )
+    .unwrap();
+    // The same versions of the dependencies, already built.
+    if manifest_dir.join("Cargo.lock").exists() {
+        fs::copy(
+            manifest_dir.join("Cargo.lock"),
+            directory.path().join("Cargo.lock"),
+        )
+        .unwrap();
+    }
+
+    fs::create_dir(directory.path().join("src")).unwrap();
+    fs::write(directory.path().join("src").join("lib.rs"), library).unwrap();
+
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--quiet", "--message-format", "short"])
+        .current_dir(directory.path())
+        .env(
+            "CARGO_TARGET_DIR",
+            Path::new(env!("CARGO_TARGET_TMPDIR")).join("syntax"),
+        )
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn test_syntax_errors() {
+    let errors = check(
+        r##"
+use wasmer_inline_c_macro::{assert_c, assert_c_str};
+
+pub fn unterminated_conditional() {
+    assert_c! {
+        #if defined(_WIN32)
+        int main() {
+            return 0;
+        }
+    };
+}
+
+pub fn unclosed_bracket() {
+    assert_c_str!(r#"int main() {
+    return 0;
+"#);
+}
+
+pub fn unterminated_string() {
+    assert_c_str!(r#"int main() { return "0; }"#);
+}
+"##,
+    );
+
+    assert!(
+        errors.contains("src/lib.rs:6:9: error: `#if` without `#endif`"),
+        "{}",
+        errors
+    );
+    assert!(
+        errors
+            .contains("src/lib.rs:14:19: error: unclosed `{`, at line 1, column 12 of the program"),
+        "{}",
+        errors
+    );
+    assert!(
+        errors.contains("src/lib.rs:20:19: error: unterminated string literal, at line 1, column 21 of the program"),
+        "{}",
+        errors
+    );
+}
+
+#[test]
+fn test_crlf_line_continuation() {
+    // The line continuation skips the CRLF line break, and the
+    // indentation of the next line.
+    let errors = check(
+        "use wasmer_inline_c_macro::assert_c_str;\r\n\
+         \r\n\
+         pub fn unclosed_bracket() {\r\n    \
+             assert_c_str!(\"int main() {\\\r\n        return 0;\\n\");\r\n\
+         }\r\n",
+    );
+
+    assert!(
+        errors
+            .contains("src/lib.rs:4:19: error: unclosed `{`, at line 1, column 12 of the program"),
+        "{}",
+        errors
+    );
+}
+
+#[test]
+fn test_balanced() {
+    (assert_c_str!(
+        r#"
+#include <string.h>
+
+int main() {
+    /* } */
+    const char *brackets = "}])";
+#if 0
+    if (1) {
+#else
+    if (strlen(brackets) == 3) {
+#endif
+        return 0;
+    }
+
+    return 1;
+}
+"#
+    ))
+    .success();
+}
@@ -0,0 +1,35 @@
+//! The `INLINE_C_RS_SKIP_TAGS` variable skips the snippets by their
+//! tags.
+
+use std::env;
+use wasmer_inline_c::{assert_c, run, Language};
+
+fn skipped() -> Result<(), &'static str> {
+    (assert_c!(tags = ["fast", "gpu"], {
+        int main() {
+            return 1;
+        }
+    }))
+    .success();
+
+    Err("the snippet has not been skipped")
+}
+
+#[test]
+fn test_skip_tags() {
+    env::set_var("INLINE_C_RS_SKIP_TAGS", "slow, gpu");
+
+    skipped().unwrap();
+
+    (assert_c!(tags = ["fast"], {
+        int main() {
+            return 0;
+        }
+    }))
+    .success();
+
+    let mut assert = run(Language::C, "int main() {\n    return 1;\n}\n").unwrap();
+
+    assert!(assert.tags(&["slow"]).is_none());
+    assert!(assert.tags(&["fast", "cpu"]).is_some());
+}
@@ -0,0 +1,31 @@
+use wasmer_inline_c::{assert_c, run, Language};
+
+#[test]
+fn test_template_of_the_macros() {
+    (assert_c!(template(TYPE = "long long", VALUE = "42"), {
+        #include <stdio.h>
+
+        int main() {
+            {{TYPE}} value = {{VALUE}};
+            int matrix[1][1] = {{0}};
+
+            printf("%d %d\n", (int) sizeof(value) >= 8, (int) value + matrix[0][0]);
+
+            return 0;
+        }
+    }))
+    .success()
+    .stdout("1 42\n");
+}
+
+#[test]
+fn test_template() {
+    run(
+        Language::C,
+        "#include <stdio.h>\n\nint main() {\n    printf(\"{{greeting}}\\n\");\n\n    return 0;\n}\n",
+    )
+    .unwrap()
+    .template("greeting", "Hello")
+    .success()
+    .stdout("Hello\n");
+}
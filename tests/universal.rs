@@ -0,0 +1,43 @@
+use wasmer_inline_c::{run, Language};
+
+const PROGRAM: &str = r#"#inline_c_rs UNIVERSAL: "1"
+
+int main() {
+    return 0;
+}
+"#;
+
+#[cfg(target_os = "macos")]
+#[test]
+fn test_universal() {
+    use std::process::Command;
+
+    let mut assert = run(Language::C, PROGRAM).unwrap();
+    let info = Command::new("lipo")
+        .arg("-info")
+        .arg(assert.executable())
+        .output()
+        .unwrap();
+    let info = String::from_utf8_lossy(&info.stdout);
+
+    assert!(
+        info.contains("x86_64") && info.contains("arm64"),
+        "{}",
+        info
+    );
+    assert.success();
+}
+
+#[cfg(not(target_os = "macos"))]
+#[test]
+fn test_universal_outside_of_macos() {
+    let error = run(Language::C, PROGRAM).err().unwrap();
+
+    assert!(
+        error
+            .to_string()
+            .starts_with("universal binaries can only be built for macOS"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,45 @@
+use std::env;
+use wasmer_inline_c::assert_c;
+
+#[test]
+fn test_unset() {
+    env::set_var("INLINE_C_RS_CFLAGS", "-DGLOBAL_C");
+    env::set_var("INLINE_C_RS_CPPFLAGS", "-DGLOBAL_CPP");
+    env::set_var("INLINE_C_RS_GREETING", "hello");
+
+    (assert_c! {
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main() {
+            printf("%s\n", getenv("GREETING"));
+
+        #if defined(GLOBAL_C) && defined(GLOBAL_CPP)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success()
+    .stdout("hello\n");
+
+    (assert_c! {
+        #inline_c_rs unset CFLAGS, CPPFLAGS GREETING
+        #inline_c_rs CFLAGS: "-DLOCAL"
+        #include <stdio.h>
+        #include <stdlib.h>
+
+        int main() {
+            printf("%s\n", getenv("GREETING") ? "set" : "unset");
+
+        #if !defined(GLOBAL_C) && !defined(GLOBAL_CPP) && defined(LOCAL)
+            return 0;
+        #else
+            return 1;
+        #endif
+        }
+    })
+    .success()
+    .stdout("unset\n");
+}
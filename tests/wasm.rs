@@ -0,0 +1,27 @@
+#![cfg(feature = "wasm")]
+
+use wasmer_inline_c::wasm::WasmModule;
+
+/// A module importing `env.f`, and exporting `run`, returning 42.
+const MODULE: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // Header.
+    0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f, // Types: `() -> i32`.
+    0x02, 0x09, 0x01, 0x03, b'e', b'n', b'v', 0x01, b'f', 0x00, 0x00, // Imports.
+    0x03, 0x02, 0x01, 0x00, // Functions.
+    0x07, 0x07, 0x01, 0x03, b'r', b'u', b'n', 0x00, 0x01, // Exports.
+    0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, 0x2a, 0x0b, // Code.
+];
+
+#[test]
+fn test_parse() {
+    let module = WasmModule::parse(MODULE).unwrap();
+
+    assert_eq!(module.exports, ["run"]);
+    assert_eq!(module.imports, [("env".to_string(), "f".to_string())]);
+    assert!(module.required_features.is_empty());
+}
+
+#[test]
+fn test_invalid_module() {
+    assert!(WasmModule::parse(&MODULE[..MODULE.len() - 1]).is_err());
+}
@@ -0,0 +1,83 @@
+//! `cargo` is replaced by a script reporting the artifact of a
+//! `staticlib` built with `cc` and `ar`, rather than building a crate
+//! in the middle of the tests.
+
+#![cfg(target_os = "linux")]
+
+use std::{env, fs, os::unix::fs::PermissionsExt, panic, path::Path, process::Command};
+use wasmer_inline_c::{run, Language};
+
+fn workspace(directory: &Path) {
+    let include = directory.join("include");
+    fs::create_dir(&include).unwrap();
+    fs::write(include.join("add.h"), "int add(int a, int b);\n").unwrap();
+    fs::write(
+        directory.join("add.c"),
+        "int add(int a, int b) { return a + b; }\n",
+    )
+    .unwrap();
+
+    assert!(Command::new("cc")
+        .current_dir(directory)
+        .args(["-c", "add.c", "-o", "add.o"])
+        .status()
+        .unwrap()
+        .success());
+    assert!(Command::new("ar")
+        .current_dir(directory)
+        .args(["rcs", "libadd_ffi.a", "add.o"])
+        .status()
+        .unwrap()
+        .success());
+
+    let cargo = directory.join("cargo");
+    fs::write(
+        &cargo,
+        format!(
+            "#!/bin/sh\n\
+             case \"$*\" in\n\
+             *\"--package add-ffi\"*)\n\
+             \techo '{{\"reason\":\"compiler-artifact\",\"manifest_path\":\"{directory}/Cargo.toml\",\"target\":{{\"kind\":[\"staticlib\"],\"name\":\"add_ffi\"}},\"filenames\":[\"{directory}/libadd_ffi.a\"]}}'\n\
+             \techo '{{\"reason\":\"build-finished\",\"success\":true}}';;\n\
+             esac\n",
+            directory = directory.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+    env::set_var("CARGO", cargo);
+}
+
+#[test]
+fn test_link_workspace_crate() {
+    let directory = tempfile::tempdir().unwrap();
+    workspace(directory.path());
+
+    let program = "#include \"add.h\"\n\nint main() {\n    return add(1, 2);\n}\n";
+
+    run(Language::C, program)
+        .unwrap()
+        .link_workspace_crate("add-ffi")
+        .failure()
+        .code(3);
+
+    run(
+        Language::C,
+        &format!("#inline_c_rs WORKSPACE_CRATES: \"add-ffi\"\n\n{}", program),
+    )
+    .unwrap()
+    .failure()
+    .code(3);
+
+    let unknown = panic::catch_unwind(|| {
+        run(Language::C, program)
+            .unwrap()
+            .link_workspace_crate("sub-ffi");
+    })
+    .unwrap_err();
+
+    assert!(unknown
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("the crate `sub-ffi` is not in the workspace"));
+}